@@ -1,4 +1,87 @@
+use wallet::bip38::{
+    bip38_decrypt, bip38_encrypt, bip38_generate_from_intermediate, bip38_intermediate_code,
+    bip38_sweep, bip38_verify_confirmation, Bip38GeneratedKey, Bip38Key,
+};
+use wallet::bip85::{
+    bip85_base64_password, bip85_entropy, bip85_hex, bip85_mnemonic, bip85_wif, bip85_xprv,
+};
+use wallet::evm::abi::{
+    decode_log as abi_decode_log, decode_output as abi_decode_output,
+    encode_call as abi_encode_call, erc1155_safe_transfer_from, erc20_approve, erc20_transfer,
+    erc721_safe_transfer_from, find_abi_item as abi_find_item,
+};
+use wallet::evm::{
+    create2_address as evm_create2_address, create_address as evm_create_address,
+    decode_transaction as evm_decode_transaction, decrypt_keystore as evm_decrypt_keystore,
+    encrypt_keystore as evm_encrypt_keystore, ens_addr_call as evm_ens_addr_call,
+    ens_labelhash as evm_ens_labelhash, ens_namehash as evm_ens_namehash,
+    ens_normalize_ascii as evm_ens_normalize_ascii, is_checksum_address as evm_is_checksum_address,
+    is_valid_address as evm_is_valid_address,
+    pack_user_operation_v07 as evm_pack_user_operation_v07, personal_sign as evm_personal_sign,
+    pubkey_to_address as evm_pubkey_to_address, recover_authority as evm_recover_authority,
+    recover_personal_signer as evm_recover_personal_signer,
+    recover_typed_data_signer as evm_recover_typed_data_signer,
+    sign_access_list_transaction as evm_sign_access_list_transaction,
+    sign_authorization as evm_sign_authorization,
+    sign_eip1559_transaction as evm_sign_eip1559_transaction,
+    sign_legacy_transaction as evm_sign_legacy_transaction,
+    sign_set_code_transaction as evm_sign_set_code_transaction,
+    sign_typed_data as evm_sign_typed_data, sign_user_operation_v06 as evm_sign_user_operation_v06,
+    sign_user_operation_v07 as evm_sign_user_operation_v07,
+    to_checksum_address as evm_to_checksum_address,
+    user_operation_v06_hash as evm_user_operation_v06_hash,
+    user_operation_v07_hash as evm_user_operation_v07_hash,
+    verify_personal_signature as evm_verify_personal_signature, AccessListItem,
+    AccessListTransaction, Authorization, DecodedTransaction, Eip1559Transaction, KeystoreKdf,
+    LegacyTransaction, PackedUserOperation, SetCodeTransaction, SignedAuthorization,
+    SignedTransaction, UserOperationV06, UserOperationV07,
+};
+use wallet::fee_bump::{cpfp_child, rbf_bump_fee};
+use wallet::frost::{
+    frost_aggregate, frost_dkg_part1, frost_dkg_part2, frost_dkg_part3, frost_signing_package,
+    frost_trusted_dealer, FrostDkgRound1, FrostDkgRound2, FrostOutput, FrostSigner,
+};
 use wallet::hd_wallet::HDWallet;
-use wallet::signer::{schnorr_sign, ecdsa_sign, p2pkh_sign, p2tr_sign, Prevout};
+use wallet::miniscript::{miniscript_compile, MiniscriptType, MiniscriptWallet};
+use wallet::multisig_wallet::{
+    combine_psbts, MultisigPrevout, MultisigScriptType, MultisigWallet, SignedPsbt,
+};
+use wallet::musig2::{musig2_aggregate_xpub, musig2_key_agg, musig2_nonce_agg, Musig2Wallet};
+use wallet::signer::{
+    ecdsa_sign, ecdsa_sign_recoverable, p2pkh_sign, p2tr_sign, p2wpkh_sign, recover_pubkey,
+    schnorr_sign, Prevout,
+};
+use wallet::silent_payments::{
+    silent_payment_outputs, SilentPaymentInput, SilentPaymentOutput, SilentPaymentWallet,
+};
+use wallet::single_key_wallet::{decode_wif, encode_wif, SingleKeyWallet};
+use wallet::slip39::{
+    is_slip39_word, slip39_generate, slip39_recover, slip39_validate_share, Slip39Group,
+};
+use wallet::storage::decrypt_wallet_metadata;
+use wallet::timelock::{
+    inheritance_address, inheritance_script, inheritance_spend, timelock_address, timelock_script,
+    timelock_spend, TimelockScriptType, TimelockType,
+};
+
+use std::sync::Arc;
+
+/// `HDWallet::from_encrypted` as a function since constructors can't return `None`
+fn hd_wallet_from_encrypted(
+    encrypted: &str,
+    password: &str,
+    passphrase: Option<String>,
+) -> Option<Arc<HDWallet>> {
+    HDWallet::from_encrypted(encrypted, password, passphrase).map(Arc::new)
+}
+
+/// `HDWallet::from_slip39` as a function since constructors can't return `None`
+fn hd_wallet_from_slip39(
+    is_testnet: u8,
+    shares: Vec<String>,
+    passphrase: &str,
+) -> Option<Arc<HDWallet>> {
+    HDWallet::from_slip39(is_testnet, shares, passphrase).map(Arc::new)
+}
 
 uniffi_macros::include_scaffolding!("thing");
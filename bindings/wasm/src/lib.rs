@@ -1,5 +1,18 @@
+use wallet::bip38;
+use wallet::bip85;
+use wallet::evm;
+use wallet::fee_bump;
+use wallet::frost;
 use wallet::hd_wallet;
+use wallet::miniscript;
+use wallet::multisig_wallet;
+use wallet::musig2;
 use wallet::signer;
+use wallet::silent_payments;
+use wallet::single_key_wallet;
+use wallet::slip39;
+use wallet::storage;
+use wallet::timelock;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -17,6 +30,16 @@ pub fn ecdsa_sign(priv_hex: &str, digest_hex: &str) -> String {
     signer::ecdsa_sign(priv_hex, digest_hex)
 }
 
+#[wasm_bindgen]
+pub fn ecdsa_sign_recoverable(priv_hex: &str, digest_hex: &str) -> String {
+    signer::ecdsa_sign_recoverable(priv_hex, digest_hex)
+}
+
+#[wasm_bindgen]
+pub fn recover_pubkey(digest_hex: &str, sig_hex: &str) -> String {
+    signer::recover_pubkey(digest_hex, sig_hex)
+}
+
 #[wasm_bindgen]
 pub fn p2pkh_sign(address: &str, priv_hex: &str, tx_hex: &str) -> String {
     signer::p2pkh_sign(address, priv_hex, tx_hex)
@@ -36,58 +59,1955 @@ pub fn p2tr_sign(address: &str, priv_hex: &str, tx_hex: &str, tx_prevouts: Vec<P
 }
 
 #[wasm_bindgen]
-pub struct HDWallet {
-    inner: hd_wallet::HDWallet,
+pub fn p2wpkh_sign(
+    address: &str,
+    priv_hex: &str,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+) -> String {
+    signer::p2wpkh_sign(
+        address,
+        priv_hex,
+        tx_hex,
+        tx_prevouts
+            .into_iter()
+            .map(|prevout| prevout.inner)
+            .collect(),
+    )
 }
 
 #[wasm_bindgen]
-impl HDWallet {
+pub fn encode_wif(priv_hex: &str, is_testnet: u8, compressed: bool) -> String {
+    single_key_wallet::encode_wif(priv_hex, is_testnet, compressed)
+}
+
+#[wasm_bindgen]
+pub fn decode_wif(wif: &str) -> String {
+    single_key_wallet::decode_wif(wif)
+}
+
+#[wasm_bindgen]
+pub struct Bip38Key {
+    inner: bip38::Bip38Key,
+}
+
+#[wasm_bindgen]
+impl Bip38Key {
+    #[wasm_bindgen(getter)]
+    pub fn priv_hex(&self) -> String {
+        self.inner.priv_hex.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn compressed(&self) -> bool {
+        self.inner.compressed
+    }
+}
+
+#[wasm_bindgen]
+pub struct Bip38GeneratedKey {
+    inner: bip38::Bip38GeneratedKey,
+}
+
+#[wasm_bindgen]
+impl Bip38GeneratedKey {
+    #[wasm_bindgen(getter)]
+    pub fn encrypted_key(&self) -> String {
+        self.inner.encrypted_key.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn confirmation_code(&self) -> String {
+        self.inner.confirmation_code.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        self.inner.address.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn bip38_encrypt(priv_hex: &str, passphrase: &str, compressed: bool) -> String {
+    bip38::bip38_encrypt(priv_hex, passphrase, compressed)
+}
+
+#[wasm_bindgen]
+pub fn bip38_decrypt(encrypted_key: &str, passphrase: &str) -> Option<Bip38Key> {
+    bip38::bip38_decrypt(encrypted_key, passphrase).map(|inner| Bip38Key { inner })
+}
+
+#[wasm_bindgen]
+pub fn bip38_intermediate_code(
+    passphrase: &str,
+    lot: Option<u32>,
+    sequence: Option<u32>,
+) -> String {
+    bip38::bip38_intermediate_code(passphrase, lot, sequence)
+}
+
+#[wasm_bindgen]
+pub fn bip38_generate_from_intermediate(intermediate: &str, compressed: bool) -> Bip38GeneratedKey {
+    Bip38GeneratedKey {
+        inner: bip38::bip38_generate_from_intermediate(intermediate, compressed),
+    }
+}
+
+#[wasm_bindgen]
+pub fn bip38_verify_confirmation(confirmation_code: &str, passphrase: &str) -> Option<String> {
+    bip38::bip38_verify_confirmation(confirmation_code, passphrase)
+}
+
+#[wasm_bindgen]
+pub fn bip38_sweep(
+    encrypted_key: &str,
+    passphrase: &str,
+    prevouts: Vec<Prevout>,
+    to_address: &str,
+    fee_sat: u64,
+) -> Option<String> {
+    bip38::bip38_sweep(
+        encrypted_key,
+        passphrase,
+        prevouts.into_iter().map(|prevout| prevout.inner).collect(),
+        to_address,
+        fee_sat,
+    )
+}
+
+#[wasm_bindgen]
+pub fn bip85_entropy(master_priv: &str, path: &str) -> String {
+    bip85::bip85_entropy(master_priv, path)
+}
+
+#[wasm_bindgen]
+pub fn bip85_mnemonic(master_priv: &str, language_code: u32, words: u32, index: u32) -> String {
+    bip85::bip85_mnemonic(master_priv, language_code, words, index)
+}
+
+#[wasm_bindgen]
+pub fn bip85_wif(master_priv: &str, index: u32) -> String {
+    bip85::bip85_wif(master_priv, index)
+}
+
+#[wasm_bindgen]
+pub fn bip85_xprv(master_priv: &str, index: u32) -> String {
+    bip85::bip85_xprv(master_priv, index)
+}
+
+#[wasm_bindgen]
+pub fn bip85_hex(master_priv: &str, num_bytes: u32, index: u32) -> String {
+    bip85::bip85_hex(master_priv, num_bytes, index)
+}
+
+#[wasm_bindgen]
+pub fn bip85_base64_password(master_priv: &str, pwd_len: u32, index: u32) -> String {
+    bip85::bip85_base64_password(master_priv, pwd_len, index)
+}
+
+#[wasm_bindgen]
+pub struct Slip39Group {
+    inner: slip39::Slip39Group,
+}
+
+#[wasm_bindgen]
+impl Slip39Group {
     #[wasm_bindgen(constructor)]
-    pub fn new(is_testnet: u8, mnemonic_str: Option<String>) -> Self {
+    pub fn new(member_threshold: u8, member_count: u8) -> Self {
         Self {
-            inner: hd_wallet::HDWallet::new(is_testnet, mnemonic_str),
+            inner: slip39::Slip39Group {
+                member_threshold,
+                member_count,
+            },
         }
     }
+}
 
-    pub fn from_master_priv(master_priv: &str) -> Self {
+/// Shares of each group, as wasm-bindgen can't return nested arrays
+#[wasm_bindgen]
+pub struct Slip39Shares {
+    groups: Vec<Vec<String>>,
+}
+
+#[wasm_bindgen]
+impl Slip39Shares {
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn group(&self, index: usize) -> Vec<String> {
+        self.groups[index].clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn is_slip39_word(word: &str) -> bool {
+    slip39::is_slip39_word(word)
+}
+
+#[wasm_bindgen]
+pub fn slip39_validate_share(share: &str) -> bool {
+    slip39::slip39_validate_share(share)
+}
+
+#[wasm_bindgen]
+pub fn slip39_generate(
+    master_secret_hex: &str,
+    passphrase: &str,
+    group_threshold: u8,
+    groups: Vec<Slip39Group>,
+    iteration_exponent: u8,
+) -> Slip39Shares {
+    Slip39Shares {
+        groups: slip39::slip39_generate(
+            master_secret_hex,
+            passphrase,
+            group_threshold,
+            groups.into_iter().map(|group| group.inner).collect(),
+            iteration_exponent,
+        ),
+    }
+}
+
+#[wasm_bindgen]
+pub fn slip39_recover(shares: Vec<String>, passphrase: &str) -> Option<String> {
+    slip39::slip39_recover(shares, passphrase)
+}
+
+#[wasm_bindgen]
+pub struct LegacyTransaction {
+    inner: evm::LegacyTransaction,
+}
+
+#[wasm_bindgen]
+impl LegacyTransaction {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        nonce: u64,
+        gas_price: String,
+        gas_limit: u64,
+        to: Option<String>,
+        value: String,
+        data: String,
+        chain_id: u64,
+    ) -> Self {
         Self {
-            inner: hd_wallet::HDWallet::from_master_priv(master_priv),
+            inner: evm::LegacyTransaction {
+                nonce,
+                gas_price,
+                gas_limit,
+                to,
+                value,
+                data,
+                chain_id,
+            },
         }
     }
+}
 
-    pub fn export_mnemonic(&self) -> String {
-        self.inner.export_mnemonic()
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct AccessListItem {
+    inner: evm::AccessListItem,
+}
+
+#[wasm_bindgen]
+impl AccessListItem {
+    #[wasm_bindgen(constructor)]
+    pub fn new(address: String, storage_keys: Vec<String>) -> Self {
+        Self {
+            inner: evm::AccessListItem {
+                address,
+                storage_keys,
+            },
+        }
     }
 
-    pub fn export_master_priv(&self) -> String {
-        self.inner.export_master_priv()
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        self.inner.address.clone()
     }
 
-    pub fn evm_address(&self) -> String {
-        self.inner.evm_address()
+    #[wasm_bindgen(getter)]
+    pub fn storage_keys(&self) -> Vec<String> {
+        self.inner.storage_keys.clone()
     }
+}
 
-    pub fn bip44_address(&self) -> String {
-        self.inner.bip44_address()
+fn access_list_inner(access_list: Vec<AccessListItem>) -> Vec<evm::AccessListItem> {
+    access_list.into_iter().map(|item| item.inner).collect()
+}
+
+#[wasm_bindgen]
+pub struct AccessListTransaction {
+    inner: evm::AccessListTransaction,
+}
+
+#[wasm_bindgen]
+impl AccessListTransaction {
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        chain_id: u64,
+        nonce: u64,
+        gas_price: String,
+        gas_limit: u64,
+        to: Option<String>,
+        value: String,
+        data: String,
+        access_list: Vec<AccessListItem>,
+    ) -> Self {
+        Self {
+            inner: evm::AccessListTransaction {
+                chain_id,
+                nonce,
+                gas_price,
+                gas_limit,
+                to,
+                value,
+                data,
+                access_list: access_list_inner(access_list),
+            },
+        }
     }
+}
 
-    pub fn bip86_address(&self) -> String {
-        self.inner.bip86_address()
+#[wasm_bindgen]
+pub struct Eip1559Transaction {
+    inner: evm::Eip1559Transaction,
+}
+
+#[wasm_bindgen]
+impl Eip1559Transaction {
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        chain_id: u64,
+        nonce: u64,
+        max_priority_fee_per_gas: String,
+        max_fee_per_gas: String,
+        gas_limit: u64,
+        to: Option<String>,
+        value: String,
+        data: String,
+        access_list: Vec<AccessListItem>,
+    ) -> Self {
+        Self {
+            inner: evm::Eip1559Transaction {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas_limit,
+                to,
+                value,
+                data,
+                access_list: access_list_inner(access_list),
+            },
+        }
     }
+}
 
-    pub fn evm_priv_hex(&self) -> String {
-        self.inner.evm_priv_hex()
+#[wasm_bindgen]
+pub struct Authorization {
+    inner: evm::Authorization,
+}
+
+#[wasm_bindgen]
+impl Authorization {
+    #[wasm_bindgen(constructor)]
+    pub fn new(chain_id: u64, address: String, nonce: u64) -> Self {
+        Self {
+            inner: evm::Authorization {
+                chain_id,
+                address,
+                nonce,
+            },
+        }
     }
+}
 
-    pub fn bip44_priv_hex(&self) -> String {
-        self.inner.bip44_priv_hex()
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct SignedAuthorization {
+    inner: evm::SignedAuthorization,
+}
+
+#[wasm_bindgen]
+impl SignedAuthorization {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        chain_id: u64,
+        address: String,
+        nonce: u64,
+        y_parity: u8,
+        r: String,
+        s: String,
+    ) -> Self {
+        Self {
+            inner: evm::SignedAuthorization {
+                chain_id,
+                address,
+                nonce,
+                y_parity,
+                r,
+                s,
+            },
+        }
     }
 
-    pub fn bip86_priv_hex(&self) -> String {
-        self.inner.bip86_priv_hex()
+    #[wasm_bindgen(getter)]
+    pub fn chain_id(&self) -> u64 {
+        self.inner.chain_id
     }
 
-    pub fn bip86_tweaked_priv_hex(&self, merkle_root: Option<String>) -> String {
-        self.inner.bip86_tweaked_priv_hex(merkle_root)
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        self.inner.address.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nonce(&self) -> u64 {
+        self.inner.nonce
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y_parity(&self) -> u8 {
+        self.inner.y_parity
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn r(&self) -> String {
+        self.inner.r.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn s(&self) -> String {
+        self.inner.s.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct SetCodeTransaction {
+    inner: evm::SetCodeTransaction,
+}
+
+#[wasm_bindgen]
+impl SetCodeTransaction {
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        chain_id: u64,
+        nonce: u64,
+        max_priority_fee_per_gas: String,
+        max_fee_per_gas: String,
+        gas_limit: u64,
+        to: String,
+        value: String,
+        data: String,
+        access_list: Vec<AccessListItem>,
+        authorization_list: Vec<SignedAuthorization>,
+    ) -> Self {
+        Self {
+            inner: evm::SetCodeTransaction {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas_limit,
+                to,
+                value,
+                data,
+                access_list: access_list_inner(access_list),
+                authorization_list: authorization_list
+                    .into_iter()
+                    .map(|auth| auth.inner)
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct SignedTransaction {
+    inner: evm::SignedTransaction,
+}
+
+#[wasm_bindgen]
+impl SignedTransaction {
+    #[wasm_bindgen(getter)]
+    pub fn raw_tx_hex(&self) -> String {
+        self.inner.raw_tx_hex.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tx_hash(&self) -> String {
+        self.inner.tx_hash.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_sign_legacy_transaction(priv_hex: &str, tx: LegacyTransaction) -> SignedTransaction {
+    SignedTransaction {
+        inner: evm::sign_legacy_transaction(priv_hex, tx.inner),
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_sign_access_list_transaction(
+    priv_hex: &str,
+    tx: AccessListTransaction,
+) -> SignedTransaction {
+    SignedTransaction {
+        inner: evm::sign_access_list_transaction(priv_hex, tx.inner),
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_sign_eip1559_transaction(priv_hex: &str, tx: Eip1559Transaction) -> SignedTransaction {
+    SignedTransaction {
+        inner: evm::sign_eip1559_transaction(priv_hex, tx.inner),
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_sign_set_code_transaction(priv_hex: &str, tx: SetCodeTransaction) -> SignedTransaction {
+    SignedTransaction {
+        inner: evm::sign_set_code_transaction(priv_hex, tx.inner),
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_sign_authorization(priv_hex: &str, auth: Authorization) -> SignedAuthorization {
+    SignedAuthorization {
+        inner: evm::sign_authorization(priv_hex, auth.inner),
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_recover_authority(auth: &SignedAuthorization) -> Option<String> {
+    evm::recover_authority(&auth.inner)
+}
+
+#[wasm_bindgen]
+pub struct DecodedTransaction {
+    inner: evm::DecodedTransaction,
+}
+
+#[wasm_bindgen]
+impl DecodedTransaction {
+    #[wasm_bindgen(getter)]
+    pub fn tx_type(&self) -> u8 {
+        self.inner.tx_type
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn chain_id(&self) -> u64 {
+        self.inner.chain_id
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nonce(&self) -> u64 {
+        self.inner.nonce
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gas_price(&self) -> Option<String> {
+        self.inner.gas_price.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_priority_fee_per_gas(&self) -> Option<String> {
+        self.inner.max_priority_fee_per_gas.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_fee_per_gas(&self) -> Option<String> {
+        self.inner.max_fee_per_gas.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gas_limit(&self) -> u64 {
+        self.inner.gas_limit
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn to(&self) -> Option<String> {
+        self.inner.to.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> String {
+        self.inner.value.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> String {
+        self.inner.data.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn access_list(&self) -> Vec<AccessListItem> {
+        self.inner
+            .access_list
+            .iter()
+            .map(|item| AccessListItem {
+                inner: item.clone(),
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn authorization_list(&self) -> Vec<SignedAuthorization> {
+        self.inner
+            .authorization_list
+            .iter()
+            .map(|auth| SignedAuthorization {
+                inner: auth.clone(),
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y_parity(&self) -> u8 {
+        self.inner.y_parity
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn r(&self) -> String {
+        self.inner.r.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn s(&self) -> String {
+        self.inner.s.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn from(&self) -> String {
+        self.inner.from.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tx_hash(&self) -> String {
+        self.inner.tx_hash.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_decode_transaction(raw_tx_hex: &str) -> Option<DecodedTransaction> {
+    evm::decode_transaction(raw_tx_hex).map(|inner| DecodedTransaction { inner })
+}
+
+#[wasm_bindgen]
+pub fn evm_personal_sign(priv_hex: &str, message: &str) -> String {
+    evm::personal_sign(priv_hex, message)
+}
+
+#[wasm_bindgen]
+pub fn evm_recover_personal_signer(message: &str, signature_hex: &str) -> Option<String> {
+    evm::recover_personal_signer(message, signature_hex)
+}
+
+#[wasm_bindgen]
+pub fn evm_verify_personal_signature(address: &str, message: &str, signature_hex: &str) -> bool {
+    evm::verify_personal_signature(address, message, signature_hex)
+}
+
+#[wasm_bindgen]
+pub fn evm_sign_typed_data(priv_hex: &str, typed_data_json: &str) -> String {
+    evm::sign_typed_data(priv_hex, typed_data_json)
+}
+
+#[wasm_bindgen]
+pub fn evm_recover_typed_data_signer(typed_data_json: &str, signature_hex: &str) -> Option<String> {
+    evm::recover_typed_data_signer(typed_data_json, signature_hex)
+}
+
+#[wasm_bindgen]
+pub fn evm_to_checksum_address(address: &str, chain_id: Option<u64>) -> Option<String> {
+    evm::to_checksum_address(address, chain_id)
+}
+
+#[wasm_bindgen]
+pub fn evm_is_valid_address(address: &str, chain_id: Option<u64>) -> bool {
+    evm::is_valid_address(address, chain_id)
+}
+
+#[wasm_bindgen]
+pub fn evm_is_checksum_address(address: &str, chain_id: Option<u64>) -> bool {
+    evm::is_checksum_address(address, chain_id)
+}
+
+#[wasm_bindgen]
+pub fn evm_pubkey_to_address(pubkey_hex: &str) -> String {
+    evm::pubkey_to_address(pubkey_hex)
+}
+
+#[wasm_bindgen]
+pub fn evm_create_address(sender: &str, nonce: u64) -> String {
+    evm::create_address(sender, nonce)
+}
+
+#[wasm_bindgen]
+pub fn evm_create2_address(deployer: &str, salt_hex: &str, init_code_hash_hex: &str) -> String {
+    evm::create2_address(deployer, salt_hex, init_code_hash_hex)
+}
+
+#[wasm_bindgen]
+pub fn evm_ens_normalize_ascii(name: &str) -> Option<String> {
+    evm::ens_normalize_ascii(name)
+}
+
+#[wasm_bindgen]
+pub fn evm_ens_namehash(name: &str) -> Option<String> {
+    evm::ens_namehash(name)
+}
+
+#[wasm_bindgen]
+pub fn evm_ens_labelhash(label: &str) -> Option<String> {
+    evm::ens_labelhash(label)
+}
+
+#[wasm_bindgen]
+pub fn evm_ens_addr_call(name: &str) -> Option<String> {
+    evm::ens_addr_call(name)
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum KeystoreKdf {
+    Scrypt,
+    ScryptLight,
+    Pbkdf2,
+}
+
+impl From<KeystoreKdf> for evm::KeystoreKdf {
+    fn from(kdf: KeystoreKdf) -> Self {
+        match kdf {
+            KeystoreKdf::Scrypt => evm::KeystoreKdf::Scrypt,
+            KeystoreKdf::ScryptLight => evm::KeystoreKdf::ScryptLight,
+            KeystoreKdf::Pbkdf2 => evm::KeystoreKdf::Pbkdf2,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_encrypt_keystore(priv_hex: &str, password: &str, kdf: KeystoreKdf) -> String {
+    evm::encrypt_keystore(priv_hex, password, kdf.into())
+}
+
+#[wasm_bindgen]
+pub fn evm_decrypt_keystore(keystore_json: &str, password: &str) -> Option<String> {
+    evm::decrypt_keystore(keystore_json, password)
+}
+
+#[wasm_bindgen]
+pub struct UserOperationV06 {
+    inner: evm::UserOperationV06,
+}
+
+#[wasm_bindgen]
+impl UserOperationV06 {
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        sender: String,
+        nonce: String,
+        init_code: String,
+        call_data: String,
+        call_gas_limit: String,
+        verification_gas_limit: String,
+        pre_verification_gas: String,
+        max_fee_per_gas: String,
+        max_priority_fee_per_gas: String,
+        paymaster_and_data: String,
+    ) -> Self {
+        Self {
+            inner: evm::UserOperationV06 {
+                sender,
+                nonce,
+                init_code,
+                call_data,
+                call_gas_limit,
+                verification_gas_limit,
+                pre_verification_gas,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                paymaster_and_data,
+            },
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct UserOperationV07 {
+    inner: evm::UserOperationV07,
+}
+
+#[wasm_bindgen]
+impl UserOperationV07 {
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        sender: String,
+        nonce: String,
+        factory: Option<String>,
+        factory_data: String,
+        call_data: String,
+        call_gas_limit: String,
+        verification_gas_limit: String,
+        pre_verification_gas: String,
+        max_fee_per_gas: String,
+        max_priority_fee_per_gas: String,
+        paymaster: Option<String>,
+        paymaster_verification_gas_limit: String,
+        paymaster_post_op_gas_limit: String,
+        paymaster_data: String,
+    ) -> Self {
+        Self {
+            inner: evm::UserOperationV07 {
+                sender,
+                nonce,
+                factory,
+                factory_data,
+                call_data,
+                call_gas_limit,
+                verification_gas_limit,
+                pre_verification_gas,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                paymaster,
+                paymaster_verification_gas_limit,
+                paymaster_post_op_gas_limit,
+                paymaster_data,
+            },
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct PackedUserOperation {
+    inner: evm::PackedUserOperation,
+}
+
+#[wasm_bindgen]
+impl PackedUserOperation {
+    #[wasm_bindgen(getter)]
+    pub fn sender(&self) -> String {
+        self.inner.sender.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nonce(&self) -> String {
+        self.inner.nonce.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn init_code(&self) -> String {
+        self.inner.init_code.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn call_data(&self) -> String {
+        self.inner.call_data.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn account_gas_limits(&self) -> String {
+        self.inner.account_gas_limits.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pre_verification_gas(&self) -> String {
+        self.inner.pre_verification_gas.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gas_fees(&self) -> String {
+        self.inner.gas_fees.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn paymaster_and_data(&self) -> String {
+        self.inner.paymaster_and_data.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_user_operation_v06_hash(
+    op: &UserOperationV06,
+    entry_point: &str,
+    chain_id: u64,
+) -> String {
+    evm::user_operation_v06_hash(&op.inner, entry_point, chain_id)
+}
+
+#[wasm_bindgen]
+pub fn evm_user_operation_v07_hash(
+    op: &UserOperationV07,
+    entry_point: &str,
+    chain_id: u64,
+) -> String {
+    evm::user_operation_v07_hash(&op.inner, entry_point, chain_id)
+}
+
+#[wasm_bindgen]
+pub fn evm_pack_user_operation_v07(op: &UserOperationV07) -> PackedUserOperation {
+    PackedUserOperation {
+        inner: evm::pack_user_operation_v07(&op.inner),
+    }
+}
+
+#[wasm_bindgen]
+pub fn evm_sign_user_operation_v06(
+    priv_hex: &str,
+    op: &UserOperationV06,
+    entry_point: &str,
+    chain_id: u64,
+) -> String {
+    evm::sign_user_operation_v06(priv_hex, &op.inner, entry_point, chain_id)
+}
+
+#[wasm_bindgen]
+pub fn evm_sign_user_operation_v07(
+    priv_hex: &str,
+    op: &UserOperationV07,
+    entry_point: &str,
+    chain_id: u64,
+) -> String {
+    evm::sign_user_operation_v07(priv_hex, &op.inner, entry_point, chain_id)
+}
+
+#[wasm_bindgen]
+pub fn abi_find_item(abi_json: &str, name: &str) -> String {
+    evm::abi::find_abi_item(abi_json, name)
+}
+
+#[wasm_bindgen]
+pub fn abi_encode_call(function: &str, args_json: &str) -> Option<String> {
+    evm::abi::encode_call(function, args_json)
+}
+
+#[wasm_bindgen]
+pub fn abi_decode_output(function: &str, data_hex: &str) -> Option<String> {
+    evm::abi::decode_output(function, data_hex)
+}
+
+#[wasm_bindgen]
+pub fn abi_decode_log(event: &str, topics: Vec<String>, data_hex: &str) -> Option<String> {
+    evm::abi::decode_log(event, topics, data_hex)
+}
+
+#[wasm_bindgen]
+pub fn erc20_transfer(to: &str, amount: &str) -> Option<String> {
+    evm::abi::erc20_transfer(to, amount)
+}
+
+#[wasm_bindgen]
+pub fn erc20_approve(spender: &str, amount: &str) -> Option<String> {
+    evm::abi::erc20_approve(spender, amount)
+}
+
+#[wasm_bindgen]
+pub fn erc721_safe_transfer_from(from: &str, to: &str, token_id: &str) -> Option<String> {
+    evm::abi::erc721_safe_transfer_from(from, to, token_id)
+}
+
+#[wasm_bindgen]
+pub fn erc1155_safe_transfer_from(
+    from: &str,
+    to: &str,
+    id: &str,
+    amount: &str,
+    data_hex: &str,
+) -> Option<String> {
+    evm::abi::erc1155_safe_transfer_from(from, to, id, amount, data_hex)
+}
+
+#[wasm_bindgen]
+pub fn decrypt_wallet_metadata(encrypted: &str, password: &str) -> Option<String> {
+    storage::decrypt_wallet_metadata(encrypted, password)
+}
+
+#[wasm_bindgen]
+pub struct HDWallet {
+    inner: hd_wallet::HDWallet,
+}
+
+#[wasm_bindgen]
+impl HDWallet {
+    #[wasm_bindgen(constructor)]
+    pub fn new(is_testnet: u8, mnemonic_str: Option<String>) -> Self {
+        Self {
+            inner: hd_wallet::HDWallet::new(is_testnet, mnemonic_str),
+        }
+    }
+
+    pub fn new_with_passphrase(
+        is_testnet: u8,
+        mnemonic_str: Option<String>,
+        passphrase: &str,
+    ) -> Self {
+        Self {
+            inner: hd_wallet::HDWallet::new_with_passphrase(is_testnet, mnemonic_str, passphrase),
+        }
+    }
+
+    pub fn from_master_priv(master_priv: &str) -> Self {
+        Self {
+            inner: hd_wallet::HDWallet::from_master_priv(master_priv),
+        }
+    }
+
+    pub fn from_encrypted(
+        encrypted: &str,
+        password: &str,
+        passphrase: Option<String>,
+    ) -> Option<HDWallet> {
+        hd_wallet::HDWallet::from_encrypted(encrypted, password, passphrase)
+            .map(|inner| Self { inner })
+    }
+
+    pub fn new_slip39(is_testnet: u8) -> Self {
+        Self {
+            inner: hd_wallet::HDWallet::new_slip39(is_testnet),
+        }
+    }
+
+    pub fn from_slip39(is_testnet: u8, shares: Vec<String>, passphrase: &str) -> Option<HDWallet> {
+        hd_wallet::HDWallet::from_slip39(is_testnet, shares, passphrase).map(|inner| Self { inner })
+    }
+
+    pub fn export_mnemonic(&self) -> String {
+        self.inner.export_mnemonic()
+    }
+
+    pub fn export_master_priv(&self) -> String {
+        self.inner.export_master_priv()
+    }
+
+    pub fn encrypt(&self, password: &str, metadata: &str) -> String {
+        self.inner.encrypt(password, metadata)
+    }
+
+    pub fn slip39_shares(
+        &self,
+        passphrase: &str,
+        group_threshold: u8,
+        groups: Vec<Slip39Group>,
+        iteration_exponent: u8,
+    ) -> Option<Slip39Shares> {
+        let groups = self.inner.slip39_shares(
+            passphrase,
+            group_threshold,
+            groups.into_iter().map(|group| group.inner).collect(),
+            iteration_exponent,
+        )?;
+        Some(Slip39Shares { groups })
+    }
+
+    pub fn evm_address(&self) -> String {
+        self.inner.evm_address()
+    }
+
+    pub fn bip44_address(&self) -> String {
+        self.inner.bip44_address()
+    }
+
+    pub fn bip84_address(&self) -> String {
+        self.inner.bip84_address()
+    }
+
+    pub fn bip86_address(&self) -> String {
+        self.inner.bip86_address()
+    }
+
+    pub fn bip48_xpub(&self, script_type: MultisigScriptType) -> String {
+        self.inner.bip48_xpub(script_type.into())
+    }
+
+    pub fn sweep_bip38(
+        &self,
+        encrypted_key: &str,
+        passphrase: &str,
+        prevouts: Vec<Prevout>,
+        fee_sat: u64,
+    ) -> Option<String> {
+        self.inner.sweep_bip38(
+            encrypted_key,
+            passphrase,
+            prevouts.into_iter().map(|prevout| prevout.inner).collect(),
+            fee_sat,
+        )
+    }
+
+    pub fn evm_priv_hex(&self) -> String {
+        self.inner.evm_priv_hex()
+    }
+
+    pub fn evm_personal_sign(&self, message: &str) -> String {
+        self.inner.evm_personal_sign(message)
+    }
+
+    pub fn evm_sign_typed_data(&self, typed_data_json: &str) -> String {
+        self.inner.evm_sign_typed_data(typed_data_json)
+    }
+
+    pub fn evm_sign_authorization(&self, auth: Authorization) -> SignedAuthorization {
+        SignedAuthorization {
+            inner: self.inner.evm_sign_authorization(auth.inner),
+        }
+    }
+
+    pub fn evm_export_keystore(&self, password: &str, kdf: KeystoreKdf) -> String {
+        self.inner.evm_export_keystore(password, kdf.into())
+    }
+
+    pub fn evm_sign_user_operation_v06(
+        &self,
+        op: &UserOperationV06,
+        entry_point: &str,
+        chain_id: u64,
+    ) -> String {
+        self.inner
+            .evm_sign_user_operation_v06(&op.inner, entry_point, chain_id)
+    }
+
+    pub fn evm_sign_user_operation_v07(
+        &self,
+        op: &UserOperationV07,
+        entry_point: &str,
+        chain_id: u64,
+    ) -> String {
+        self.inner
+            .evm_sign_user_operation_v07(&op.inner, entry_point, chain_id)
+    }
+
+    pub fn bip44_priv_hex(&self) -> String {
+        self.inner.bip44_priv_hex()
+    }
+
+    pub fn bip84_priv_hex(&self) -> String {
+        self.inner.bip84_priv_hex()
+    }
+
+    pub fn bip86_priv_hex(&self) -> String {
+        self.inner.bip86_priv_hex()
+    }
+
+    pub fn musig2_pubkey(&self) -> String {
+        self.inner.musig2_pubkey()
+    }
+
+    pub fn bip86_tweaked_priv_hex(&self, merkle_root: Option<String>) -> String {
+        self.inner.bip86_tweaked_priv_hex(merkle_root)
+    }
+
+    pub fn bip44_wif(&self) -> String {
+        self.inner.bip44_wif()
+    }
+
+    pub fn bip84_wif(&self) -> String {
+        self.inner.bip84_wif()
+    }
+
+    pub fn bip86_wif(&self) -> String {
+        self.inner.bip86_wif()
+    }
+
+    pub fn bip85_mnemonic(&self, language_code: u32, words: u32, index: u32) -> String {
+        self.inner.bip85_mnemonic(language_code, words, index)
+    }
+
+    pub fn bip85_wif(&self, index: u32) -> String {
+        self.inner.bip85_wif(index)
+    }
+
+    pub fn bip85_xprv(&self, index: u32) -> String {
+        self.inner.bip85_xprv(index)
+    }
+
+    pub fn bip85_hex(&self, num_bytes: u32, index: u32) -> String {
+        self.inner.bip85_hex(num_bytes, index)
+    }
+
+    pub fn bip85_base64_password(&self, pwd_len: u32, index: u32) -> String {
+        self.inner.bip85_base64_password(pwd_len, index)
+    }
+}
+
+#[wasm_bindgen]
+pub struct SingleKeyWallet {
+    inner: single_key_wallet::SingleKeyWallet,
+}
+
+#[wasm_bindgen]
+impl SingleKeyWallet {
+    pub fn from_wif(wif: &str) -> Self {
+        Self {
+            inner: single_key_wallet::SingleKeyWallet::from_wif(wif),
+        }
+    }
+
+    pub fn from_priv_hex(priv_hex: &str, is_testnet: u8) -> Self {
+        Self {
+            inner: single_key_wallet::SingleKeyWallet::from_priv_hex(priv_hex, is_testnet),
+        }
+    }
+
+    pub fn export_wif(&self) -> String {
+        self.inner.export_wif()
+    }
+
+    pub fn priv_hex(&self) -> String {
+        self.inner.priv_hex()
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.inner.is_compressed()
+    }
+
+    pub fn p2pkh_address(&self) -> String {
+        self.inner.p2pkh_address()
+    }
+
+    pub fn p2wpkh_address(&self) -> String {
+        self.inner.p2wpkh_address()
+    }
+
+    pub fn p2tr_address(&self) -> String {
+        self.inner.p2tr_address()
+    }
+
+    pub fn evm_address(&self) -> String {
+        self.inner.evm_address()
+    }
+
+    pub fn tweaked_priv_hex(&self, merkle_root: Option<String>) -> String {
+        self.inner.tweaked_priv_hex(merkle_root)
+    }
+
+    pub fn p2pkh_sign(&self, tx_hex: &str) -> String {
+        self.inner.p2pkh_sign(tx_hex)
+    }
+
+    pub fn p2wpkh_sign(&self, tx_hex: &str, tx_prevouts: Vec<Prevout>) -> String {
+        self.inner.p2wpkh_sign(
+            tx_hex,
+            tx_prevouts
+                .into_iter()
+                .map(|prevout| prevout.inner)
+                .collect(),
+        )
+    }
+
+    pub fn p2tr_sign(&self, tx_hex: &str, tx_prevouts: Vec<Prevout>) -> String {
+        self.inner.p2tr_sign(
+            tx_hex,
+            tx_prevouts
+                .into_iter()
+                .map(|prevout| prevout.inner)
+                .collect(),
+        )
+    }
+}
+
+#[wasm_bindgen]
+pub enum MultisigScriptType {
+    P2shP2wsh,
+    P2wsh,
+    P2tr,
+}
+
+impl From<MultisigScriptType> for multisig_wallet::MultisigScriptType {
+    fn from(script_type: MultisigScriptType) -> Self {
+        match script_type {
+            MultisigScriptType::P2shP2wsh => multisig_wallet::MultisigScriptType::P2shP2wsh,
+            MultisigScriptType::P2wsh => multisig_wallet::MultisigScriptType::P2wsh,
+            MultisigScriptType::P2tr => multisig_wallet::MultisigScriptType::P2tr,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct MultisigPrevout {
+    inner: multisig_wallet::MultisigPrevout,
+}
+
+#[wasm_bindgen]
+impl MultisigPrevout {
+    #[wasm_bindgen(constructor)]
+    pub fn new(txid: String, vout: u32, amount: f64, is_change: bool, index: u32) -> Self {
+        Self {
+            inner: multisig_wallet::MultisigPrevout {
+                txid,
+                vout,
+                amount,
+                is_change,
+                index,
+            },
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct SignedPsbt {
+    inner: multisig_wallet::SignedPsbt,
+}
+
+#[wasm_bindgen]
+impl SignedPsbt {
+    #[wasm_bindgen(getter)]
+    pub fn psbt(&self) -> String {
+        self.inner.psbt.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn signed_inputs(&self) -> Vec<u32> {
+        self.inner.signed_inputs.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn errors(&self) -> Vec<String> {
+        self.inner.errors.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn combine_psbts(psbts: Vec<String>) -> String {
+    multisig_wallet::combine_psbts(psbts)
+}
+
+#[wasm_bindgen]
+pub struct MultisigWallet {
+    inner: multisig_wallet::MultisigWallet,
+}
+
+#[wasm_bindgen]
+impl MultisigWallet {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        wallet: &HDWallet,
+        threshold: u8,
+        cosigner_xpubs: Vec<String>,
+        script_type: MultisigScriptType,
+    ) -> Self {
+        Self {
+            inner: multisig_wallet::MultisigWallet::new(
+                &wallet.inner,
+                threshold,
+                cosigner_xpubs,
+                script_type.into(),
+            ),
+        }
+    }
+
+    pub fn new_with_internal_key(
+        wallet: &HDWallet,
+        threshold: u8,
+        cosigner_xpubs: Vec<String>,
+        internal_key: Option<String>,
+    ) -> Self {
+        Self {
+            inner: multisig_wallet::MultisigWallet::new_with_internal_key(
+                &wallet.inner,
+                threshold,
+                cosigner_xpubs,
+                internal_key,
+            ),
+        }
+    }
+
+    pub fn descriptor(&self, is_change: bool) -> String {
+        self.inner.descriptor(is_change)
+    }
+
+    pub fn address(&self, is_change: bool, index: u32) -> String {
+        self.inner.address(is_change, index)
+    }
+
+    pub fn create_psbt(&self, tx_hex: &str, prevouts: Vec<MultisigPrevout>) -> String {
+        self.inner.create_psbt(
+            tx_hex,
+            prevouts.into_iter().map(|prevout| prevout.inner).collect(),
+        )
+    }
+
+    pub fn sign_psbt(&self, psbt: &str) -> SignedPsbt {
+        SignedPsbt {
+            inner: self.inner.sign_psbt(psbt),
+        }
+    }
+
+    pub fn finalize_psbt(&self, psbt: &str) -> String {
+        self.inner.finalize_psbt(psbt)
+    }
+}
+
+#[wasm_bindgen]
+pub enum MiniscriptType {
+    Wsh,
+    Tr,
+}
+
+impl From<MiniscriptType> for miniscript::MiniscriptType {
+    fn from(script_type: MiniscriptType) -> Self {
+        match script_type {
+            MiniscriptType::Wsh => miniscript::MiniscriptType::Wsh,
+            MiniscriptType::Tr => miniscript::MiniscriptType::Tr,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn miniscript_compile(policy: &str, script_type: MiniscriptType) -> String {
+    miniscript::miniscript_compile(policy, script_type.into())
+}
+
+#[wasm_bindgen]
+pub struct MiniscriptWallet {
+    inner: miniscript::MiniscriptWallet,
+}
+
+#[wasm_bindgen]
+impl MiniscriptWallet {
+    #[wasm_bindgen(constructor)]
+    pub fn new(wallet: &HDWallet, policy: &str, script_type: MiniscriptType) -> Self {
+        Self {
+            inner: miniscript::MiniscriptWallet::new(&wallet.inner, policy, script_type.into()),
+        }
+    }
+
+    pub fn descriptor(&self, is_change: bool) -> String {
+        self.inner.descriptor(is_change)
+    }
+
+    pub fn address(&self, is_change: bool, index: u32) -> String {
+        self.inner.address(is_change, index)
+    }
+
+    pub fn create_psbt(&self, tx_hex: &str, prevouts: Vec<MultisigPrevout>) -> String {
+        self.inner.create_psbt(
+            tx_hex,
+            prevouts.into_iter().map(|prevout| prevout.inner).collect(),
+        )
+    }
+
+    pub fn sign_psbt(&self, psbt: &str) -> SignedPsbt {
+        SignedPsbt {
+            inner: self.inner.sign_psbt(psbt),
+        }
+    }
+
+    pub fn finalize_psbt(&self, psbt: &str, preimages: Vec<String>) -> Option<String> {
+        self.inner.finalize_psbt(psbt, preimages)
+    }
+}
+
+#[wasm_bindgen]
+pub fn musig2_key_agg(pubkeys: Vec<String>) -> String {
+    musig2::musig2_key_agg(pubkeys)
+}
+
+#[wasm_bindgen]
+pub fn musig2_aggregate_xpub(pubkeys: Vec<String>, is_testnet: u8) -> String {
+    musig2::musig2_aggregate_xpub(pubkeys, is_testnet)
+}
+
+#[wasm_bindgen]
+pub fn musig2_nonce_agg(pubnonces: Vec<String>) -> String {
+    musig2::musig2_nonce_agg(pubnonces)
+}
+
+#[wasm_bindgen]
+pub struct Musig2Wallet {
+    inner: musig2::Musig2Wallet,
+}
+
+#[wasm_bindgen]
+impl Musig2Wallet {
+    #[wasm_bindgen(constructor)]
+    pub fn new(wallet: &HDWallet, cosigner_pubkeys: Vec<String>) -> Self {
+        Self {
+            inner: musig2::Musig2Wallet::new(&wallet.inner, cosigner_pubkeys),
+        }
+    }
+
+    pub fn aggregate_xpub(&self) -> String {
+        self.inner.aggregate_xpub()
+    }
+
+    pub fn descriptor(&self, is_change: bool) -> String {
+        self.inner.descriptor(is_change)
+    }
+
+    pub fn address(&self, is_change: bool, index: u32) -> String {
+        self.inner.address(is_change, index)
+    }
+
+    pub fn create_psbt(&self, tx_hex: &str, prevouts: Vec<MultisigPrevout>) -> String {
+        self.inner.create_psbt(
+            tx_hex,
+            prevouts.into_iter().map(|prevout| prevout.inner).collect(),
+        )
+    }
+
+    pub fn nonce_psbt(&self, psbt: &str) -> String {
+        self.inner.nonce_psbt(psbt)
+    }
+
+    pub fn sign_psbt(&self, psbt: &str) -> String {
+        self.inner.sign_psbt(psbt)
+    }
+
+    pub fn finalize_psbt(&self, psbt: &str) -> String {
+        self.inner.finalize_psbt(psbt)
+    }
+}
+
+#[wasm_bindgen]
+pub fn frost_trusted_dealer(
+    threshold: u16,
+    max_signers: u16,
+    secret_hex: Option<String>,
+) -> Vec<String> {
+    frost::frost_trusted_dealer(threshold, max_signers, secret_hex)
+}
+
+#[wasm_bindgen]
+pub struct FrostDkgRound1 {
+    inner: frost::FrostDkgRound1,
+}
+
+#[wasm_bindgen]
+impl FrostDkgRound1 {
+    #[wasm_bindgen(getter)]
+    pub fn secret_package(&self) -> String {
+        self.inner.secret_package.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn package(&self) -> String {
+        self.inner.package.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct FrostDkgRound2 {
+    inner: frost::FrostDkgRound2,
+}
+
+#[wasm_bindgen]
+impl FrostDkgRound2 {
+    #[wasm_bindgen(getter)]
+    pub fn secret_package(&self) -> Option<String> {
+        self.inner.secret_package.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn packages(&self) -> Vec<String> {
+        self.inner.packages.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn culprits(&self) -> Vec<u16> {
+        self.inner.culprits.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn errors(&self) -> Vec<String> {
+        self.inner.errors.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct FrostOutput {
+    inner: frost::FrostOutput,
+}
+
+#[wasm_bindgen]
+impl FrostOutput {
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> Option<String> {
+        self.inner.value.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn culprits(&self) -> Vec<u16> {
+        self.inner.culprits.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn errors(&self) -> Vec<String> {
+        self.inner.errors.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn frost_dkg_part1(identifier: u16, threshold: u16, max_signers: u16) -> FrostDkgRound1 {
+    FrostDkgRound1 {
+        inner: frost::frost_dkg_part1(identifier, threshold, max_signers),
+    }
+}
+
+#[wasm_bindgen]
+pub fn frost_dkg_part2(secret_package: &str, round1_packages: Vec<String>) -> FrostDkgRound2 {
+    FrostDkgRound2 {
+        inner: frost::frost_dkg_part2(secret_package, round1_packages),
+    }
+}
+
+#[wasm_bindgen]
+pub fn frost_dkg_part3(
+    secret_package: &str,
+    round1_packages: Vec<String>,
+    round2_packages: Vec<String>,
+) -> FrostOutput {
+    FrostOutput {
+        inner: frost::frost_dkg_part3(secret_package, round1_packages, round2_packages),
+    }
+}
+
+#[wasm_bindgen]
+pub fn frost_signing_package(
+    commitments: Vec<String>,
+    message_hex: &str,
+    taproot_tweak: bool,
+) -> FrostOutput {
+    FrostOutput {
+        inner: frost::frost_signing_package(commitments, message_hex, taproot_tweak),
+    }
+}
+
+#[wasm_bindgen]
+pub fn frost_aggregate(
+    signing_package: &str,
+    signature_shares: Vec<String>,
+    public_key_package: &str,
+) -> FrostOutput {
+    FrostOutput {
+        inner: frost::frost_aggregate(signing_package, signature_shares, public_key_package),
+    }
+}
+
+#[wasm_bindgen]
+pub struct FrostSigner {
+    inner: frost::FrostSigner,
+}
+
+#[wasm_bindgen]
+impl FrostSigner {
+    #[wasm_bindgen(constructor)]
+    pub fn new(key_share: &str) -> Self {
+        Self {
+            inner: frost::FrostSigner::new(key_share),
+        }
+    }
+
+    pub fn identifier(&self) -> u16 {
+        self.inner.identifier()
+    }
+
+    pub fn public_key_package(&self) -> String {
+        self.inner.public_key_package()
+    }
+
+    pub fn group_public_key(&self) -> String {
+        self.inner.group_public_key()
+    }
+
+    pub fn address(&self, is_testnet: u8) -> String {
+        self.inner.address(is_testnet)
+    }
+
+    pub fn commit(&self) -> String {
+        self.inner.commit()
+    }
+
+    pub fn sign(&self, signing_package: &str) -> Option<String> {
+        self.inner.sign(signing_package)
+    }
+}
+
+#[wasm_bindgen]
+pub enum TimelockType {
+    AbsoluteHeight,
+    AbsoluteTime,
+    RelativeHeight,
+    RelativeTime,
+}
+
+impl From<TimelockType> for timelock::TimelockType {
+    fn from(timelock_type: TimelockType) -> Self {
+        match timelock_type {
+            TimelockType::AbsoluteHeight => timelock::TimelockType::AbsoluteHeight,
+            TimelockType::AbsoluteTime => timelock::TimelockType::AbsoluteTime,
+            TimelockType::RelativeHeight => timelock::TimelockType::RelativeHeight,
+            TimelockType::RelativeTime => timelock::TimelockType::RelativeTime,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub enum TimelockScriptType {
+    P2wsh,
+    P2tr,
+}
+
+impl From<TimelockScriptType> for timelock::TimelockScriptType {
+    fn from(script_type: TimelockScriptType) -> Self {
+        match script_type {
+            TimelockScriptType::P2wsh => timelock::TimelockScriptType::P2wsh,
+            TimelockScriptType::P2tr => timelock::TimelockScriptType::P2tr,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn timelock_script(
+    pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+) -> String {
+    timelock::timelock_script(pubkey_hex, timelock_type.into(), value, script_type.into())
+}
+
+#[wasm_bindgen]
+pub fn timelock_address(
+    pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+    is_testnet: u8,
+) -> String {
+    timelock::timelock_address(
+        pubkey_hex,
+        timelock_type.into(),
+        value,
+        script_type.into(),
+        is_testnet,
+    )
+}
+
+#[wasm_bindgen]
+pub fn timelock_spend(
+    priv_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+) -> String {
+    timelock::timelock_spend(
+        priv_hex,
+        timelock_type.into(),
+        value,
+        script_type.into(),
+        tx_hex,
+        tx_prevouts
+            .into_iter()
+            .map(|prevout| prevout.inner)
+            .collect(),
+    )
+}
+
+#[wasm_bindgen]
+pub fn inheritance_script(
+    owner_pubkey_hex: &str,
+    heir_pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+) -> String {
+    timelock::inheritance_script(
+        owner_pubkey_hex,
+        heir_pubkey_hex,
+        timelock_type.into(),
+        value,
+        script_type.into(),
+    )
+}
+
+#[wasm_bindgen]
+pub fn inheritance_address(
+    owner_pubkey_hex: &str,
+    heir_pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+    is_testnet: u8,
+) -> String {
+    timelock::inheritance_address(
+        owner_pubkey_hex,
+        heir_pubkey_hex,
+        timelock_type.into(),
+        value,
+        script_type.into(),
+        is_testnet,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn inheritance_spend(
+    priv_hex: &str,
+    owner_pubkey_hex: &str,
+    heir_pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+) -> String {
+    timelock::inheritance_spend(
+        priv_hex,
+        owner_pubkey_hex,
+        heir_pubkey_hex,
+        timelock_type.into(),
+        value,
+        script_type.into(),
+        tx_hex,
+        tx_prevouts
+            .into_iter()
+            .map(|prevout| prevout.inner)
+            .collect(),
+    )
+}
+
+#[wasm_bindgen]
+pub fn rbf_bump_fee(
+    address: &str,
+    priv_hex: &str,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+    fee_rate: f64,
+    extra_prevouts: Vec<Prevout>,
+) -> String {
+    fee_bump::rbf_bump_fee(
+        address,
+        priv_hex,
+        tx_hex,
+        tx_prevouts
+            .into_iter()
+            .map(|prevout| prevout.inner)
+            .collect(),
+        fee_rate,
+        extra_prevouts
+            .into_iter()
+            .map(|prevout| prevout.inner)
+            .collect(),
+    )
+}
+
+#[wasm_bindgen]
+pub fn cpfp_child(
+    address: &str,
+    priv_hex: &str,
+    parent_tx_hex: &str,
+    parent_prevouts: Vec<Prevout>,
+    fee_rate: f64,
+) -> String {
+    fee_bump::cpfp_child(
+        address,
+        priv_hex,
+        parent_tx_hex,
+        parent_prevouts
+            .into_iter()
+            .map(|prevout| prevout.inner)
+            .collect(),
+        fee_rate,
+    )
+}
+
+#[wasm_bindgen]
+pub struct SilentPaymentInput {
+    inner: silent_payments::SilentPaymentInput,
+}
+
+#[wasm_bindgen]
+impl SilentPaymentInput {
+    #[wasm_bindgen(constructor)]
+    pub fn new(txid: String, vout: u32, priv_hex: Option<String>, script_pubkey: String) -> Self {
+        Self {
+            inner: silent_payments::SilentPaymentInput {
+                txid,
+                vout,
+                priv_hex,
+                script_pubkey,
+            },
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct SilentPaymentOutput {
+    inner: silent_payments::SilentPaymentOutput,
+}
+
+#[wasm_bindgen]
+impl SilentPaymentOutput {
+    #[wasm_bindgen(getter)]
+    pub fn vout(&self) -> u32 {
+        self.inner.vout
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tweak(&self) -> String {
+        self.inner.tweak.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn label(&self) -> Option<u32> {
+        self.inner.label
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn priv_hex(&self) -> String {
+        self.inner.priv_hex.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn silent_payment_outputs(
+    inputs: Vec<SilentPaymentInput>,
+    recipients: Vec<String>,
+) -> Option<Vec<String>> {
+    silent_payments::silent_payment_outputs(
+        inputs.into_iter().map(|input| input.inner).collect(),
+        recipients,
+    )
+}
+
+#[wasm_bindgen]
+pub struct SilentPaymentWallet {
+    inner: silent_payments::SilentPaymentWallet,
+}
+
+#[wasm_bindgen]
+impl SilentPaymentWallet {
+    #[wasm_bindgen(constructor)]
+    pub fn new(wallet: &HDWallet) -> Self {
+        Self {
+            inner: silent_payments::SilentPaymentWallet::new(&wallet.inner),
+        }
+    }
+
+    pub fn address(&self, label: Option<u32>) -> String {
+        self.inner.address(label)
+    }
+
+    pub fn scan(
+        &self,
+        tx_hex: &str,
+        prevout_script_pubkeys: Vec<String>,
+        labels: Vec<u32>,
+    ) -> Vec<SilentPaymentOutput> {
+        self.inner
+            .scan(tx_hex, prevout_script_pubkeys, labels)
+            .into_iter()
+            .map(|inner| SilentPaymentOutput { inner })
+            .collect()
     }
 }
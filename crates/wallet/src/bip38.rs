@@ -0,0 +1,402 @@
+use crate::signer::{p2pkh_sign, Prevout};
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::Aes256;
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::hex::DisplayHex;
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
+use bitcoin::secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use bitcoin::{
+    absolute, base58, consensus, transaction, Address, Amount, Network, OutPoint, ScriptBuf,
+    Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+
+const NON_EC_PREFIX: [u8; 2] = [0x01, 0x42];
+const EC_PREFIX: [u8; 2] = [0x01, 0x43];
+const CONFIRMATION_PREFIX: [u8; 5] = [0x64, 0x3b, 0xf6, 0xa8, 0x9a];
+const MAGIC: [u8; 7] = [0x2c, 0xe9, 0xb3, 0xe1, 0xff, 0x39, 0xe2];
+/// Last magic byte of an intermediate code without and with lot and sequence numbers
+const MAGIC_NO_LOT: u8 = 0x51;
+const MAGIC_LOT: u8 = 0x53;
+
+const FLAG_NON_EC: u8 = 0xc0;
+const FLAG_COMPRESSED: u8 = 0x20;
+const FLAG_LOT: u8 = 0x04;
+
+/// A private key decrypted from BIP38
+pub struct Bip38Key {
+    pub priv_hex: String,
+    /// whether the address of the key uses the compressed public key
+    pub compressed: bool,
+}
+
+/// A key generated from an intermediate code by a third party, see
+/// [`bip38_generate_from_intermediate`]
+pub struct Bip38GeneratedKey {
+    /// `6P...` encrypted private key
+    pub encrypted_key: String,
+    /// `cfrm38...` code the passphrase owner checks with [`bip38_verify_confirmation`]
+    pub confirmation_code: String,
+    pub address: String,
+}
+
+fn scrypt(password: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32, len: usize) -> Vec<u8> {
+    let params = scrypt::Params::new(log_n, r, p, len).unwrap();
+    let mut output = vec![0u8; len];
+    scrypt::scrypt(password, salt, &params, &mut output).unwrap();
+    output
+}
+
+fn normalize_passphrase(passphrase: &str) -> Vec<u8> {
+    passphrase.nfc().collect::<String>().into_bytes()
+}
+
+fn xor(a: &[u8], b: &[u8]) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    for i in 0..16 {
+        out[i] = a[i] ^ b[i];
+    }
+    out
+}
+
+fn aes_encrypt(block: [u8; 16], key: &[u8]) -> [u8; 16] {
+    let mut block = block.into();
+    Aes256::new(key.into()).encrypt_block(&mut block);
+    block.into()
+}
+
+fn aes_decrypt(block: &[u8], key: &[u8]) -> [u8; 16] {
+    let mut block = *aes::Block::from_slice(block);
+    Aes256::new(key.into()).decrypt_block(&mut block);
+    block.into()
+}
+
+/// Mainnet P2PKH address of a public key
+fn address(pubkey: &PublicKey, compressed: bool) -> Address {
+    let pubkey = bitcoin::PublicKey {
+        compressed,
+        inner: *pubkey,
+    };
+    Address::p2pkh(&pubkey, Network::Bitcoin)
+}
+
+/// First 4 bytes of the double sha256 of the address
+fn hash_address(address: &Address) -> [u8; 4] {
+    let hash = sha256d::Hash::hash(address.to_string().as_bytes());
+    hash[..4].try_into().unwrap()
+}
+
+/// ### Encrypt a private key with a passphrase, non-EC-multiply mode
+///
+/// compressed:
+/// whether the key's address uses the compressed public key
+///
+/// returns the `6P...` encrypted key
+pub fn bip38_encrypt(priv_hex: &str, passphrase: &str, compressed: bool) -> String {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_str(priv_hex).unwrap();
+    let address_hash = hash_address(&address(&secret_key.public_key(&secp), compressed));
+
+    let derived = scrypt(
+        &normalize_passphrase(passphrase),
+        &address_hash,
+        14,
+        8,
+        8,
+        64,
+    );
+    let (half1, half2) = derived.split_at(32);
+    let secret = secret_key.secret_bytes();
+
+    let mut data = NON_EC_PREFIX.to_vec();
+    data.push(FLAG_NON_EC | if compressed { FLAG_COMPRESSED } else { 0 });
+    data.extend(address_hash);
+    data.extend(aes_encrypt(xor(&secret[..16], &half1[..16]), half2));
+    data.extend(aes_encrypt(xor(&secret[16..], &half1[16..]), half2));
+    base58::encode_check(&data)
+}
+
+/// Pass factor of the passphrase and owner entropy, the private half of EC-multiplied keys
+fn pass_factor(passphrase: &str, owner_entropy: &[u8], has_lot: bool) -> SecretKey {
+    let passphrase = normalize_passphrase(passphrase);
+    if has_lot {
+        let prefactor = scrypt(&passphrase, &owner_entropy[..4], 14, 8, 8, 32);
+        let mut data = prefactor;
+        data.extend(owner_entropy);
+        SecretKey::from_slice(&sha256d::Hash::hash(&data)[..]).unwrap()
+    } else {
+        SecretKey::from_slice(&scrypt(&passphrase, owner_entropy, 14, 8, 8, 32)).unwrap()
+    }
+}
+
+/// ### Create an intermediate code for the EC-multiply mode
+///
+/// the passphrase owner hands the code to a third party that generates encrypted keys with
+/// [`bip38_generate_from_intermediate`] without learning the private keys
+///
+/// lot, sequence:
+/// optional lot (up to 1048575) and sequence (up to 4095) numbers encoded into the keys, both
+/// or neither
+///
+/// returns the `passphrase...` intermediate code
+pub fn bip38_intermediate_code(
+    passphrase: &str,
+    lot: Option<u32>,
+    sequence: Option<u32>,
+) -> String {
+    let secp = Secp256k1::new();
+    let mut owner_entropy = [0u8; 8];
+    thread_rng().fill_bytes(&mut owner_entropy);
+    let lot_sequence = match (lot, sequence) {
+        (Some(lot), Some(sequence)) => Some((lot, sequence)),
+        (None, None) => None,
+        _ => panic!("lot and sequence go together"),
+    };
+    if let Some((lot, sequence)) = lot_sequence {
+        assert!(
+            lot <= 1048575 && sequence <= 4095,
+            "invalid lot or sequence"
+        );
+        owner_entropy[4..].copy_from_slice(&(lot * 4096 + sequence).to_be_bytes());
+    }
+
+    let pass_point =
+        pass_factor(passphrase, &owner_entropy, lot_sequence.is_some()).public_key(&secp);
+
+    let mut data = MAGIC.to_vec();
+    data.push(match lot_sequence {
+        Some(_) => MAGIC_LOT,
+        None => MAGIC_NO_LOT,
+    });
+    data.extend(owner_entropy);
+    data.extend(pass_point.serialize());
+    base58::encode_check(&data)
+}
+
+/// ### Generate an encrypted key from an intermediate code, EC-multiply mode
+///
+/// compressed:
+/// whether the generated address uses the compressed public key
+pub fn bip38_generate_from_intermediate(intermediate: &str, compressed: bool) -> Bip38GeneratedKey {
+    let secp = Secp256k1::new();
+    let data = base58::decode_check(intermediate).unwrap();
+    assert!(
+        data.len() == 49 && data[..7] == MAGIC,
+        "invalid intermediate code"
+    );
+    let has_lot = match data[7] {
+        MAGIC_NO_LOT => false,
+        MAGIC_LOT => true,
+        _ => panic!("invalid intermediate code"),
+    };
+    let owner_entropy = &data[8..16];
+    let pass_point = PublicKey::from_slice(&data[16..]).unwrap();
+
+    let mut flag = 0u8;
+    if compressed {
+        flag |= FLAG_COMPRESSED;
+    }
+    if has_lot {
+        flag |= FLAG_LOT;
+    }
+
+    let mut seed_b = [0u8; 24];
+    thread_rng().fill_bytes(&mut seed_b);
+    let factor_b = sha256d::Hash::hash(&seed_b).to_byte_array();
+    let pubkey = pass_point
+        .mul_tweak(&secp, &Scalar::from_be_bytes(factor_b).unwrap())
+        .unwrap();
+    let address = address(&pubkey, compressed);
+    let address_hash = hash_address(&address);
+
+    let mut salt = address_hash.to_vec();
+    salt.extend(owner_entropy);
+    let derived = scrypt(&pass_point.serialize(), &salt, 10, 1, 1, 64);
+    let (half1, half2) = derived.split_at(32);
+
+    let encrypted_part1 = aes_encrypt(xor(&seed_b[..16], &half1[..16]), half2);
+    let mut part2 = encrypted_part1[8..].to_vec();
+    part2.extend(&seed_b[16..]);
+    let encrypted_part2 = aes_encrypt(xor(&part2, &half1[16..]), half2);
+
+    let mut key = EC_PREFIX.to_vec();
+    key.push(flag);
+    key.extend(address_hash);
+    key.extend(owner_entropy);
+    key.extend(&encrypted_part1[..8]);
+    key.extend(encrypted_part2);
+
+    let point_b = SecretKey::from_slice(&factor_b)
+        .unwrap()
+        .public_key(&secp)
+        .serialize();
+    let mut confirmation = CONFIRMATION_PREFIX.to_vec();
+    confirmation.push(flag);
+    confirmation.extend(address_hash);
+    confirmation.extend(owner_entropy);
+    confirmation.push(point_b[0] ^ (half2[31] & 1));
+    confirmation.extend(aes_encrypt(xor(&point_b[1..17], &half1[..16]), half2));
+    confirmation.extend(aes_encrypt(xor(&point_b[17..], &half1[16..]), half2));
+
+    Bip38GeneratedKey {
+        encrypted_key: base58::encode_check(&key),
+        confirmation_code: base58::encode_check(&confirmation),
+        address: address.to_string(),
+    }
+}
+
+/// ### Check a confirmation code against the passphrase
+///
+/// returns the address the encrypted key belongs to, `None` if the passphrase is wrong
+pub fn bip38_verify_confirmation(confirmation_code: &str, passphrase: &str) -> Option<String> {
+    let secp = Secp256k1::new();
+    let data = base58::decode_check(confirmation_code).unwrap();
+    assert!(
+        data.len() == 51 && data[..5] == CONFIRMATION_PREFIX,
+        "invalid confirmation code"
+    );
+    let flag = data[5];
+    let address_hash = &data[6..10];
+    let owner_entropy = &data[10..18];
+
+    let pass_factor = pass_factor(passphrase, owner_entropy, flag & FLAG_LOT != 0);
+    let pass_point = pass_factor.public_key(&secp);
+    let mut salt = address_hash.to_vec();
+    salt.extend(owner_entropy);
+    let derived = scrypt(&pass_point.serialize(), &salt, 10, 1, 1, 64);
+    let (half1, half2) = derived.split_at(32);
+
+    let mut point_b = vec![data[18] ^ (half2[31] & 1)];
+    point_b.extend(xor(&aes_decrypt(&data[19..35], half2), &half1[..16]));
+    point_b.extend(xor(&aes_decrypt(&data[35..], half2), &half1[16..]));
+    let point_b = PublicKey::from_slice(&point_b).ok()?;
+
+    let pubkey = point_b
+        .mul_tweak(&secp, &Scalar::from(pass_factor))
+        .unwrap();
+    let address = address(&pubkey, flag & FLAG_COMPRESSED != 0);
+    (address_hash == hash_address(&address)).then(|| address.to_string())
+}
+
+/// ### Decrypt a BIP38 key
+///
+/// supports both the non-EC-multiply and the EC-multiply modes
+///
+/// returns `None` if the passphrase is wrong
+pub fn bip38_decrypt(encrypted_key: &str, passphrase: &str) -> Option<Bip38Key> {
+    let secp = Secp256k1::new();
+    let data = base58::decode_check(encrypted_key).unwrap();
+    assert_eq!(data.len(), 39, "invalid BIP38 key length");
+    let flag = data[2];
+    let address_hash = &data[3..7];
+    let compressed = flag & FLAG_COMPRESSED != 0;
+
+    let secret_key = match [data[0], data[1]] {
+        NON_EC_PREFIX => {
+            let derived = scrypt(
+                &normalize_passphrase(passphrase),
+                address_hash,
+                14,
+                8,
+                8,
+                64,
+            );
+            let (half1, half2) = derived.split_at(32);
+            let mut secret = xor(&aes_decrypt(&data[7..23], half2), &half1[..16]).to_vec();
+            secret.extend(xor(&aes_decrypt(&data[23..], half2), &half1[16..]));
+            SecretKey::from_slice(&secret).ok()?
+        }
+        EC_PREFIX => {
+            let owner_entropy = &data[7..15];
+            let pass_factor = pass_factor(passphrase, owner_entropy, flag & FLAG_LOT != 0);
+            let pass_point = pass_factor.public_key(&secp);
+            let mut salt = address_hash.to_vec();
+            salt.extend(owner_entropy);
+            let derived = scrypt(&pass_point.serialize(), &salt, 10, 1, 1, 64);
+            let (half1, half2) = derived.split_at(32);
+
+            let part2 = xor(&aes_decrypt(&data[23..], half2), &half1[16..]);
+            let mut encrypted_part1 = data[15..23].to_vec();
+            encrypted_part1.extend(&part2[..8]);
+            let mut seed_b = xor(&aes_decrypt(&encrypted_part1, half2), &half1[..16]).to_vec();
+            seed_b.extend(&part2[8..]);
+
+            let factor_b = sha256d::Hash::hash(&seed_b).to_byte_array();
+            pass_factor
+                .mul_tweak(&Scalar::from_be_bytes(factor_b).ok()?)
+                .ok()?
+        }
+        _ => panic!("invalid BIP38 key prefix"),
+    };
+
+    let address = address(&secret_key.public_key(&secp), compressed);
+    (address_hash == hash_address(&address)).then(|| Bip38Key {
+        priv_hex: secret_key.secret_bytes().as_hex().to_string(),
+        compressed,
+    })
+}
+
+/// ### Sweep the coins of a BIP38 paper wallet
+///
+/// prevouts:
+/// the UTXOs of the paper wallet's P2PKH address on the network of `to_address`
+///
+/// fee_sat:
+/// absolute fee in satoshis deducted from the swept amount
+///
+/// returns the signed transaction in hex, signed with [`p2pkh_sign`], `None` if the passphrase
+/// is wrong
+pub fn bip38_sweep(
+    encrypted_key: &str,
+    passphrase: &str,
+    prevouts: Vec<Prevout>,
+    to_address: &str,
+    fee_sat: u64,
+) -> Option<String> {
+    let secp = Secp256k1::new();
+    let key = bip38_decrypt(encrypted_key, passphrase)?;
+    let to_address = Address::from_str(to_address).unwrap().assume_checked();
+    let secret_key = SecretKey::from_str(&key.priv_hex).unwrap();
+    let pubkey = bitcoin::PublicKey {
+        compressed: key.compressed,
+        inner: secret_key.public_key(&secp),
+    };
+    let from_address = Address::p2pkh(&pubkey, *to_address.network());
+
+    let total = prevouts
+        .iter()
+        .map(|v| Amount::from_btc(v.amount).unwrap())
+        .sum::<Amount>();
+    let value = total
+        .checked_sub(Amount::from_sat(fee_sat))
+        .expect("fee exceeds the swept amount");
+
+    let tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: prevouts
+            .iter()
+            .map(|v| TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_str(&v.txid).unwrap(),
+                    vout: v.vout,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            })
+            .collect(),
+        output: vec![TxOut {
+            value,
+            script_pubkey: to_address.script_pubkey(),
+        }],
+    };
+    let tx_hex = consensus::serialize(&tx).as_hex().to_string();
+
+    Some(p2pkh_sign(
+        &from_address.to_string(),
+        &key.priv_hex,
+        &tx_hex,
+    ))
+}
@@ -0,0 +1,132 @@
+use base64ct::{Base64, Encoding};
+use bip39::{Language, Mnemonic};
+use bitcoin::bip32::{ChainCode, ChildNumber, DerivationPath, Fingerprint, Xpriv};
+use bitcoin::hashes::{hmac, sha512, Hash, HashEngine};
+use bitcoin::hex::DisplayHex;
+use bitcoin::secp256k1::{Secp256k1, SecretKey};
+use bitcoin::PrivateKey;
+use std::str::FromStr;
+
+/// Purpose of every BIP85 derivation path, "bip" on a phone keypad
+const PURPOSE: &str = "83696968'";
+
+/// ### Derive the 64 bytes of BIP85 entropy at a path
+///
+/// master_priv:
+/// root extended private key, e.g. `HDWallet::export_master_priv`
+///
+/// path:
+/// full hardened path such as `m/83696968'/0'/0'`
+///
+/// returns the entropy in hex
+pub fn bip85_entropy(master_priv: &str, path: &str) -> String {
+    derive_entropy(master_priv, path).as_hex().to_string()
+}
+
+/// HMAC-SHA512 keyed with "bip-entropy-from-k" of the private key at `path`
+fn derive_entropy(master_priv: &str, path: &str) -> [u8; 64] {
+    let secp = Secp256k1::new();
+    let root = Xpriv::from_str(master_priv).unwrap();
+    let path = DerivationPath::from_str(path).unwrap();
+    assert!(
+        path.into_iter().all(ChildNumber::is_hardened),
+        "BIP85 paths are fully hardened"
+    );
+    let k = root.derive_priv(&secp, &path).unwrap().private_key;
+
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(b"bip-entropy-from-k");
+    engine.input(&k.secret_bytes());
+    hmac::Hmac::<sha512::Hash>::from_engine(engine).to_byte_array()
+}
+
+fn language(code: u32) -> Language {
+    match code {
+        0 => Language::English,
+        1 => Language::Japanese,
+        2 => Language::Korean,
+        3 => Language::Spanish,
+        4 => Language::SimplifiedChinese,
+        5 => Language::TraditionalChinese,
+        6 => Language::French,
+        7 => Language::Italian,
+        8 => Language::Czech,
+        9 => Language::Portuguese,
+        _ => panic!("unsupported language {code}"),
+    }
+}
+
+/// ### Derive a child BIP39 mnemonic
+///
+/// language_code:
+/// BIP85 wordlist code, 0 English, 1 Japanese, 2 Korean, 3 Spanish, 4 Chinese (Simplified),
+/// 5 Chinese (Traditional), 6 French, 7 Italian, 8 Czech, 9 Portuguese
+///
+/// words:
+/// 12, 15, 18, 21 or 24
+pub fn bip85_mnemonic(master_priv: &str, language_code: u32, words: u32, index: u32) -> String {
+    assert!(
+        matches!(words, 12 | 15 | 18 | 21 | 24),
+        "invalid word count {words}"
+    );
+    let entropy = derive_entropy(
+        master_priv,
+        &format!("m/{PURPOSE}/39'/{language_code}'/{words}'/{index}'"),
+    );
+    let len = words as usize * 4 / 3;
+    Mnemonic::from_entropy_in(language(language_code), &entropy[..len])
+        .unwrap()
+        .to_string()
+}
+
+/// ### Derive a child private key for an HD-seed wallet such as Bitcoin Core
+///
+/// returns the key as compressed WIF on the network of `master_priv`
+pub fn bip85_wif(master_priv: &str, index: u32) -> String {
+    let network = Xpriv::from_str(master_priv).unwrap().network;
+    let entropy = derive_entropy(master_priv, &format!("m/{PURPOSE}/2'/{index}'"));
+    let secret_key = SecretKey::from_slice(&entropy[..32]).unwrap();
+    PrivateKey::new(secret_key, network).to_wif()
+}
+
+/// ### Derive a child root extended private key
+///
+/// the first 32 bytes of entropy are the chain code, the last 32 the private key
+pub fn bip85_xprv(master_priv: &str, index: u32) -> String {
+    let network = Xpriv::from_str(master_priv).unwrap().network;
+    let entropy = derive_entropy(master_priv, &format!("m/{PURPOSE}/32'/{index}'"));
+    Xpriv {
+        network,
+        depth: 0,
+        parent_fingerprint: Fingerprint::default(),
+        child_number: ChildNumber::from_normal_idx(0).unwrap(),
+        private_key: SecretKey::from_slice(&entropy[32..]).unwrap(),
+        chain_code: ChainCode::from(<[u8; 32]>::try_from(&entropy[..32]).unwrap()),
+    }
+    .to_string()
+}
+
+/// ### Derive child entropy in hex
+///
+/// num_bytes:
+/// 16 to 64
+pub fn bip85_hex(master_priv: &str, num_bytes: u32, index: u32) -> String {
+    assert!((16..=64).contains(&num_bytes), "invalid length {num_bytes}");
+    let entropy = derive_entropy(
+        master_priv,
+        &format!("m/{PURPOSE}/128169'/{num_bytes}'/{index}'"),
+    );
+    entropy[..num_bytes as usize].as_hex().to_string()
+}
+
+/// ### Derive a base64 password
+///
+/// pwd_len:
+/// 20 to 86 characters of the base64 encoded entropy
+pub fn bip85_base64_password(master_priv: &str, pwd_len: u32, index: u32) -> String {
+    assert!((20..=86).contains(&pwd_len), "invalid length {pwd_len}");
+    let entropy = derive_entropy(
+        master_priv,
+        &format!("m/{PURPOSE}/707764'/{pwd_len}'/{index}'"),
+    );
+    Base64::encode_string(&entropy)[..pwd_len as usize].to_string()
+}
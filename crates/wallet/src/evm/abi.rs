@@ -0,0 +1,715 @@
+use super::address::address_from_bytes;
+use super::{format_uint, keccak256, negate, try_decode_hex, try_int_word, try_uint_word};
+use bitcoin::hex::DisplayHex;
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// Solidity ABI type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamType {
+    Address,
+    Bool,
+    Uint(usize),
+    Int(usize),
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<ParamType>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    pub name: String,
+    pub kind: ParamType,
+    /// only meaningful for event inputs
+    pub indexed: bool,
+}
+
+/// Contract function with its inputs and outputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    pub inputs: Vec<Param>,
+    pub outputs: Vec<Param>,
+}
+
+/// Contract event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub name: String,
+    pub inputs: Vec<Param>,
+    pub anonymous: bool,
+}
+
+impl fmt::Display for ParamType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamType::Address => write!(f, "address"),
+            ParamType::Bool => write!(f, "bool"),
+            ParamType::Uint(size) => write!(f, "uint{size}"),
+            ParamType::Int(size) => write!(f, "int{size}"),
+            ParamType::FixedBytes(size) => write!(f, "bytes{size}"),
+            ParamType::Bytes => write!(f, "bytes"),
+            ParamType::String => write!(f, "string"),
+            ParamType::Array(kind) => write!(f, "{kind}[]"),
+            ParamType::FixedArray(kind, len) => write!(f, "{kind}[{len}]"),
+            ParamType::Tuple(kinds) => write!(f, "({})", join_types(kinds)),
+        }
+    }
+}
+
+fn join_types(kinds: &[ParamType]) -> String {
+    kinds
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Index of the parenthesis closing the one at the start of `s`
+fn closing_paren(s: &str) -> usize {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+    }
+    panic!("unbalanced parentheses in {s}")
+}
+
+/// Split on commas outside of parentheses
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !s[start..].trim().is_empty() {
+        parts.push(s[start..].trim());
+    }
+    parts
+}
+
+fn with_array_suffix(mut kind: ParamType, suffix: &str) -> ParamType {
+    for dim in suffix.split_terminator(']') {
+        let dim = dim.strip_prefix('[').unwrap();
+        kind = match dim {
+            "" => ParamType::Array(Box::new(kind)),
+            len => match len.parse() {
+                Ok(len) if len > 0 => ParamType::FixedArray(Box::new(kind), len),
+                _ => panic!("invalid array length {len}"),
+            },
+        };
+    }
+    kind
+}
+
+impl ParamType {
+    /// Parse a canonical or human-readable type like `uint`, `bytes32[2]` or `(address,uint256)[]`
+    pub fn parse(ty: &str) -> Self {
+        let ty = ty.trim();
+        if ty.starts_with('(') {
+            let end = closing_paren(ty);
+            let kinds = split_top_level(&ty[1..end])
+                .into_iter()
+                .map(|param| parse_param(param).kind)
+                .collect();
+            return with_array_suffix(ParamType::Tuple(kinds), &ty[end + 1..]);
+        }
+
+        let (base, suffix) = ty.split_at(ty.find('[').unwrap_or(ty.len()));
+        let kind = match base {
+            "address" => ParamType::Address,
+            "bool" => ParamType::Bool,
+            "string" => ParamType::String,
+            "bytes" => ParamType::Bytes,
+            "uint" => ParamType::Uint(256),
+            "int" => ParamType::Int(256),
+            _ if base.starts_with("uint") => ParamType::Uint(base[4..].parse().unwrap_or(0)),
+            _ if base.starts_with("int") => ParamType::Int(base[3..].parse().unwrap_or(0)),
+            _ if base.starts_with("bytes") => ParamType::FixedBytes(base[5..].parse().unwrap_or(0)),
+            _ => panic!("unknown abi type {ty}"),
+        };
+        let valid = match kind {
+            ParamType::Uint(bits) | ParamType::Int(bits) => padding_bytes(bits).is_some(),
+            ParamType::FixedBytes(size) => (1..=32).contains(&size),
+            _ => true,
+        };
+        assert!(valid, "unknown abi type {ty}");
+        with_array_suffix(kind, suffix)
+    }
+
+    fn from_json(param: &Value) -> Self {
+        let ty = param["type"].as_str().unwrap();
+        match ty.strip_prefix("tuple") {
+            Some(suffix) => {
+                let kinds = param["components"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(ParamType::from_json)
+                    .collect();
+                with_array_suffix(ParamType::Tuple(kinds), suffix)
+            }
+            None => ParamType::parse(ty),
+        }
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::FixedArray(kind, _) => kind.is_dynamic(),
+            ParamType::Tuple(kinds) => kinds.iter().any(ParamType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// Size of the head of a value, 32 bytes unless a static tuple or fixed array
+    fn head_size(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => 32,
+            ParamType::FixedArray(kind, len) => kind.head_size() * len,
+            ParamType::Tuple(kinds) => kinds.iter().map(ParamType::head_size).sum(),
+            _ => 32,
+        }
+    }
+}
+
+/// Parse a human-readable parameter like `address indexed from` or `uint256[] calldata ids`
+fn parse_param(param: &str) -> Param {
+    let param = param.trim();
+    let type_end = if param.starts_with('(') {
+        let end = closing_paren(param) + 1;
+        end + param[end..]
+            .find(char::is_whitespace)
+            .unwrap_or(param.len() - end)
+    } else {
+        param.find(char::is_whitespace).unwrap_or(param.len())
+    };
+
+    let mut name = String::new();
+    let mut indexed = false;
+    for word in param[type_end..].split_whitespace() {
+        match word {
+            "indexed" => indexed = true,
+            "memory" | "calldata" | "storage" | "payable" => {}
+            _ => name = word.to_string(),
+        }
+    }
+
+    Param {
+        name,
+        kind: ParamType::parse(&param[..type_end]),
+        indexed,
+    }
+}
+
+fn parse_params(params: &str) -> Vec<Param> {
+    split_top_level(params)
+        .into_iter()
+        .map(parse_param)
+        .collect()
+}
+
+fn params_from_json(params: &Value) -> Vec<Param> {
+    params
+        .as_array()
+        .map(|params| {
+            params
+                .iter()
+                .map(|param| Param {
+                    name: param["name"].as_str().unwrap_or_default().to_string(),
+                    kind: ParamType::from_json(param),
+                    indexed: param["indexed"].as_bool().unwrap_or_default(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Split a human-readable signature into its name, inputs and the text following them
+fn split_signature<'a>(signature: &'a str, keyword: &str) -> (&'a str, &'a str, &'a str) {
+    let signature = signature.trim();
+    // the keyword is a word of its own, `functionCall(...)` is a function named so
+    let signature = match signature.strip_prefix(keyword) {
+        Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+        _ => signature,
+    };
+    let start = signature.find('(').unwrap();
+    let end = start + closing_paren(&signature[start..]);
+    (
+        signature[..start].trim(),
+        &signature[start + 1..end],
+        &signature[end + 1..],
+    )
+}
+
+/// Find an item of a JSON ABI by name, or by canonical signature for overloaded items
+pub fn find_abi_item(abi_json: &str, name: &str) -> String {
+    let abi: Value = serde_json::from_str(abi_json).unwrap();
+    abi.as_array()
+        .unwrap()
+        .iter()
+        .find(|item| {
+            let item_name = item["name"].as_str().unwrap_or_default();
+            if item_name == name {
+                return true;
+            }
+            let inputs = params_from_json(&item["inputs"])
+                .into_iter()
+                .map(|param| param.kind)
+                .collect::<Vec<_>>();
+            format!("{item_name}({})", join_types(&inputs)) == name
+        })
+        .unwrap_or_else(|| panic!("{name} not found in abi"))
+        .to_string()
+}
+
+impl Function {
+    /// Parse a signature like `transfer(address to, uint256 amount) returns (bool)`
+    /// or a JSON ABI function item
+    pub fn parse(function: &str) -> Self {
+        if function.trim_start().starts_with('{') {
+            let item: Value = serde_json::from_str(function).unwrap();
+            return Self {
+                name: item["name"].as_str().unwrap().to_string(),
+                inputs: params_from_json(&item["inputs"]),
+                outputs: params_from_json(&item["outputs"]),
+            };
+        }
+
+        let (name, inputs, rest) = split_signature(function, "function");
+        let outputs = rest
+            .find("returns")
+            .map(|i| {
+                let returns = rest[i + "returns".len()..].trim();
+                parse_params(&returns[1..closing_paren(returns)])
+            })
+            .unwrap_or_default();
+        Self {
+            name: name.to_string(),
+            inputs: parse_params(inputs),
+            outputs,
+        }
+    }
+
+    /// Canonical signature like `transfer(address,uint256)`
+    pub fn signature(&self) -> String {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|p| p.kind.clone())
+            .collect::<Vec<_>>();
+        format!("{}({})", self.name, join_types(&inputs))
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        let hash = keccak256(self.signature().as_bytes());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// Call data, the selector followed by the encoded arguments, `None` if the arguments
+    /// don't match the inputs
+    pub fn encode_input(&self, args: &[Value]) -> Option<Vec<u8>> {
+        let kinds = self
+            .inputs
+            .iter()
+            .map(|p| p.kind.clone())
+            .collect::<Vec<_>>();
+        let mut data = self.selector().to_vec();
+        data.extend(encode(&kinds, args)?);
+        Some(data)
+    }
+
+    pub fn decode_output(&self, data: &[u8]) -> Option<Vec<Value>> {
+        let kinds = self
+            .outputs
+            .iter()
+            .map(|p| p.kind.clone())
+            .collect::<Vec<_>>();
+        decode(&kinds, data)
+    }
+}
+
+impl Event {
+    /// Parse a signature like `Transfer(address indexed from, address indexed to, uint256 value)`
+    /// or a JSON ABI event item
+    pub fn parse(event: &str) -> Self {
+        if event.trim_start().starts_with('{') {
+            let item: Value = serde_json::from_str(event).unwrap();
+            return Self {
+                name: item["name"].as_str().unwrap().to_string(),
+                inputs: params_from_json(&item["inputs"]),
+                anonymous: item["anonymous"].as_bool().unwrap_or_default(),
+            };
+        }
+
+        let (name, inputs, rest) = split_signature(event, "event");
+        Self {
+            name: name.to_string(),
+            inputs: parse_params(inputs),
+            anonymous: rest.contains("anonymous"),
+        }
+    }
+
+    pub fn signature(&self) -> String {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|p| p.kind.clone())
+            .collect::<Vec<_>>();
+        format!("{}({})", self.name, join_types(&inputs))
+    }
+
+    /// First topic of a non-anonymous event log
+    pub fn topic(&self) -> [u8; 32] {
+        keccak256(self.signature().as_bytes())
+    }
+
+    /// Decode a log into an object keyed by parameter name, or by position for unnamed ones.
+    /// Indexed dynamic values are only available as their topic hash.
+    ///
+    /// returns `None` if the log isn't one of this event or is malformed
+    pub fn decode_log(&self, topics: &[Vec<u8>], data: &[u8]) -> Option<Map<String, Value>> {
+        let mut topics = topics.iter();
+        if !self.anonymous && topics.next()?[..] != self.topic() {
+            return None;
+        }
+
+        let kinds = self
+            .inputs
+            .iter()
+            .filter(|p| !p.indexed)
+            .map(|p| p.kind.clone())
+            .collect::<Vec<_>>();
+        let mut values = decode(&kinds, data)?.into_iter();
+
+        let mut decoded = Map::new();
+        for (i, param) in self.inputs.iter().enumerate() {
+            let value = if !param.indexed {
+                values.next()?
+            } else if param.kind.is_dynamic() || matches!(param.kind, ParamType::Tuple(_)) {
+                json!(format!("0x{}", read_word(topics.next()?)?.as_hex()))
+            } else {
+                decode_value(&param.kind, topics.next()?)?
+            };
+            let key = match param.name.as_str() {
+                "" => i.to_string(),
+                name => name.to_string(),
+            };
+            decoded.insert(key, value);
+        }
+        Some(decoded)
+    }
+}
+
+fn pad_right(data: &[u8]) -> Vec<u8> {
+    let mut out = data.to_vec();
+    out.resize(data.len().div_ceil(32) * 32, 0);
+    out
+}
+
+fn usize_word(value: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+fn read_word(data: &[u8]) -> Option<&[u8; 32]> {
+    data.get(..32)?.try_into().ok()
+}
+
+fn read_usize(data: &[u8]) -> Option<usize> {
+    let word = read_word(data)?;
+    if word[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    usize::try_from(u64::from_be_bytes(word[24..].try_into().unwrap())).ok()
+}
+
+/// Number of bytes above an integer of `bits` in a word, `None` for invalid sizes
+fn padding_bytes(bits: usize) -> Option<usize> {
+    (bits > 0 && bits <= 256 && bits.is_multiple_of(8)).then(|| 32 - bits / 8)
+}
+
+/// Whether a word holds an unsigned integer of `bits`, the higher bytes being zero
+fn fits_uint(word: &[u8; 32], bits: usize) -> bool {
+    padding_bytes(bits).is_some_and(|top| word[..top].iter().all(|b| *b == 0))
+}
+
+/// Whether a two's complement word holds a signed integer of `bits`, the higher bytes
+/// extending its sign
+fn fits_int(word: &[u8; 32], bits: usize) -> bool {
+    padding_bytes(bits).is_some_and(|top| {
+        let fill = if word[top] & 0x80 != 0 { 0xff } else { 0 };
+        word[..top].iter().all(|b| *b == fill)
+    })
+}
+
+/// ### Encode values as a tuple of the given types
+///
+/// Values are JSON: addresses, `0x` prefixed hex bytes and strings as strings, integers as
+/// numbers or decimal/hex strings, booleans as booleans, arrays and tuples as arrays.
+///
+/// returns `None` if the values don't match the types, e.g. an integer out of range
+pub fn encode(kinds: &[ParamType], values: &[Value]) -> Option<Vec<u8>> {
+    if kinds.len() != values.len() {
+        return None;
+    }
+    let head_len = kinds.iter().map(ParamType::head_size).sum::<usize>();
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    for (kind, value) in kinds.iter().zip(values) {
+        let encoded = encode_value(kind, value)?;
+        if kind.is_dynamic() {
+            head.extend(usize_word(head_len + tail.len()));
+            tail.extend(encoded);
+        } else {
+            head.extend(encoded);
+        }
+    }
+    head.extend(tail);
+    Some(head)
+}
+
+fn encode_value(kind: &ParamType, value: &Value) -> Option<Vec<u8>> {
+    let encoded = match kind {
+        ParamType::Address => {
+            let address = try_decode_hex(value.as_str()?)?;
+            if address.len() != 20 {
+                return None;
+            }
+            let mut word = [0u8; 32];
+            word[12..].copy_from_slice(&address);
+            word.to_vec()
+        }
+        ParamType::Bool => {
+            let mut word = [0u8; 32];
+            word[31] = value.as_bool()? as u8;
+            word.to_vec()
+        }
+        ParamType::Uint(bits) => {
+            let word = try_uint_word(value)?;
+            fits_uint(&word, *bits).then_some(word)?.to_vec()
+        }
+        ParamType::Int(bits) => {
+            let word = try_int_word(value)?;
+            fits_int(&word, *bits).then_some(word)?.to_vec()
+        }
+        ParamType::FixedBytes(size) => {
+            let bytes = try_decode_hex(value.as_str()?)?;
+            if bytes.len() > *size || *size > 32 {
+                return None;
+            }
+            let mut word = [0u8; 32];
+            word[..bytes.len()].copy_from_slice(&bytes);
+            word.to_vec()
+        }
+        ParamType::Bytes | ParamType::String => {
+            let bytes = match kind {
+                ParamType::Bytes => try_decode_hex(value.as_str()?)?,
+                _ => value.as_str()?.as_bytes().to_vec(),
+            };
+            let mut out = usize_word(bytes.len()).to_vec();
+            out.extend(pad_right(&bytes));
+            out
+        }
+        ParamType::Array(element) => {
+            let values = value.as_array()?;
+            let mut out = usize_word(values.len()).to_vec();
+            out.extend(encode(&vec![*element.clone(); values.len()], values)?);
+            out
+        }
+        ParamType::FixedArray(element, len) => {
+            encode(&vec![*element.clone(); *len], value.as_array()?)?
+        }
+        ParamType::Tuple(kinds) => encode(kinds, value.as_array()?)?,
+    };
+    Some(encoded)
+}
+
+/// ### Decode a tuple of the given types, see [`encode`] for the JSON representation
+///
+/// returns `None` if the data is truncated, an offset or length points outside of it, or a
+/// value is out of range for its type
+pub fn decode(kinds: &[ParamType], data: &[u8]) -> Option<Vec<Value>> {
+    decode_sequence(kinds.iter(), data)
+}
+
+fn decode_sequence<'a>(
+    kinds: impl Iterator<Item = &'a ParamType>,
+    data: &[u8],
+) -> Option<Vec<Value>> {
+    let mut offset = 0;
+    kinds
+        .map(|kind| {
+            let head = data.get(offset..)?;
+            let value = if kind.is_dynamic() {
+                decode_value(kind, data.get(read_usize(head)?..)?)?
+            } else {
+                decode_value(kind, head)?
+            };
+            offset += kind.head_size();
+            Some(value)
+        })
+        .collect()
+}
+
+fn decode_value(kind: &ParamType, data: &[u8]) -> Option<Value> {
+    let value = match kind {
+        ParamType::Address => {
+            let word = read_word(data)?;
+            fits_uint(word, 160).then(|| json!(address_from_bytes(&word[12..])))?
+        }
+        ParamType::Bool => match read_word(data)? {
+            word if fits_uint(word, 8) && word[31] <= 1 => json!(word[31] == 1),
+            _ => return None,
+        },
+        ParamType::Uint(bits) => {
+            let word = read_word(data)?;
+            fits_uint(word, *bits).then(|| json!(format_uint(word)))?
+        }
+        ParamType::Int(bits) => {
+            let word = read_word(data)?;
+            if !fits_int(word, *bits) {
+                return None;
+            }
+            match word[0] & 0x80 {
+                0 => json!(format_uint(word)),
+                _ => json!(format!("-{}", format_uint(&negate(word)))),
+            }
+        }
+        ParamType::FixedBytes(size) => {
+            let word = read_word(data)?;
+            if word.get(*size..)?.iter().any(|b| *b != 0) {
+                return None;
+            }
+            json!(format!("0x{}", word[..*size].as_hex()))
+        }
+        ParamType::Bytes | ParamType::String => {
+            let len = read_usize(data)?;
+            let bytes = data.get(32..32usize.checked_add(len)?)?;
+            match kind {
+                ParamType::Bytes => json!(format!("0x{}", bytes.as_hex())),
+                _ => json!(String::from_utf8(bytes.to_vec()).ok()?),
+            }
+        }
+        ParamType::Array(element) => {
+            let len = read_usize(data)?;
+            let data = &data[32..];
+            // every element takes at least a word of the data, which bounds the length
+            if len.checked_mul(element.head_size().max(32))? > data.len() {
+                return None;
+            }
+            Value::Array(decode_sequence(
+                std::iter::repeat_n(element.as_ref(), len),
+                data,
+            )?)
+        }
+        ParamType::FixedArray(element, len) => Value::Array(decode_sequence(
+            std::iter::repeat_n(element.as_ref(), *len),
+            data,
+        )?),
+        ParamType::Tuple(kinds) => Value::Array(decode(kinds, data)?),
+    };
+    Some(value)
+}
+
+/// ### Encode a contract call
+///
+/// function:
+/// signature like `transfer(address,uint256)` or a JSON ABI function item, see [`find_abi_item`]
+///
+/// args_json:
+/// JSON array of arguments, see [`encode`]
+///
+/// returns the call data in hex, `None` if the arguments don't match the function inputs
+pub fn encode_call(function: &str, args_json: &str) -> Option<String> {
+    let args: Value = serde_json::from_str(args_json).ok()?;
+    let data = Function::parse(function).encode_input(args.as_array()?)?;
+    Some(format!("0x{}", data.as_hex()))
+}
+
+/// ### Decode the return data of a contract call
+///
+/// function:
+/// signature with outputs like `balanceOf(address) returns (uint256)` or a JSON ABI function item
+///
+/// returns a JSON array of the decoded values, `None` if the data is malformed
+pub fn decode_output(function: &str, data_hex: &str) -> Option<String> {
+    let data = try_decode_hex(data_hex)?;
+    Some(Value::Array(Function::parse(function).decode_output(&data)?).to_string())
+}
+
+/// ### Decode an event log
+///
+/// event:
+/// signature like `Transfer(address indexed from, address indexed to, uint256 value)` or a JSON
+/// ABI event item
+///
+/// returns a JSON object of the decoded values keyed by parameter name, `None` if the log isn't
+/// one of this event or is malformed
+pub fn decode_log(event: &str, topics: Vec<String>, data_hex: &str) -> Option<String> {
+    let topics = topics
+        .iter()
+        .map(|t| try_decode_hex(t))
+        .collect::<Option<Vec<_>>>()?;
+    let data = try_decode_hex(data_hex)?;
+    Some(Value::Object(Event::parse(event).decode_log(&topics, &data)?).to_string())
+}
+
+/// ERC-20 `transfer(address,uint256)` call data
+pub fn erc20_transfer(to: &str, amount: &str) -> Option<String> {
+    encode_call(
+        "transfer(address,uint256)",
+        &json!([to, amount]).to_string(),
+    )
+}
+
+/// ERC-20 `approve(address,uint256)` call data
+pub fn erc20_approve(spender: &str, amount: &str) -> Option<String> {
+    encode_call(
+        "approve(address,uint256)",
+        &json!([spender, amount]).to_string(),
+    )
+}
+
+/// ERC-721 `safeTransferFrom(address,address,uint256)` call data
+pub fn erc721_safe_transfer_from(from: &str, to: &str, token_id: &str) -> Option<String> {
+    encode_call(
+        "safeTransferFrom(address,address,uint256)",
+        &json!([from, to, token_id]).to_string(),
+    )
+}
+
+/// ERC-1155 `safeTransferFrom(address,address,uint256,uint256,bytes)` call data
+pub fn erc1155_safe_transfer_from(
+    from: &str,
+    to: &str,
+    id: &str,
+    amount: &str,
+    data_hex: &str,
+) -> Option<String> {
+    encode_call(
+        "safeTransferFrom(address,address,uint256,uint256,bytes)",
+        &json!([from, to, id, amount, data_hex]).to_string(),
+    )
+}
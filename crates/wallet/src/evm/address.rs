@@ -0,0 +1,112 @@
+use super::rlp::Item;
+use super::{decode_hex, keccak256};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hex::DisplayHex;
+use bitcoin::secp256k1::PublicKey;
+
+/// Lowercase hex of a `0x` prefixed 20 bytes address, `None` if it is not one
+fn address_hex(address: &str) -> Option<String> {
+    let hex = address.strip_prefix("0x")?;
+    (hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| hex.to_lowercase())
+}
+
+/// EIP-55 mixed-case checksum of a lowercase hex address, with the EIP-1191 chain id prefix
+/// when given
+fn checksum_hex(hex: &str, chain_id: Option<u64>) -> String {
+    let hash = match chain_id {
+        Some(chain_id) => keccak256(format!("{chain_id}0x{hex}").as_bytes()),
+        None => keccak256(hex.as_bytes()),
+    };
+
+    hex.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// EIP-55 checksummed address of 20 bytes
+pub(crate) fn address_from_bytes(bytes: &[u8]) -> String {
+    assert_eq!(bytes.len(), 20, "invalid address length");
+    format!("0x{}", checksum_hex(&bytes.as_hex().to_string(), None))
+}
+
+/// ### Checksum an address
+///
+/// chain_id:
+/// `None` for EIP-55, or the chain id to mix into the checksum per EIP-1191 (e.g. RSK 30)
+///
+/// returns `None` if the input is not a `0x` prefixed 20 bytes hex address
+pub fn to_checksum_address(address: &str, chain_id: Option<u64>) -> Option<String> {
+    address_hex(address).map(|hex| format!("0x{}", checksum_hex(&hex, chain_id)))
+}
+
+/// Whether the address is well formed and, when mixed-case, carries a valid checksum.
+/// All lowercase or all uppercase addresses carry no checksum and are accepted.
+pub fn is_valid_address(address: &str, chain_id: Option<u64>) -> bool {
+    let Some(hex) = address_hex(address) else {
+        return false;
+    };
+    let body = &address[2..];
+    body == hex || body == hex.to_uppercase() || is_checksum_address(address, chain_id)
+}
+
+/// Whether the address exactly matches its checksummed form
+pub fn is_checksum_address(address: &str, chain_id: Option<u64>) -> bool {
+    to_checksum_address(address, chain_id).is_some_and(|checksummed| checksummed == address)
+}
+
+/// ### Address of a public key
+///
+/// pubkey_hex:
+/// compressed or uncompressed secp256k1 public key in hex
+pub fn pubkey_to_address(pubkey_hex: &str) -> String {
+    let pubkey_hex = pubkey_hex.strip_prefix("0x").unwrap_or(pubkey_hex);
+    let pubkey = PublicKey::from_slice(&Vec::<u8>::from_hex(pubkey_hex).unwrap()).unwrap();
+    address_from_pubkey(&pubkey)
+}
+
+pub(crate) fn address_from_pubkey(pubkey: &PublicKey) -> String {
+    let output = keccak256(&pubkey.serialize_uncompressed()[1..]);
+    address_from_bytes(&output[12..])
+}
+
+/// ### Address of a contract deployed with `CREATE`
+///
+/// `keccak256(rlp([sender, nonce]))[12..]`
+pub fn create_address(sender: &str, nonce: u64) -> String {
+    let sender = decode_hex(sender);
+    assert_eq!(sender.len(), 20, "invalid address length");
+    let output = keccak256(&Item::List(vec![Item::Bytes(sender), Item::uint(nonce)]).encode());
+    address_from_bytes(&output[12..])
+}
+
+/// ### Address of a contract deployed with `CREATE2`
+///
+/// `keccak256(0xff || deployer || salt || keccak256(init_code))[12..]`
+///
+/// salt_hex:
+/// 32 bytes salt in hex
+///
+/// init_code_hash_hex:
+/// keccak256 of the contract creation code in hex
+pub fn create2_address(deployer: &str, salt_hex: &str, init_code_hash_hex: &str) -> String {
+    let deployer = decode_hex(deployer);
+    let salt = decode_hex(salt_hex);
+    let init_code_hash = decode_hex(init_code_hash_hex);
+    assert_eq!(deployer.len(), 20, "invalid address length");
+    assert_eq!(salt.len(), 32, "invalid salt length");
+    assert_eq!(init_code_hash.len(), 32, "invalid init code hash length");
+
+    let mut data = vec![0xff];
+    data.extend(deployer);
+    data.extend(salt);
+    data.extend(init_code_hash);
+    address_from_bytes(&keccak256(&data)[12..])
+}
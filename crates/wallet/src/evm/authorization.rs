@@ -0,0 +1,110 @@
+use super::address::address_from_bytes;
+use super::rlp::{trim_leading_zeros, Item};
+use super::transaction::{is_low_s, recover_address, sign_digest};
+use super::{decode_hex, keccak256};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hex::DisplayHex;
+
+/// Magic prefix of the EIP-7702 authorization signing hash
+const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// EIP-7702 authorization to delegate the code of an EOA to a contract
+pub struct Authorization {
+    /// 0 to authorize on every chain
+    pub chain_id: u64,
+    /// delegate contract address, the zero address clears the delegation
+    pub address: String,
+    /// nonce of the authority account at the time the authorization is processed
+    pub nonce: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedAuthorization {
+    pub chain_id: u64,
+    pub address: String,
+    pub nonce: u64,
+    pub y_parity: u8,
+    pub r: String,
+    pub s: String,
+}
+
+impl Authorization {
+    /// `keccak256(0x05 || rlp([chain_id, address, nonce]))`
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let address = decode_hex(&self.address);
+        assert_eq!(address.len(), 20, "invalid address length");
+        let mut data = vec![AUTHORIZATION_MAGIC];
+        data.extend(
+            Item::List(vec![
+                Item::uint(self.chain_id),
+                Item::Bytes(address),
+                Item::uint(self.nonce),
+            ])
+            .encode(),
+        );
+        keccak256(&data)
+    }
+}
+
+impl SignedAuthorization {
+    pub(crate) fn to_rlp(&self) -> Item {
+        Item::List(vec![
+            Item::uint(self.chain_id),
+            Item::Bytes(decode_hex(&self.address)),
+            Item::uint(self.nonce),
+            Item::uint(self.y_parity as u64),
+            Item::Bytes(trim_leading_zeros(&decode_hex(&self.r)).to_vec()),
+            Item::Bytes(trim_leading_zeros(&decode_hex(&self.s)).to_vec()),
+        ])
+    }
+
+    pub(crate) fn from_rlp(item: &Item) -> Option<Self> {
+        let [chain_id, address, nonce, y_parity, r, s] = item.as_list()? else {
+            return None;
+        };
+        let address = address.as_bytes()?;
+        Some(Self {
+            chain_id: chain_id.as_u64()?,
+            address: (address.len() == 20).then(|| address_from_bytes(address))?,
+            nonce: nonce.as_u64()?,
+            y_parity: u8::try_from(y_parity.as_u64()?).ok()?,
+            r: format!("0x{:0>64}", r.as_bytes()?.as_hex().to_string()),
+            s: format!("0x{:0>64}", s.as_bytes()?.as_hex().to_string()),
+        })
+    }
+}
+
+/// ### Sign an EIP-7702 authorization
+///
+/// priv_hex:
+/// private key of the authority account in hex, e.g. `HDWallet::evm_priv_hex`
+pub fn sign_authorization(priv_hex: &str, auth: Authorization) -> SignedAuthorization {
+    let (y_parity, r, s) = sign_digest(priv_hex, auth.signing_hash());
+    SignedAuthorization {
+        chain_id: auth.chain_id,
+        address: address_from_bytes(&decode_hex(&auth.address)),
+        nonce: auth.nonce,
+        y_parity,
+        r: format!("0x{}", r.as_hex()),
+        s: format!("0x{}", s.as_hex()),
+    }
+}
+
+/// Recover the address of the account which signed an authorization
+///
+/// returns `None` for invalid signatures, including a `y_parity` above 1 or a high `s` which
+/// EIP-7702 rejects
+pub fn recover_authority(auth: &SignedAuthorization) -> Option<String> {
+    let hex = |value: &str| Vec::<u8>::from_hex(value.strip_prefix("0x").unwrap_or(value)).ok();
+    let (r, s) = (hex(&auth.r)?, hex(&auth.s)?);
+    if auth.y_parity > 1 || !is_low_s(&s) {
+        return None;
+    }
+    let digest = Authorization {
+        chain_id: auth.chain_id,
+        address: auth.address.clone(),
+        nonce: auth.nonce,
+    }
+    .signing_hash();
+    recover_address(digest, auth.y_parity, &r, &s)
+}
@@ -0,0 +1,87 @@
+use super::keccak256;
+use bitcoin::hex::DisplayHex;
+
+/// `addr(bytes32)` of the ENS public resolver
+const ADDR_SELECTOR: [u8; 4] = [0x3b, 0x3b, 0x57, 0xde];
+
+/// Normalize a single ASCII label, `None` if it is disallowed
+fn normalize_label(label: &str) -> Option<String> {
+    // other characters need the mapped, disallowed, emoji and confusable tables of ENSIP-15
+    if label.is_empty() || !label.is_ascii() {
+        return None;
+    }
+    let label = label.to_ascii_lowercase();
+
+    // underscores are only allowed as a leading run
+    if label.trim_start_matches('_').contains('_') {
+        return None;
+    }
+    // label extensions like `xn--` are reserved
+    if label.len() >= 4 && &label[2..4] == "--" {
+        return None;
+    }
+    let valid = label
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '$'));
+    valid.then_some(label)
+}
+
+/// ### Normalize an ASCII ENS name
+///
+/// lowercases and applies the underscore, label extension and disallowed character rules,
+/// which is all ENSIP-15 does to ASCII names. Names with other characters, like emoji or the
+/// fullwidth `ｎｉｃｋ`, are rejected rather than resolved to another node, normalize them with
+/// a full ENSIP-15 implementation.
+///
+/// returns `None` if the name is invalid or not ASCII, e.g. `"Nick.ETH"` gives `"nick.eth"`
+pub fn ens_normalize_ascii(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some(String::new());
+    }
+    let labels = name
+        .split('.')
+        .map(normalize_label)
+        .collect::<Option<Vec<_>>>()?;
+    Some(labels.join("."))
+}
+
+/// `keccak256` of a single normalized label, `None` if the label is invalid or not ASCII
+pub fn ens_labelhash(label: &str) -> Option<String> {
+    let label = normalize_label(label)?;
+    Some(format!("0x{}", keccak256(label.as_bytes()).as_hex()))
+}
+
+fn namehash(name: &str) -> Option<[u8; 32]> {
+    let name = ens_normalize_ascii(name)?;
+    let mut node = [0u8; 32];
+    if name.is_empty() {
+        return Some(node);
+    }
+    for label in name.rsplit('.') {
+        let mut data = node.to_vec();
+        data.extend(keccak256(label.as_bytes()));
+        node = keccak256(&data);
+    }
+    Some(node)
+}
+
+/// ### ENS namehash (EIP-137) of a name
+///
+/// the name is normalized first, the empty name is the root node of 32 zero bytes
+///
+/// returns `None` if the name is invalid or not ASCII, see [`ens_normalize_ascii`]
+pub fn ens_namehash(name: &str) -> Option<String> {
+    Some(format!("0x{}", namehash(name)?.as_hex()))
+}
+
+/// ### Call data of `addr(bytes32 node)` for the resolver of a name
+///
+/// `eth_call` it on the resolver returned by the registry's `resolver(bytes32)`, the result is
+/// the address left padded to 32 bytes
+///
+/// returns `None` if the name is invalid or not ASCII
+pub fn ens_addr_call(name: &str) -> Option<String> {
+    let mut data = ADDR_SELECTOR.to_vec();
+    data.extend(namehash(name)?);
+    Some(format!("0x{}", data.as_hex()))
+}
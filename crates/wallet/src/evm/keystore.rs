@@ -0,0 +1,181 @@
+use super::address::address_from_pubkey;
+use super::{decode_hex, keccak256, try_decode_hex};
+use aes::cipher::{KeyIvInit, StreamCipher};
+use bitcoin::hex::DisplayHex;
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
+use bitcoin::secp256k1::{Secp256k1, SecretKey};
+use serde_json::{json, Value};
+use subtle::ConstantTimeEq;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Key derivation function of a V3 keystore
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeystoreKdf {
+    /// scrypt with geth's standard parameters, n = 2^18, r = 8, p = 1
+    Scrypt,
+    /// scrypt with geth's light parameters, n = 2^12, r = 8, p = 6, for mobile devices
+    ScryptLight,
+    /// pbkdf2 with hmac-sha256 and 262144 iterations
+    Pbkdf2,
+}
+
+impl KeystoreKdf {
+    fn params(&self, salt: &[u8]) -> (&'static str, Value) {
+        let salt = salt.as_hex().to_string();
+        match self {
+            KeystoreKdf::Scrypt => (
+                "scrypt",
+                json!({ "dklen": 32, "n": 262144, "p": 1, "r": 8, "salt": salt }),
+            ),
+            KeystoreKdf::ScryptLight => (
+                "scrypt",
+                json!({ "dklen": 32, "n": 4096, "p": 6, "r": 8, "salt": salt }),
+            ),
+            KeystoreKdf::Pbkdf2 => (
+                "pbkdf2",
+                json!({ "c": 262144, "dklen": 32, "prf": "hmac-sha256", "salt": salt }),
+            ),
+        }
+    }
+}
+
+/// Largest scrypt n accepted when decrypting, 2^20 needs 1 GiB with r = 8
+const MAX_SCRYPT_N: u64 = 1 << 20;
+/// Largest scrypt r and p accepted when decrypting
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+
+fn param_u32(params: &Value, name: &str) -> Option<u32> {
+    u32::try_from(params[name].as_u64()?).ok()
+}
+
+/// Derive the key from the password with the `kdf` and `kdfparams` of a keystore,
+/// `None` if the parameters are missing or unsupported
+fn derive_key(kdf: &str, params: &Value, password: &str) -> Option<Vec<u8>> {
+    let salt = try_decode_hex(params["salt"].as_str()?)?;
+    // the first half keys AES-128, the second half the MAC
+    if params["dklen"].as_u64()? != 32 {
+        return None;
+    }
+    let mut key = vec![0u8; 32];
+    match kdf {
+        "scrypt" => {
+            let n = params["n"].as_u64()?;
+            let r = param_u32(params, "r")?;
+            let p = param_u32(params, "p")?;
+            if !n.is_power_of_two() || n > MAX_SCRYPT_N || r > MAX_SCRYPT_R || p > MAX_SCRYPT_P {
+                return None;
+            }
+            let params = scrypt::Params::new(n.trailing_zeros() as u8, r, p, key.len()).ok()?;
+            scrypt::scrypt(password.as_bytes(), &salt, &params, &mut key).ok()?;
+        }
+        "pbkdf2" => {
+            let c = param_u32(params, "c")?;
+            if params["prf"] != "hmac-sha256" || c == 0 {
+                return None;
+            }
+            pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), &salt, c, &mut key);
+        }
+        _ => return None,
+    }
+    Some(key)
+}
+
+/// `keccak256(derived_key[16..32] || ciphertext)`
+fn mac(key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut data = key[16..32].to_vec();
+    data.extend(ciphertext);
+    keccak256(&data)
+}
+
+/// Random UUID v4
+fn uuid_v4() -> String {
+    let mut bytes = [0u8; 16];
+    thread_rng().fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = bytes.as_hex().to_string();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// ### Encrypt a private key into a Web3 Secret Storage V3 keystore
+///
+/// priv_hex:
+/// 32 bytes private key in hex, e.g. `HDWallet::evm_priv_hex`
+///
+/// returns the keystore JSON as written by geth and MetaMask
+pub fn encrypt_keystore(priv_hex: &str, password: &str, kdf: KeystoreKdf) -> String {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&decode_hex(priv_hex)).unwrap();
+    let address = address_from_pubkey(&secret_key.public_key(&secp));
+
+    let mut salt = [0u8; 32];
+    let mut iv = [0u8; 16];
+    thread_rng().fill_bytes(&mut salt);
+    thread_rng().fill_bytes(&mut iv);
+
+    let (kdf_name, kdf_params) = kdf.params(&salt);
+    let key = derive_key(kdf_name, &kdf_params, password).expect("invalid kdf params");
+
+    let mut ciphertext = secret_key.secret_bytes().to_vec();
+    Aes128Ctr::new(key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+
+    json!({
+        "address": address[2..].to_lowercase(),
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": iv.as_hex().to_string() },
+            "ciphertext": ciphertext.as_hex().to_string(),
+            "kdf": kdf_name,
+            "kdfparams": kdf_params,
+            "mac": mac(&key, &ciphertext).as_hex().to_string(),
+        },
+        "id": uuid_v4(),
+        "version": 3,
+    })
+    .to_string()
+}
+
+/// ### Decrypt a Web3 Secret Storage V3 keystore
+///
+/// supports the scrypt and pbkdf2 (hmac-sha256) KDFs with AES-128-CTR
+///
+/// returns the private key in hex, `None` if the password is wrong or the keystore is malformed
+/// or uses unsupported parameters
+pub fn decrypt_keystore(keystore_json: &str, password: &str) -> Option<String> {
+    let keystore: Value = serde_json::from_str(keystore_json).ok()?;
+    if keystore["version"] != 3 {
+        return None;
+    }
+    // MyEtherWallet writes `Crypto`
+    let crypto = match &keystore["crypto"] {
+        Value::Null => &keystore["Crypto"],
+        crypto => crypto,
+    };
+    if crypto["cipher"] != "aes-128-ctr" {
+        return None;
+    }
+
+    let kdf = crypto["kdf"].as_str()?;
+    let key = derive_key(kdf, &crypto["kdfparams"], password)?;
+    let ciphertext = try_decode_hex(crypto["ciphertext"].as_str()?)?;
+    let expected = try_decode_hex(crypto["mac"].as_str()?)?;
+    if !bool::from(mac(&key, &ciphertext)[..].ct_eq(&expected)) {
+        return None;
+    }
+
+    let iv: [u8; 16] = try_decode_hex(crypto["cipherparams"]["iv"].as_str()?)?
+        .try_into()
+        .ok()?;
+    let mut plaintext = ciphertext;
+    Aes128Ctr::new(key[..16].into(), &iv.into()).apply_keystream(&mut plaintext);
+    Some(plaintext.as_hex().to_string())
+}
@@ -0,0 +1,72 @@
+use super::keccak256;
+use super::transaction::{recover_address, sign_digest};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hex::DisplayHex;
+
+/// Message bytes from `0x` prefixed hex, otherwise the UTF-8 bytes of the string, e.g. for
+/// `0x is cool`
+fn message_bytes(message: &str) -> Vec<u8> {
+    match message
+        .strip_prefix("0x")
+        .and_then(|hex| Vec::<u8>::from_hex(hex).ok())
+    {
+        Some(bytes) => bytes,
+        None => message.as_bytes().to_vec(),
+    }
+}
+
+/// EIP-191 version `0x45` hash, `keccak256("\x19Ethereum Signed Message:\n" || len || message)`
+pub fn hash_personal_message(message: &str) -> [u8; 32] {
+    let message = message_bytes(message);
+    let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    data.extend(message);
+    keccak256(&data)
+}
+
+/// ### Sign a message like `personal_sign`
+///
+/// priv_hex:
+/// private key in hex, e.g. `HDWallet::evm_priv_hex`
+///
+/// message:
+/// `0x` prefixed hex of the message bytes, or plain text signed as UTF-8, including text
+/// starting with `0x` which isn't valid hex
+///
+/// returns the 65 bytes signature `r || s || v` in hex where `v` is 27 or 28
+pub fn personal_sign(priv_hex: &str, message: &str) -> String {
+    let (recid, r, s) = sign_digest(priv_hex, hash_personal_message(message));
+    format!("0x{}{}{:02x}", r.as_hex(), s.as_hex(), 27 + recid)
+}
+
+/// Recover the checksummed address which produced a `personal_sign` signature, `None` if the
+/// signature is malformed or invalid
+pub fn recover_personal_signer(message: &str, signature_hex: &str) -> Option<String> {
+    let (y_parity, r, s) = split_signature(signature_hex)?;
+    recover_address(hash_personal_message(message), y_parity, &r, &s)
+}
+
+/// Verify a `personal_sign` signature against an address, ignoring its case, `false` if the
+/// signature is malformed
+pub fn verify_personal_signature(address: &str, message: &str, signature_hex: &str) -> bool {
+    recover_personal_signer(message, signature_hex)
+        .is_some_and(|signer| signer.eq_ignore_ascii_case(address))
+}
+
+/// Split a 65 bytes `r || s || v` signature, accepting `v` as 0/1 or 27/28
+pub(crate) fn split_signature(signature_hex: &str) -> Option<(u8, [u8; 32], [u8; 32])> {
+    let signature =
+        Vec::<u8>::from_hex(signature_hex.strip_prefix("0x").unwrap_or(signature_hex)).ok()?;
+    if signature.len() != 65 {
+        return None;
+    }
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&signature[..32]);
+    s.copy_from_slice(&signature[32..64]);
+    let y_parity = match signature[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        _ => return None,
+    };
+    Some((y_parity, r, s))
+}
@@ -0,0 +1,164 @@
+pub mod abi;
+pub mod address;
+pub mod authorization;
+pub mod ens;
+pub mod keystore;
+pub mod message;
+pub mod rlp;
+pub mod transaction;
+pub mod typed_data;
+pub mod user_operation;
+
+pub use address::{
+    create2_address, create_address, is_checksum_address, is_valid_address, pubkey_to_address,
+    to_checksum_address,
+};
+pub use authorization::{
+    recover_authority, sign_authorization, Authorization, SignedAuthorization,
+};
+pub use ens::{ens_addr_call, ens_labelhash, ens_namehash, ens_normalize_ascii};
+pub use keystore::{decrypt_keystore, encrypt_keystore, KeystoreKdf};
+pub use message::{
+    hash_personal_message, personal_sign, recover_personal_signer, verify_personal_signature,
+};
+pub use transaction::{
+    decode_transaction, sign_access_list_transaction, sign_eip1559_transaction,
+    sign_legacy_transaction, sign_set_code_transaction, AccessListItem, AccessListTransaction,
+    DecodedTransaction, Eip1559Transaction, LegacyTransaction, SetCodeTransaction,
+    SignedTransaction,
+};
+pub use typed_data::{hash_typed_data, recover_typed_data_signer, sign_typed_data, TypedData};
+pub use user_operation::{
+    pack_user_operation_v07, sign_user_operation_v06, sign_user_operation_v07,
+    user_operation_v06_hash, user_operation_v07_hash, PackedUserOperation, UserOperationV06,
+    UserOperationV07,
+};
+
+use bitcoin::hashes::hex::FromHex;
+use serde_json::Value;
+use tiny_keccak::{Hasher, Keccak};
+
+/// Keccak-256 as used by Ethereum, not the standardized SHA3-256
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut output = [0u8; 32];
+
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    hasher.finalize(&mut output);
+
+    output
+}
+
+/// Decode a hex string with or without the `0x` prefix
+pub(crate) fn decode_hex(hex: &str) -> Vec<u8> {
+    try_decode_hex(hex).unwrap()
+}
+
+/// Like [`decode_hex`], `None` for invalid hex
+pub(crate) fn try_decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    Vec::<u8>::from_hex(hex).ok()
+}
+
+/// Parse an unsigned 256-bit integer given in decimal or `0x` prefixed hex
+/// into its big-endian bytes without leading zeros
+pub(crate) fn parse_uint(value: &str) -> Vec<u8> {
+    try_parse_uint(value).unwrap_or_else(|| panic!("invalid uint256 {value}"))
+}
+
+/// Like [`parse_uint`], `None` for invalid digits, negative values or overflow
+pub(crate) fn try_parse_uint(value: &str) -> Option<Vec<u8>> {
+    let bytes = match value.strip_prefix("0x") {
+        Some(hex) if hex.len() % 2 == 1 => try_decode_hex(&format!("0{hex}"))?,
+        Some(hex) => try_decode_hex(hex)?,
+        None if value.is_empty() => return None,
+        None => {
+            let mut bytes = Vec::<u8>::new();
+            for c in value.chars() {
+                let mut carry = c.to_digit(10)?;
+                for b in bytes.iter_mut().rev() {
+                    let v = *b as u32 * 10 + carry;
+                    *b = v as u8;
+                    carry = v >> 8;
+                }
+                if carry > 0 {
+                    bytes.insert(0, carry as u8);
+                }
+            }
+            bytes
+        }
+    };
+    let bytes = rlp::trim_leading_zeros(&bytes).to_vec();
+    (bytes.len() <= 32).then_some(bytes)
+}
+
+/// Format big-endian bytes of an unsigned integer as a decimal string
+pub(crate) fn format_uint(bytes: &[u8]) -> String {
+    let mut digits = vec![0u8];
+    for b in bytes {
+        let mut carry = *b as u32;
+        for d in digits.iter_mut() {
+            let v = *d as u32 * 256 + carry;
+            *d = (v % 10) as u8;
+            carry = v / 10;
+        }
+        while carry > 0 {
+            digits.push((carry % 10) as u8);
+            carry /= 10;
+        }
+    }
+    digits.iter().rev().map(|d| (b'0' + d) as char).collect()
+}
+
+/// Integer given as a JSON number or a decimal or `0x` prefixed hex string
+fn number_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// 32 bytes big-endian word of an unsigned integer
+pub(crate) fn uint_word(value: &Value) -> [u8; 32] {
+    try_uint_word(value).unwrap_or_else(|| panic!("invalid uint256 {value}"))
+}
+
+/// Like [`uint_word`], `None` if the value isn't a uint256
+pub(crate) fn try_uint_word(value: &Value) -> Option<[u8; 32]> {
+    let bytes = try_parse_uint(&number_string(value)?)?;
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    Some(word)
+}
+
+/// 32 bytes two's complement word of a signed integer
+pub(crate) fn int_word(value: &Value) -> [u8; 32] {
+    try_int_word(value).unwrap_or_else(|| panic!("invalid int256 {value}"))
+}
+
+/// Like [`int_word`], `None` if the value isn't an int256
+pub(crate) fn try_int_word(value: &Value) -> Option<[u8; 32]> {
+    let number = number_string(value)?;
+    let (negative, abs) = match number.strip_prefix('-') {
+        Some(abs) => (true, abs),
+        None => (false, number.as_str()),
+    };
+    let abs = try_uint_word(&Value::String(abs.to_string()))?;
+    let word = if negative { negate(&abs) } else { abs };
+    // the sign bit must match, only -2^255 is its own two's complement
+    let sign = word[0] & 0x80 != 0;
+    (sign == (negative && abs != [0u8; 32])).then_some(word)
+}
+
+/// Two's complement negation of a 32 bytes word
+pub(crate) fn negate(word: &[u8; 32]) -> [u8; 32] {
+    let mut negated = *word;
+    let mut carry = true;
+    for b in negated.iter_mut().rev() {
+        let (v, overflow) = (!*b).overflowing_add(carry as u8);
+        *b = v;
+        carry = overflow;
+    }
+    negated
+}
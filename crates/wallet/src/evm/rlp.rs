@@ -0,0 +1,120 @@
+/// A value in the recursive length prefix encoding, either a byte string or a list of values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Bytes(Vec<u8>),
+    List(Vec<Item>),
+}
+
+impl Item {
+    /// Unsigned integer as a big-endian byte string without leading zeros
+    pub fn uint(value: u64) -> Self {
+        Item::Bytes(trim_leading_zeros(&value.to_be_bytes()).to_vec())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Item::Bytes(bytes) if bytes.len() == 1 && bytes[0] < 0x80 => bytes.clone(),
+            Item::Bytes(bytes) => {
+                let mut out = encode_length(bytes.len(), 0x80);
+                out.extend_from_slice(bytes);
+                out
+            }
+            Item::List(items) => {
+                let payload = items.iter().flat_map(Item::encode).collect::<Vec<_>>();
+                let mut out = encode_length(payload.len(), 0xc0);
+                out.extend(payload);
+                out
+            }
+        }
+    }
+}
+
+impl Item {
+    /// Decode a single item which must span the whole input, `None` for malformed or truncated
+    /// input
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (item, rest) = Self::decode_partial(data)?;
+        rest.is_empty().then_some(item)
+    }
+
+    fn decode_partial(data: &[u8]) -> Option<(Self, &[u8])> {
+        let prefix = *data.first()?;
+        let (is_list, offset, len) = match prefix {
+            0x00..=0x7f => return Some((Item::Bytes(vec![prefix]), &data[1..])),
+            0x80..=0xb7 => (false, 1, (prefix - 0x80) as usize),
+            0xb8..=0xbf => {
+                let len_of_len = (prefix - 0xb7) as usize;
+                (
+                    false,
+                    1 + len_of_len,
+                    decode_length(data.get(1..1 + len_of_len)?)?,
+                )
+            }
+            0xc0..=0xf7 => (true, 1, (prefix - 0xc0) as usize),
+            0xf8..=0xff => {
+                let len_of_len = (prefix - 0xf7) as usize;
+                (
+                    true,
+                    1 + len_of_len,
+                    decode_length(data.get(1..1 + len_of_len)?)?,
+                )
+            }
+        };
+        let end = offset.checked_add(len)?;
+        let payload = data.get(offset..end)?;
+        let rest = &data[end..];
+        if !is_list {
+            return Some((Item::Bytes(payload.to_vec()), rest));
+        }
+
+        let mut items = Vec::new();
+        let mut payload = payload;
+        while !payload.is_empty() {
+            let (item, remaining) = Self::decode_partial(payload)?;
+            items.push(item);
+            payload = remaining;
+        }
+        Some((Item::List(items), rest))
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Item::Bytes(bytes) => Some(bytes),
+            Item::List(_) => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Item]> {
+        match self {
+            Item::List(items) => Some(items),
+            Item::Bytes(_) => None,
+        }
+    }
+
+    /// `None` for a list or an integer above `u64`
+    pub fn as_u64(&self) -> Option<u64> {
+        let bytes = self.as_bytes()?;
+        (bytes.len() <= 8).then(|| bytes.iter().fold(0, |acc, b| acc << 8 | *b as u64))
+    }
+}
+
+fn decode_length(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().try_fold(0usize, |acc, b| {
+        acc.checked_mul(256).map(|acc| acc | *b as usize)
+    })
+}
+
+fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+    let len_bytes = trim_leading_zeros(&len.to_be_bytes()).to_vec();
+    let mut out = vec![offset + 55 + len_bytes.len() as u8];
+    out.extend(len_bytes);
+    out
+}
+
+pub(crate) fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
@@ -0,0 +1,514 @@
+use super::address::{address_from_bytes, address_from_pubkey};
+use super::authorization::SignedAuthorization;
+use super::rlp::{trim_leading_zeros, Item};
+use super::{decode_hex, format_uint, keccak256, parse_uint};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hex::DisplayHex;
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+use std::str::FromStr;
+
+const ACCESS_LIST_TX_TYPE: u8 = 0x01;
+const EIP1559_TX_TYPE: u8 = 0x02;
+const SET_CODE_TX_TYPE: u8 = 0x04;
+
+/// Half the secp256k1 curve order, the largest `s` allowed by EIP-2
+const HALF_CURVE_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// Legacy transaction, replay protected by EIP-155 when `chain_id` is not 0
+pub struct LegacyTransaction {
+    pub nonce: u64,
+    /// gas price in wei, decimal or `0x` prefixed hex
+    pub gas_price: String,
+    pub gas_limit: u64,
+    /// recipient address, `None` for contract creation
+    pub to: Option<String>,
+    /// value in wei, decimal or `0x` prefixed hex
+    pub value: String,
+    /// call data in hex
+    pub data: String,
+    pub chain_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: String,
+    /// 32 bytes storage slots in hex
+    pub storage_keys: Vec<String>,
+}
+
+/// EIP-2930 transaction, type `0x01`
+pub struct AccessListTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_price: String,
+    pub gas_limit: u64,
+    pub to: Option<String>,
+    pub value: String,
+    pub data: String,
+    pub access_list: Vec<AccessListItem>,
+}
+
+/// EIP-1559 transaction, type `0x02`
+pub struct Eip1559Transaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    /// tip in wei, decimal or `0x` prefixed hex
+    pub max_priority_fee_per_gas: String,
+    /// fee cap in wei, decimal or `0x` prefixed hex
+    pub max_fee_per_gas: String,
+    pub gas_limit: u64,
+    pub to: Option<String>,
+    pub value: String,
+    pub data: String,
+    pub access_list: Vec<AccessListItem>,
+}
+
+/// EIP-7702 set code transaction, type `0x04`
+pub struct SetCodeTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: String,
+    pub max_fee_per_gas: String,
+    pub gas_limit: u64,
+    /// set code transactions cannot create contracts
+    pub to: String,
+    pub value: String,
+    pub data: String,
+    pub access_list: Vec<AccessListItem>,
+    pub authorization_list: Vec<SignedAuthorization>,
+}
+
+pub struct SignedTransaction {
+    /// signed transaction ready for `eth_sendRawTransaction`
+    pub raw_tx_hex: String,
+    pub tx_hash: String,
+}
+
+/// Fields of a signed transaction for display, amounts are decimal strings in wei
+pub struct DecodedTransaction {
+    /// 0 for legacy, 1 for EIP-2930, 2 for EIP-1559 and 4 for EIP-7702
+    pub tx_type: u8,
+    /// 0 for a legacy transaction without EIP-155 replay protection
+    pub chain_id: u64,
+    pub nonce: u64,
+    /// set for legacy and EIP-2930 transactions
+    pub gas_price: Option<String>,
+    /// set for EIP-1559 and EIP-7702 transactions
+    pub max_priority_fee_per_gas: Option<String>,
+    /// set for EIP-1559 and EIP-7702 transactions
+    pub max_fee_per_gas: Option<String>,
+    pub gas_limit: u64,
+    pub to: Option<String>,
+    pub value: String,
+    pub data: String,
+    pub access_list: Vec<AccessListItem>,
+    /// set for EIP-7702 transactions
+    pub authorization_list: Vec<SignedAuthorization>,
+    /// signature y parity, derived from `v` for legacy transactions
+    pub y_parity: u8,
+    pub r: String,
+    pub s: String,
+    /// recovered sender address
+    pub from: String,
+    pub tx_hash: String,
+}
+
+impl LegacyTransaction {
+    fn fields(&self) -> Vec<Item> {
+        vec![
+            Item::uint(self.nonce),
+            Item::Bytes(parse_uint(&self.gas_price)),
+            Item::uint(self.gas_limit),
+            encode_to(&self.to),
+            Item::Bytes(parse_uint(&self.value)),
+            Item::Bytes(decode_hex(&self.data)),
+        ]
+    }
+
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut fields = self.fields();
+        if self.chain_id != 0 {
+            fields.extend([Item::uint(self.chain_id), Item::uint(0), Item::uint(0)]);
+        }
+        keccak256(&Item::List(fields).encode())
+    }
+}
+
+impl AccessListTransaction {
+    fn fields(&self) -> Vec<Item> {
+        vec![
+            Item::uint(self.chain_id),
+            Item::uint(self.nonce),
+            Item::Bytes(parse_uint(&self.gas_price)),
+            Item::uint(self.gas_limit),
+            encode_to(&self.to),
+            Item::Bytes(parse_uint(&self.value)),
+            Item::Bytes(decode_hex(&self.data)),
+            encode_access_list(&self.access_list),
+        ]
+    }
+
+    pub fn signing_hash(&self) -> [u8; 32] {
+        typed_signing_hash(ACCESS_LIST_TX_TYPE, self.fields())
+    }
+}
+
+impl Eip1559Transaction {
+    fn fields(&self) -> Vec<Item> {
+        vec![
+            Item::uint(self.chain_id),
+            Item::uint(self.nonce),
+            Item::Bytes(parse_uint(&self.max_priority_fee_per_gas)),
+            Item::Bytes(parse_uint(&self.max_fee_per_gas)),
+            Item::uint(self.gas_limit),
+            encode_to(&self.to),
+            Item::Bytes(parse_uint(&self.value)),
+            Item::Bytes(decode_hex(&self.data)),
+            encode_access_list(&self.access_list),
+        ]
+    }
+
+    pub fn signing_hash(&self) -> [u8; 32] {
+        typed_signing_hash(EIP1559_TX_TYPE, self.fields())
+    }
+}
+
+impl SetCodeTransaction {
+    fn fields(&self) -> Vec<Item> {
+        vec![
+            Item::uint(self.chain_id),
+            Item::uint(self.nonce),
+            Item::Bytes(parse_uint(&self.max_priority_fee_per_gas)),
+            Item::Bytes(parse_uint(&self.max_fee_per_gas)),
+            Item::uint(self.gas_limit),
+            Item::Bytes(decode_address(&self.to)),
+            Item::Bytes(parse_uint(&self.value)),
+            Item::Bytes(decode_hex(&self.data)),
+            encode_access_list(&self.access_list),
+            Item::List(
+                self.authorization_list
+                    .iter()
+                    .map(SignedAuthorization::to_rlp)
+                    .collect(),
+            ),
+        ]
+    }
+
+    pub fn signing_hash(&self) -> [u8; 32] {
+        typed_signing_hash(SET_CODE_TX_TYPE, self.fields())
+    }
+}
+
+fn typed_signing_hash(tx_type: u8, fields: Vec<Item>) -> [u8; 32] {
+    let mut payload = vec![tx_type];
+    payload.extend(Item::List(fields).encode());
+    keccak256(&payload)
+}
+
+fn encode_to(to: &Option<String>) -> Item {
+    Item::Bytes(to.as_deref().map(decode_address).unwrap_or_default())
+}
+
+fn encode_access_list(access_list: &[AccessListItem]) -> Item {
+    Item::List(
+        access_list
+            .iter()
+            .map(|item| {
+                Item::List(vec![
+                    Item::Bytes(decode_address(&item.address)),
+                    Item::List(
+                        item.storage_keys
+                            .iter()
+                            .map(|key| {
+                                let key = decode_hex(key);
+                                assert_eq!(key.len(), 32, "invalid storage key length");
+                                Item::Bytes(key)
+                            })
+                            .collect(),
+                    ),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn decode_address(address: &str) -> Vec<u8> {
+    let bytes = decode_hex(address);
+    assert_eq!(bytes.len(), 20, "invalid address length");
+    bytes
+}
+
+/// Sign a digest returning the recovery id and the 32 bytes `r` and `s`
+pub(crate) fn sign_digest(priv_hex: &str, digest: [u8; 32]) -> (u8, [u8; 32], [u8; 32]) {
+    let secp = Secp256k1::new();
+    let private_key = SecretKey::from_str(priv_hex.strip_prefix("0x").unwrap_or(priv_hex)).unwrap();
+    let msg = Message::from_digest(digest);
+    let (recid, sig) = secp
+        .sign_ecdsa_recoverable(&msg, &private_key)
+        .serialize_compact();
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&sig[..32]);
+    s.copy_from_slice(&sig[32..]);
+    (recid.to_i32() as u8, r, s)
+}
+
+/// Recover the checksummed address which signed a digest, `None` for an invalid signature
+pub(crate) fn recover_address(
+    digest: [u8; 32],
+    y_parity: u8,
+    r: &[u8],
+    s: &[u8],
+) -> Option<String> {
+    if r.len() > 32 || s.len() > 32 {
+        return None;
+    }
+    let secp = Secp256k1::new();
+    let mut compact = [0u8; 64];
+    compact[32 - r.len()..32].copy_from_slice(r);
+    compact[64 - s.len()..].copy_from_slice(s);
+    let sig =
+        RecoverableSignature::from_compact(&compact, RecoveryId::from_i32(y_parity as i32).ok()?)
+            .ok()?;
+    let pubkey = secp
+        .recover_ecdsa(&Message::from_digest(digest), &sig)
+        .ok()?;
+    Some(address_from_pubkey(&pubkey))
+}
+
+/// Whether `s` is at most half the curve order, rejecting the malleated twin of a signature
+pub(crate) fn is_low_s(s: &[u8]) -> bool {
+    if s.len() > 32 {
+        return false;
+    }
+    let mut padded = [0u8; 32];
+    padded[32 - s.len()..].copy_from_slice(s);
+    padded <= HALF_CURVE_ORDER
+}
+
+fn signature_fields(y_parity_or_v: u64, r: &[u8; 32], s: &[u8; 32]) -> [Item; 3] {
+    [
+        Item::uint(y_parity_or_v),
+        Item::Bytes(trim_leading_zeros(r).to_vec()),
+        Item::Bytes(trim_leading_zeros(s).to_vec()),
+    ]
+}
+
+fn signed(raw_tx: Vec<u8>) -> SignedTransaction {
+    SignedTransaction {
+        tx_hash: format!("0x{}", keccak256(&raw_tx).as_hex()),
+        raw_tx_hex: format!("0x{}", raw_tx.as_hex()),
+    }
+}
+
+fn sign_typed(priv_hex: &str, tx_type: u8, mut fields: Vec<Item>) -> SignedTransaction {
+    let (y_parity, r, s) = sign_digest(priv_hex, typed_signing_hash(tx_type, fields.clone()));
+    fields.extend(signature_fields(y_parity as u64, &r, &s));
+
+    let mut raw_tx = vec![tx_type];
+    raw_tx.extend(Item::List(fields).encode());
+    signed(raw_tx)
+}
+
+/// ### Sign a legacy transaction
+///
+/// priv_hex:
+/// private key in hex, e.g. `HDWallet::evm_priv_hex`
+///
+/// tx:
+/// unsigned transaction, `v` is computed as `chain_id * 2 + 35 + recid` per EIP-155
+/// or `27 + recid` when `chain_id` is 0
+pub fn sign_legacy_transaction(priv_hex: &str, tx: LegacyTransaction) -> SignedTransaction {
+    let (recid, r, s) = sign_digest(priv_hex, tx.signing_hash());
+    let v = match tx.chain_id {
+        0 => 27 + recid as u64,
+        chain_id => chain_id
+            .checked_mul(2)
+            .and_then(|v| v.checked_add(35 + recid as u64))
+            .expect("chain id too large for EIP-155"),
+    };
+
+    let mut fields = tx.fields();
+    fields.extend(signature_fields(v, &r, &s));
+
+    signed(Item::List(fields).encode())
+}
+
+/// ### Sign an EIP-2930 transaction
+///
+/// The raw transaction is `0x01 || rlp([chain_id, nonce, gas_price, gas_limit, to, value, data,
+/// access_list, y_parity, r, s])`
+pub fn sign_access_list_transaction(
+    priv_hex: &str,
+    tx: AccessListTransaction,
+) -> SignedTransaction {
+    sign_typed(priv_hex, ACCESS_LIST_TX_TYPE, tx.fields())
+}
+
+/// ### Sign an EIP-1559 transaction
+///
+/// The raw transaction is `0x02 || rlp([chain_id, nonce, max_priority_fee_per_gas,
+/// max_fee_per_gas, gas_limit, to, value, data, access_list, y_parity, r, s])`
+pub fn sign_eip1559_transaction(priv_hex: &str, tx: Eip1559Transaction) -> SignedTransaction {
+    sign_typed(priv_hex, EIP1559_TX_TYPE, tx.fields())
+}
+
+/// ### Sign an EIP-7702 set code transaction
+///
+/// The raw transaction is `0x04 || rlp([chain_id, nonce, max_priority_fee_per_gas,
+/// max_fee_per_gas, gas_limit, to, value, data, access_list, authorization_list, y_parity, r, s])`
+///
+/// tx:
+/// unsigned transaction whose authorizations are signed with `sign_authorization`
+pub fn sign_set_code_transaction(priv_hex: &str, tx: SetCodeTransaction) -> SignedTransaction {
+    assert!(
+        !tx.authorization_list.is_empty(),
+        "empty authorization list"
+    );
+    sign_typed(priv_hex, SET_CODE_TX_TYPE, tx.fields())
+}
+
+fn decode_access_list(item: &Item) -> Option<Vec<AccessListItem>> {
+    item.as_list()?
+        .iter()
+        .map(|entry| {
+            let [address, storage_keys] = entry.as_list()? else {
+                return None;
+            };
+            Some(AccessListItem {
+                address: decode_address_item(address)?,
+                storage_keys: storage_keys
+                    .as_list()?
+                    .iter()
+                    .map(|key| Some(format!("0x{}", key.as_bytes()?.as_hex())))
+                    .collect::<Option<_>>()?,
+            })
+        })
+        .collect()
+}
+
+fn decode_address_item(item: &Item) -> Option<String> {
+    let address = item.as_bytes()?;
+    (address.len() == 20).then(|| address_from_bytes(address))
+}
+
+/// `Some(None)` for contract creation
+fn decode_to(item: &Item) -> Option<Option<String>> {
+    match item.as_bytes()? {
+        [] => Some(None),
+        _ => decode_address_item(item).map(Some),
+    }
+}
+
+/// ### Decode a signed raw transaction
+///
+/// raw_tx_hex:
+/// signed legacy, EIP-2930, EIP-1559 or EIP-7702 transaction in hex
+///
+/// returns `None` for malformed or truncated transactions, or invalid signatures including the
+/// high `s` ones rejected by EIP-2
+pub fn decode_transaction(raw_tx_hex: &str) -> Option<DecodedTransaction> {
+    let raw_tx = Vec::<u8>::from_hex(raw_tx_hex.strip_prefix("0x").unwrap_or(raw_tx_hex)).ok()?;
+    let tx_hash = format!("0x{}", keccak256(&raw_tx).as_hex());
+
+    let (tx_type, item, field_count) = match *raw_tx.first()? {
+        ACCESS_LIST_TX_TYPE => (ACCESS_LIST_TX_TYPE, Item::decode(&raw_tx[1..])?, 11),
+        EIP1559_TX_TYPE => (EIP1559_TX_TYPE, Item::decode(&raw_tx[1..])?, 12),
+        SET_CODE_TX_TYPE => (SET_CODE_TX_TYPE, Item::decode(&raw_tx[1..])?, 13),
+        0xc0..=0xff => (0, Item::decode(&raw_tx)?, 9),
+        _ => return None,
+    };
+    let fields = item.as_list()?;
+    if fields.len() != field_count {
+        return None;
+    }
+
+    let (signed_fields, signature) = fields.split_at(fields.len() - 3);
+    let v = signature[0].as_u64()?;
+    let r = signature[1].as_bytes()?;
+    let s = signature[2].as_bytes()?;
+    if !is_low_s(s) {
+        return None;
+    }
+
+    let (chain_id, y_parity) = match tx_type {
+        0 if v == 27 || v == 28 => (0, (v - 27) as u8),
+        0 => {
+            let v = v.checked_sub(35)?;
+            (v / 2, (v % 2) as u8)
+        }
+        _ => match v {
+            0 | 1 => (signed_fields[0].as_u64()?, v as u8),
+            _ => return None,
+        },
+    };
+
+    let signing_hash = match tx_type {
+        0 => {
+            let mut unsigned = signed_fields.to_vec();
+            if chain_id != 0 {
+                unsigned.extend([Item::uint(chain_id), Item::uint(0), Item::uint(0)]);
+            }
+            keccak256(&Item::List(unsigned).encode())
+        }
+        _ => typed_signing_hash(tx_type, signed_fields.to_vec()),
+    };
+    let from = recover_address(signing_hash, y_parity, r, s)?;
+
+    let uint = |i: usize| signed_fields[i].as_bytes().map(format_uint);
+    let data = |i: usize| {
+        signed_fields[i]
+            .as_bytes()
+            .map(|data| format!("0x{}", data.as_hex()))
+    };
+    let (gas_price, max_priority_fee_per_gas, max_fee_per_gas, rest, access_list) = match tx_type {
+        0 => (Some(uint(1)?), None, None, 2, Vec::new()),
+        ACCESS_LIST_TX_TYPE => (
+            Some(uint(2)?),
+            None,
+            None,
+            3,
+            decode_access_list(&signed_fields[7])?,
+        ),
+        _ => (
+            None,
+            Some(uint(2)?),
+            Some(uint(3)?),
+            4,
+            decode_access_list(&signed_fields[8])?,
+        ),
+    };
+    let nonce = signed_fields[if tx_type == 0 { 0 } else { 1 }].as_u64()?;
+    let authorization_list = match tx_type {
+        SET_CODE_TX_TYPE => signed_fields[9]
+            .as_list()?
+            .iter()
+            .map(SignedAuthorization::from_rlp)
+            .collect::<Option<_>>()?,
+        _ => Vec::new(),
+    };
+
+    Some(DecodedTransaction {
+        tx_type,
+        chain_id,
+        nonce,
+        gas_price,
+        max_priority_fee_per_gas,
+        max_fee_per_gas,
+        gas_limit: signed_fields[rest].as_u64()?,
+        to: decode_to(&signed_fields[rest + 1])?,
+        value: uint(rest + 2)?,
+        data: data(rest + 3)?,
+        access_list,
+        authorization_list,
+        y_parity,
+        r: format!("0x{:0>64}", r.as_hex().to_string()),
+        s: format!("0x{:0>64}", s.as_hex().to_string()),
+        from,
+        tx_hash,
+    })
+}
@@ -0,0 +1,186 @@
+use super::message::split_signature;
+use super::transaction::{recover_address, sign_digest};
+use super::{decode_hex, int_word, keccak256, uint_word};
+use bitcoin::hex::DisplayHex;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Fields of `EIP712Domain` in their canonical order, used when `types` omits the domain type
+const DOMAIN_FIELDS: [(&str, &str); 5] = [
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+    ("salt", "bytes32"),
+];
+
+/// Typed structured data as sent to `eth_signTypedData_v4`
+pub struct TypedData {
+    types: Map<String, Value>,
+    primary_type: String,
+    domain: Value,
+    message: Value,
+}
+
+impl TypedData {
+    pub fn from_json(json: &str) -> Self {
+        let value: Value = serde_json::from_str(json).unwrap();
+        let mut types = value["types"].as_object().unwrap().clone();
+        let domain = value["domain"].clone();
+        if !types.contains_key("EIP712Domain") {
+            let fields = DOMAIN_FIELDS
+                .iter()
+                .filter(|(name, _)| domain.get(name).is_some())
+                .map(|(name, ty)| serde_json::json!({ "name": name, "type": ty }))
+                .collect();
+            types.insert("EIP712Domain".to_string(), Value::Array(fields));
+        }
+
+        Self {
+            types,
+            primary_type: value["primaryType"].as_str().unwrap().to_string(),
+            domain,
+            message: value["message"].clone(),
+        }
+    }
+
+    fn fields(&self, ty: &str) -> Vec<(&str, &str)> {
+        self.types[ty]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| {
+                (
+                    field["name"].as_str().unwrap(),
+                    field["type"].as_str().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn collect_dependencies(&self, ty: &str, found: &mut BTreeSet<String>) {
+        let base = ty.split('[').next().unwrap();
+        if !self.types.contains_key(base) || found.contains(base) {
+            return;
+        }
+        found.insert(base.to_string());
+        for (_, field_ty) in self.fields(base) {
+            self.collect_dependencies(field_ty, found);
+        }
+    }
+
+    /// `encodeType`, the primary type followed by its referenced struct types sorted by name
+    pub fn encode_type(&self, ty: &str) -> String {
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(ty, &mut dependencies);
+        dependencies.remove(ty);
+
+        std::iter::once(ty)
+            .chain(dependencies.iter().map(String::as_str))
+            .map(|name| {
+                let fields = self
+                    .fields(name)
+                    .iter()
+                    .map(|(field, field_ty)| format!("{field_ty} {field}"))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!("{name}({fields})")
+            })
+            .collect()
+    }
+
+    pub fn type_hash(&self, ty: &str) -> [u8; 32] {
+        keccak256(self.encode_type(ty).as_bytes())
+    }
+
+    /// `hashStruct`, `keccak256(typeHash || encodeData(value))`
+    pub fn hash_struct(&self, ty: &str, value: &Value) -> [u8; 32] {
+        let mut data = self.type_hash(ty).to_vec();
+        for (name, field_ty) in self.fields(ty) {
+            data.extend(self.encode_value(field_ty, &value[name]));
+        }
+        keccak256(&data)
+    }
+
+    fn encode_value(&self, ty: &str, value: &Value) -> [u8; 32] {
+        if let Some(element_ty) = ty.strip_suffix(']').and_then(|t| t.rsplit_once('[')) {
+            let data = value
+                .as_array()
+                .unwrap()
+                .iter()
+                .flat_map(|element| self.encode_value(element_ty.0, element))
+                .collect::<Vec<_>>();
+            return keccak256(&data);
+        }
+        if self.types.contains_key(ty) {
+            return self.hash_struct(ty, value);
+        }
+
+        let mut word = [0u8; 32];
+        match ty {
+            "string" => return keccak256(value.as_str().unwrap().as_bytes()),
+            "bytes" => return keccak256(&decode_hex(value.as_str().unwrap())),
+            "bool" => {
+                word[31] = match value {
+                    Value::Bool(b) => *b as u8,
+                    Value::String(s) => (s == "true") as u8,
+                    _ => panic!("invalid bool {value}"),
+                }
+            }
+            "address" => {
+                let address = decode_hex(value.as_str().unwrap());
+                assert_eq!(address.len(), 20, "invalid address length");
+                word[12..].copy_from_slice(&address);
+            }
+            _ if ty.starts_with("uint") => word = uint_word(value),
+            _ if ty.starts_with("int") => word = int_word(value),
+            _ if ty.starts_with("bytes") => {
+                let bytes = decode_hex(value.as_str().unwrap());
+                assert!(bytes.len() <= 32, "invalid {ty} length");
+                word[..bytes.len()].copy_from_slice(&bytes);
+            }
+            _ => panic!("unknown type {ty}"),
+        }
+        word
+    }
+
+    pub fn domain_separator(&self) -> [u8; 32] {
+        self.hash_struct("EIP712Domain", &self.domain)
+    }
+
+    /// `keccak256("\x19\x01" || domainSeparator || hashStruct(message))`
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut data = vec![0x19, 0x01];
+        data.extend(self.domain_separator());
+        if self.primary_type != "EIP712Domain" {
+            data.extend(self.hash_struct(&self.primary_type, &self.message));
+        }
+        keccak256(&data)
+    }
+}
+
+/// EIP-712 digest of an `eth_signTypedData_v4` JSON payload
+pub fn hash_typed_data(typed_data_json: &str) -> [u8; 32] {
+    TypedData::from_json(typed_data_json).signing_hash()
+}
+
+/// ### Sign typed structured data like `eth_signTypedData_v4`
+///
+/// priv_hex:
+/// private key in hex, e.g. `HDWallet::evm_priv_hex`
+///
+/// typed_data_json:
+/// JSON object with `types`, `primaryType`, `domain` and `message`
+///
+/// returns the 65 bytes signature `r || s || v` in hex where `v` is 27 or 28
+pub fn sign_typed_data(priv_hex: &str, typed_data_json: &str) -> String {
+    let (recid, r, s) = sign_digest(priv_hex, hash_typed_data(typed_data_json));
+    format!("0x{}{}{:02x}", r.as_hex(), s.as_hex(), 27 + recid)
+}
+
+/// Recover the checksummed address which produced an `eth_signTypedData_v4` signature,
+/// `None` if the signature is invalid
+pub fn recover_typed_data_signer(typed_data_json: &str, signature_hex: &str) -> Option<String> {
+    let (y_parity, r, s) = split_signature(signature_hex)?;
+    recover_address(hash_typed_data(typed_data_json), y_parity, &r, &s)
+}
@@ -0,0 +1,244 @@
+use super::abi::{encode, ParamType};
+use super::message::personal_sign;
+use super::{decode_hex, keccak256, uint_word};
+use bitcoin::hex::DisplayHex;
+use serde_json::{json, Value};
+
+/// ERC-4337 user operation for EntryPoint v0.6
+pub struct UserOperationV06 {
+    pub sender: String,
+    /// decimal or `0x` prefixed hex, including the 192 bits nonce key
+    pub nonce: String,
+    pub init_code: String,
+    pub call_data: String,
+    pub call_gas_limit: String,
+    pub verification_gas_limit: String,
+    pub pre_verification_gas: String,
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    pub paymaster_and_data: String,
+}
+
+/// ERC-4337 user operation for EntryPoint v0.7, with the unpacked fields used by bundler RPCs
+pub struct UserOperationV07 {
+    pub sender: String,
+    pub nonce: String,
+    /// `None` when the account is already deployed
+    pub factory: Option<String>,
+    pub factory_data: String,
+    pub call_data: String,
+    pub call_gas_limit: String,
+    pub verification_gas_limit: String,
+    pub pre_verification_gas: String,
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    /// `None` when the operation is not sponsored
+    pub paymaster: Option<String>,
+    pub paymaster_verification_gas_limit: String,
+    pub paymaster_post_op_gas_limit: String,
+    pub paymaster_data: String,
+}
+
+/// `PackedUserOperation` as passed to `EntryPoint.handleOps` v0.7, without the signature
+pub struct PackedUserOperation {
+    pub sender: String,
+    pub nonce: String,
+    pub init_code: String,
+    pub call_data: String,
+    /// `verificationGasLimit || callGasLimit` as two uint128
+    pub account_gas_limits: String,
+    pub pre_verification_gas: String,
+    /// `maxPriorityFeePerGas || maxFeePerGas` as two uint128
+    pub gas_fees: String,
+    pub paymaster_and_data: String,
+}
+
+fn hash_hex(data_hex: &str) -> Value {
+    json!(format!("0x{}", keccak256(&decode_hex(data_hex)).as_hex()))
+}
+
+/// Two integers packed as uint128 into 32 bytes
+fn pack_uint128(high: &str, low: &str) -> [u8; 32] {
+    let high = uint_word(&json!(high));
+    let low = uint_word(&json!(low));
+    assert!(
+        high[..16].iter().chain(&low[..16]).all(|b| *b == 0),
+        "uint128 overflow"
+    );
+    let mut word = [0u8; 32];
+    word[..16].copy_from_slice(&high[16..]);
+    word[16..].copy_from_slice(&low[16..]);
+    word
+}
+
+/// `keccak256(abi.encode(keccak256(pack(userOp)), entryPoint, chainId))`
+fn user_operation_hash(packed: Vec<u8>, entry_point: &str, chain_id: u64) -> [u8; 32] {
+    keccak256(
+        &encode(
+            &[
+                ParamType::FixedBytes(32),
+                ParamType::Address,
+                ParamType::Uint(256),
+            ],
+            &[
+                json!(format!("0x{}", keccak256(&packed).as_hex())),
+                json!(entry_point),
+                json!(chain_id),
+            ],
+        )
+        .expect("invalid entry point"),
+    )
+}
+
+impl UserOperationV06 {
+    /// `getUserOpHash` of EntryPoint v0.6
+    pub fn hash(&self, entry_point: &str, chain_id: u64) -> [u8; 32] {
+        let packed = encode(
+            &[
+                ParamType::Address,
+                ParamType::Uint(256),
+                ParamType::FixedBytes(32),
+                ParamType::FixedBytes(32),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::FixedBytes(32),
+            ],
+            &[
+                json!(self.sender),
+                json!(self.nonce),
+                hash_hex(&self.init_code),
+                hash_hex(&self.call_data),
+                json!(self.call_gas_limit),
+                json!(self.verification_gas_limit),
+                json!(self.pre_verification_gas),
+                json!(self.max_fee_per_gas),
+                json!(self.max_priority_fee_per_gas),
+                hash_hex(&self.paymaster_and_data),
+            ],
+        )
+        .expect("invalid user operation");
+        user_operation_hash(packed, entry_point, chain_id)
+    }
+}
+
+impl UserOperationV07 {
+    pub fn pack(&self) -> PackedUserOperation {
+        let init_code = match &self.factory {
+            Some(factory) => {
+                let mut init_code = decode_hex(factory);
+                init_code.extend(decode_hex(&self.factory_data));
+                init_code
+            }
+            None => Vec::new(),
+        };
+        let paymaster_and_data = match &self.paymaster {
+            Some(paymaster) => {
+                let mut data = decode_hex(paymaster);
+                data.extend(pack_uint128(
+                    &self.paymaster_verification_gas_limit,
+                    &self.paymaster_post_op_gas_limit,
+                ));
+                data.extend(decode_hex(&self.paymaster_data));
+                data
+            }
+            None => Vec::new(),
+        };
+
+        PackedUserOperation {
+            sender: self.sender.clone(),
+            nonce: self.nonce.clone(),
+            init_code: format!("0x{}", init_code.as_hex()),
+            call_data: self.call_data.clone(),
+            account_gas_limits: format!(
+                "0x{}",
+                pack_uint128(&self.verification_gas_limit, &self.call_gas_limit).as_hex()
+            ),
+            pre_verification_gas: self.pre_verification_gas.clone(),
+            gas_fees: format!(
+                "0x{}",
+                pack_uint128(&self.max_priority_fee_per_gas, &self.max_fee_per_gas).as_hex()
+            ),
+            paymaster_and_data: format!("0x{}", paymaster_and_data.as_hex()),
+        }
+    }
+
+    /// `getUserOpHash` of EntryPoint v0.7
+    pub fn hash(&self, entry_point: &str, chain_id: u64) -> [u8; 32] {
+        let op = self.pack();
+        let packed = encode(
+            &[
+                ParamType::Address,
+                ParamType::Uint(256),
+                ParamType::FixedBytes(32),
+                ParamType::FixedBytes(32),
+                ParamType::FixedBytes(32),
+                ParamType::Uint(256),
+                ParamType::FixedBytes(32),
+                ParamType::FixedBytes(32),
+            ],
+            &[
+                json!(op.sender),
+                json!(op.nonce),
+                hash_hex(&op.init_code),
+                hash_hex(&op.call_data),
+                json!(op.account_gas_limits),
+                json!(op.pre_verification_gas),
+                json!(op.gas_fees),
+                hash_hex(&op.paymaster_and_data),
+            ],
+        )
+        .expect("invalid user operation");
+        user_operation_hash(packed, entry_point, chain_id)
+    }
+}
+
+/// Pack a user operation into the `PackedUserOperation` fields of EntryPoint v0.7
+pub fn pack_user_operation_v07(op: &UserOperationV07) -> PackedUserOperation {
+    op.pack()
+}
+
+/// User operation hash in hex for EntryPoint v0.6
+pub fn user_operation_v06_hash(op: &UserOperationV06, entry_point: &str, chain_id: u64) -> String {
+    format!("0x{}", op.hash(entry_point, chain_id).as_hex())
+}
+
+/// User operation hash in hex for EntryPoint v0.7
+pub fn user_operation_v07_hash(op: &UserOperationV07, entry_point: &str, chain_id: u64) -> String {
+    format!("0x{}", op.hash(entry_point, chain_id).as_hex())
+}
+
+/// Owner signature of a user operation hash, signed as an EIP-191 personal message of the 32
+/// hash bytes like `SimpleAccount` and most ECDSA owned accounts expect
+fn sign_user_operation_hash(priv_hex: &str, hash: [u8; 32]) -> String {
+    personal_sign(priv_hex, &format!("0x{}", hash.as_hex()))
+}
+
+/// ### Sign a user operation for EntryPoint v0.6
+///
+/// priv_hex:
+/// private key of the account owner in hex, e.g. `HDWallet::evm_priv_hex`
+///
+/// returns the 65 bytes `r || s || v` signature to put in the `signature` field
+pub fn sign_user_operation_v06(
+    priv_hex: &str,
+    op: &UserOperationV06,
+    entry_point: &str,
+    chain_id: u64,
+) -> String {
+    sign_user_operation_hash(priv_hex, op.hash(entry_point, chain_id))
+}
+
+/// ### Sign a user operation for EntryPoint v0.7
+///
+/// see [`sign_user_operation_v06`]
+pub fn sign_user_operation_v07(
+    priv_hex: &str,
+    op: &UserOperationV07,
+    entry_point: &str,
+    chain_id: u64,
+) -> String {
+    sign_user_operation_hash(priv_hex, op.hash(entry_point, chain_id))
+}
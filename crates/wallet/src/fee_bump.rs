@@ -0,0 +1,245 @@
+use crate::signer::{p2tr_sign, p2wpkh_sign, Prevout};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hex::DisplayHex;
+use bitcoin::{
+    absolute, consensus, transaction, Address, AddressType, Amount, OutPoint, ScriptBuf, Sequence,
+    Transaction, TxIn, TxOut, Txid, Witness,
+};
+use std::str::FromStr;
+
+/// BIP125 incremental relay fee rate of Bitcoin Core, in sat/vB
+const INCREMENTAL_RELAY_FEE_RATE: f64 = 1.0;
+
+/// Minimum relay fee rate of Bitcoin Core, in sat/vB
+const MIN_RELAY_FEE_RATE: f64 = 1.0;
+
+/// Address of a single key wallet, whose inputs the signers of [`crate::signer`] can sign
+fn wallet_address(address: &str) -> Address {
+    let address = Address::from_str(address).unwrap().assume_checked();
+    assert!(
+        matches!(
+            address.address_type(),
+            Some(AddressType::P2wpkh) | Some(AddressType::P2tr)
+        ),
+        "unsupported address type"
+    );
+    address
+}
+
+fn sign(address: &Address, priv_hex: &str, tx: &Transaction, prevouts: &[Prevout]) -> String {
+    let tx_hex = consensus::serialize(tx).as_hex().to_string();
+    let prevouts = prevouts.to_vec();
+    match address.address_type() {
+        Some(AddressType::P2wpkh) => p2wpkh_sign(&address.to_string(), priv_hex, &tx_hex, prevouts),
+        Some(AddressType::P2tr) => p2tr_sign(&address.to_string(), priv_hex, &tx_hex, prevouts),
+        _ => unreachable!(),
+    }
+}
+
+/// Virtual size once signed, with signatures of the largest size
+fn signed_vsize(address: &Address, tx: &Transaction) -> u64 {
+    let witness = match address.address_type() {
+        Some(AddressType::P2wpkh) => Witness::from_slice(&[vec![0; 72], vec![0; 33]]),
+        _ => Witness::from_slice(&[vec![0; 64]]),
+    };
+    let mut tx = tx.clone();
+    for input in tx.input.iter_mut() {
+        input.witness = witness.clone();
+    }
+    tx.vsize() as u64
+}
+
+fn fee_for(fee_rate: f64, vsize: u64) -> Amount {
+    Amount::from_sat((fee_rate * vsize as f64).ceil() as u64)
+}
+
+fn total(prevouts: &[Prevout]) -> Amount {
+    prevouts
+        .iter()
+        .map(|prevout| Amount::from_btc(prevout.amount).unwrap())
+        .sum()
+}
+
+fn input(prevout: &Prevout) -> TxIn {
+    TxIn {
+        previous_output: OutPoint {
+            txid: Txid::from_str(&prevout.txid).unwrap(),
+            vout: prevout.vout,
+        },
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+        witness: Witness::new(),
+    }
+}
+
+/// ### Replace a transaction with a higher fee rate, following BIP125
+///
+/// address:
+/// p2wpkh or p2tr address of `priv_hex` locking every input, its outputs are the change
+///
+/// tx_hex:
+/// the original transaction, signed or not
+///
+/// tx_prevouts:
+/// responding prevouts of the original inputs
+///
+/// fee_rate:
+/// target fee rate in sat/vB. The replacement also pays the original fee plus its own relay
+/// fee at the incremental relay fee rate, as BIP125 requires.
+///
+/// extra_prevouts:
+/// other outputs of `address` added as inputs, in order, when the change can't pay the fee
+///
+/// returns the signed replacement in hex. Recipients keep their amounts, the change is reduced,
+/// dropped when it would be dust, or increased with extra inputs.
+pub fn rbf_bump_fee(
+    address: &str,
+    priv_hex: &str,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+    fee_rate: f64,
+    extra_prevouts: Vec<Prevout>,
+) -> String {
+    let address = wallet_address(address);
+    let original =
+        consensus::deserialize::<Transaction>(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap();
+    assert_eq!(original.input.len(), tx_prevouts.len());
+    let original_fee = total(&tx_prevouts)
+        .checked_sub(original.output.iter().map(|output| output.value).sum())
+        .expect("outputs above inputs");
+    let original_vsize = signed_vsize(&address, &original);
+    assert!(
+        fee_rate > original_fee.to_sat() as f64 / original_vsize as f64,
+        "fee rate not above the original one"
+    );
+
+    let script_pubkey = address.script_pubkey();
+    let recipients: Vec<TxOut> = original
+        .output
+        .iter()
+        .filter(|output| output.script_pubkey != script_pubkey)
+        .cloned()
+        .collect();
+    let mut prevouts = tx_prevouts;
+    let mut extra_prevouts = extra_prevouts.into_iter();
+    loop {
+        let mut tx = Transaction {
+            version: original.version,
+            lock_time: original.lock_time,
+            input: prevouts.iter().map(input).collect(),
+            output: recipients.clone(),
+        };
+        // keep the sequences of the original inputs, signaling replaceability
+        for (input, original) in tx.input.iter_mut().zip(&original.input) {
+            if original.sequence.is_rbf() {
+                input.sequence = original.sequence;
+            }
+        }
+        let available = total(&prevouts)
+            .checked_sub(recipients.iter().map(|output| output.value).sum())
+            .expect("outputs above inputs");
+
+        let with_change = {
+            let mut tx = tx.clone();
+            tx.output.push(TxOut {
+                value: Amount::ZERO,
+                script_pubkey: script_pubkey.clone(),
+            });
+            tx
+        };
+        for mut candidate in [with_change, tx] {
+            let vsize = signed_vsize(&address, &candidate);
+            let fee = fee_for(fee_rate, vsize)
+                .max(original_fee + fee_for(INCREMENTAL_RELAY_FEE_RATE, vsize));
+            let Some(change) = available.checked_sub(fee) else {
+                continue;
+            };
+            match candidate.output.last_mut() {
+                Some(output) if output.script_pubkey == script_pubkey => {
+                    if change < script_pubkey.dust_value() {
+                        continue;
+                    }
+                    output.value = change;
+                }
+                _ => {}
+            }
+            return sign(&address, priv_hex, &candidate, &prevouts);
+        }
+
+        let prevout = extra_prevouts
+            .next()
+            .expect("insufficient funds to bump the fee");
+        prevouts.push(prevout);
+    }
+}
+
+/// ### Spend the change of a stuck transaction so that the package pays a fee rate
+///
+/// address:
+/// p2wpkh or p2tr address of `priv_hex`, the change of the parent that the child spends and
+/// pays to
+///
+/// parent_tx_hex:
+/// the signed parent transaction
+///
+/// parent_prevouts:
+/// responding prevouts of the parent inputs, for its fee
+///
+/// fee_rate:
+/// target fee rate in sat/vB of parent and child together
+///
+/// returns the signed child in hex
+pub fn cpfp_child(
+    address: &str,
+    priv_hex: &str,
+    parent_tx_hex: &str,
+    parent_prevouts: Vec<Prevout>,
+    fee_rate: f64,
+) -> String {
+    let address = wallet_address(address);
+    let parent =
+        consensus::deserialize::<Transaction>(&Vec::<u8>::from_hex(parent_tx_hex).unwrap())
+            .unwrap();
+    assert_eq!(parent.input.len(), parent_prevouts.len());
+    let parent_fee = total(&parent_prevouts)
+        .checked_sub(parent.output.iter().map(|output| output.value).sum())
+        .expect("outputs above inputs");
+    let parent_vsize = parent.vsize() as u64;
+
+    let script_pubkey = address.script_pubkey();
+    let txid = parent.txid().to_string();
+    let prevouts: Vec<Prevout> = parent
+        .output
+        .iter()
+        .enumerate()
+        .filter(|(_, output)| output.script_pubkey == script_pubkey)
+        .map(|(vout, output)| Prevout {
+            txid: txid.clone(),
+            vout: vout as u32,
+            amount: output.value.to_btc(),
+        })
+        .collect();
+    assert!(!prevouts.is_empty(), "no change output to spend");
+
+    let mut child = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: prevouts.iter().map(input).collect(),
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+    let child_vsize = signed_vsize(&address, &child);
+    let package_fee = fee_for(fee_rate, parent_vsize + child_vsize);
+    let child_fee = package_fee
+        .checked_sub(parent_fee)
+        .unwrap_or_default()
+        .max(fee_for(MIN_RELAY_FEE_RATE, child_vsize));
+    let value = total(&prevouts)
+        .checked_sub(child_fee)
+        .filter(|value| *value >= script_pubkey.dust_value())
+        .expect("change too small to pay the fee");
+    child.output[0].value = value;
+    sign(&address, priv_hex, &child, &prevouts)
+}
@@ -0,0 +1,869 @@
+use crate::musig2::{
+    cbytes, has_even_y, parity, parse_point, parse_scalar, reduce, tagged_hash, xbytes,
+};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hex::DisplayHex;
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
+use bitcoin::secp256k1::{Secp256k1, XOnlyPublicKey};
+use bitcoin::{Address, Network};
+use k256::{ProjectivePoint, Scalar};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+/// Packages of the first round of a DKG
+pub struct FrostDkgRound1 {
+    /// kept by the participant for [`frost_dkg_part2`]
+    pub secret_package: String,
+    /// broadcast to every other participant
+    pub package: String,
+}
+
+/// Packages of the second round of a DKG
+pub struct FrostDkgRound2 {
+    /// kept by the participant for [`frost_dkg_part3`], none if a participant misbehaved
+    pub secret_package: Option<String>,
+    /// sent privately to their `recipient`, one for every other participant
+    pub packages: Vec<String>,
+    /// identifiers of the participants whose round 1 package is missing or invalid, the DKG
+    /// is to be aborted and restarted without them
+    pub culprits: Vec<u16>,
+    /// reason of each failure, as "participant N: reason"
+    pub errors: Vec<String>,
+}
+
+/// Output of a FROST step checking the packages of the other participants
+pub struct FrostOutput {
+    /// none if a participant misbehaved
+    pub value: Option<String>,
+    /// identifiers of the misbehaving participants
+    pub culprits: Vec<u16>,
+    /// reason of each failure, as "participant N: reason", or "package N: reason" by position
+    /// for packages without a valid identifier
+    pub errors: Vec<String>,
+}
+
+/// Misbehaving participants found while checking their packages
+#[derive(Default)]
+struct Blame {
+    culprits: BTreeSet<u16>,
+    errors: Vec<String>,
+}
+
+impl Blame {
+    fn participant(&mut self, id: u16, reason: &str) {
+        if self.culprits.insert(id) {
+            self.errors.push(format!("participant {id}: {reason}"));
+        }
+    }
+
+    fn package(&mut self, index: usize, reason: &str) {
+        self.errors.push(format!("package {index}: {reason}"));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    fn output(self, value: impl FnOnce() -> String) -> FrostOutput {
+        FrostOutput {
+            value: self.is_empty().then(value),
+            culprits: self.culprits.into_iter().collect(),
+            errors: self.errors,
+        }
+    }
+}
+
+fn random_scalar() -> Scalar {
+    loop {
+        let mut bytes = [0u8; 32];
+        thread_rng().fill_bytes(&mut bytes);
+        match parse_scalar(&bytes) {
+            Some(k) if !bool::from(k.is_zero()) => return k,
+            _ => continue,
+        }
+    }
+}
+
+fn scalar_hex(k: &Scalar) -> String {
+    k.to_bytes().as_hex().to_string()
+}
+
+fn point_hex(point: &ProjectivePoint) -> String {
+    cbytes(point).as_hex().to_string()
+}
+
+fn scalar_field(value: &Value, key: &str) -> Option<Scalar> {
+    let bytes: [u8; 32] = Vec::<u8>::from_hex(value[key].as_str()?)
+        .ok()?
+        .try_into()
+        .ok()?;
+    parse_scalar(&bytes)
+}
+
+fn point_of(hex: &Value) -> Option<ProjectivePoint> {
+    parse_point(&Vec::<u8>::from_hex(hex.as_str()?).ok()?)
+}
+
+fn identifier(value: &Value, key: &str) -> Option<u16> {
+    let id = u16::try_from(value[key].as_u64()?).ok()?;
+    (id >= 1).then_some(id)
+}
+
+fn parse(package: &str) -> Option<Value> {
+    serde_json::from_str(package).ok()
+}
+
+/// Own secret package or key share, which panics if malformed
+fn parse_own(package: &str) -> (Value, u16, u16) {
+    let value = parse(package).expect("invalid package");
+    let id = identifier(&value, "identifier").expect("invalid identifier");
+    let threshold = value["threshold"].as_u64().expect("missing threshold") as u16;
+    (value, id, threshold)
+}
+
+/// Value at `x` of the polynomial of `coefficients`, constant term first
+fn evaluate(coefficients: &[Scalar], x: u16) -> Scalar {
+    let x = Scalar::from(x as u64);
+    coefficients
+        .iter()
+        .rev()
+        .fold(Scalar::ZERO, |acc, a| acc * x + a)
+}
+
+/// Same as [`evaluate`] in the exponent, with commitments `a * G` of the coefficients
+fn evaluate_commitments(commitments: &[ProjectivePoint], x: u16) -> ProjectivePoint {
+    let x = Scalar::from(x as u64);
+    commitments
+        .iter()
+        .rev()
+        .fold(ProjectivePoint::IDENTITY, |acc, c| acc * x + c)
+}
+
+/// Lagrange coefficient at zero of `id` among the `signers`
+fn lagrange(id: u16, signers: &[u16]) -> Scalar {
+    let x = Scalar::from(id as u64);
+    let (num, den) =
+        signers
+            .iter()
+            .filter(|j| **j != id)
+            .fold((Scalar::ONE, Scalar::ONE), |(num, den), j| {
+                let j = Scalar::from(*j as u64);
+                (num * j, den * (j - x))
+            });
+    num * Option::<Scalar>::from(den.invert()).expect("duplicate signer")
+}
+
+/// Key share of a participant, the group key normalized to an even Y as BIP340 keys are
+fn key_share(
+    identifier: u16,
+    threshold: u16,
+    signing_share: Scalar,
+    group_key: ProjectivePoint,
+    verifying_shares: BTreeMap<u16, ProjectivePoint>,
+) -> String {
+    let g = parity(&group_key);
+    let verifying_shares: Map<String, Value> = verifying_shares
+        .iter()
+        .map(|(id, share)| (id.to_string(), point_hex(&(*share * g)).into()))
+        .collect();
+    json!({
+        "identifier": identifier,
+        "threshold": threshold,
+        "signing_share": scalar_hex(&(signing_share * g)),
+        "group_key": point_hex(&(group_key * g)),
+        "verifying_shares": verifying_shares,
+    })
+    .to_string()
+}
+
+fn check_parameters(threshold: u16, max_signers: u16) {
+    assert!(
+        threshold >= 2 && threshold <= max_signers,
+        "invalid threshold {threshold} of {max_signers}"
+    );
+}
+
+/// ### Split a key into FROST key shares with a trusted dealer
+///
+/// secret_hex:
+/// 32 bytes private key to split, a random one if `None`
+///
+/// returns the key share of participants 1 to `max_signers`, each to be given to its
+/// participant only and loaded with [`FrostSigner::new`]
+pub fn frost_trusted_dealer(
+    threshold: u16,
+    max_signers: u16,
+    secret_hex: Option<String>,
+) -> Vec<String> {
+    check_parameters(threshold, max_signers);
+    let secret = match secret_hex {
+        Some(secret) => {
+            parse_scalar(&Vec::<u8>::from_hex(&secret).unwrap()).expect("invalid private key")
+        }
+        None => random_scalar(),
+    };
+    assert!(!bool::from(secret.is_zero()), "invalid private key");
+
+    let mut coefficients = vec![secret];
+    coefficients.extend((1..threshold).map(|_| random_scalar()));
+    let shares: BTreeMap<u16, Scalar> = (1..=max_signers)
+        .map(|id| (id, evaluate(&coefficients, id)))
+        .collect();
+    let verifying_shares: BTreeMap<u16, ProjectivePoint> = shares
+        .iter()
+        .map(|(id, share)| (*id, ProjectivePoint::GENERATOR * share))
+        .collect();
+    let group_key = ProjectivePoint::GENERATOR * secret;
+
+    shares
+        .iter()
+        .map(|(id, share)| key_share(*id, threshold, *share, group_key, verifying_shares.clone()))
+        .collect()
+}
+
+/// Challenge of the proof of knowledge of the constant term of a DKG polynomial
+fn dkg_challenge(identifier: u16, commitment: &ProjectivePoint, r: &ProjectivePoint) -> Scalar {
+    reduce(tagged_hash(
+        "FROST/dkg-pok",
+        &[&identifier.to_be_bytes(), &cbytes(commitment), &cbytes(r)],
+    ))
+}
+
+/// Commitments of a round 1 package, none if they or the proof of knowledge are invalid
+fn dkg_commitments(id: u16, package: &Value, threshold: u16) -> Option<Vec<ProjectivePoint>> {
+    let commitments = package["commitments"]
+        .as_array()?
+        .iter()
+        .map(point_of)
+        .collect::<Option<Vec<_>>>()?;
+    if commitments.len() != threshold as usize {
+        return None;
+    }
+    let r = point_of(&package["proof"]["r"])?;
+    let mu = scalar_field(&package["proof"], "mu")?;
+    let valid = ProjectivePoint::GENERATOR * mu
+        == r + commitments[0] * dkg_challenge(id, &commitments[0], &r);
+    valid.then_some(commitments)
+}
+
+/// Checked commitments of the round 1 packages of the other participants by identifier.
+/// Packages without a valid identifier or of unknown participants are left out, their senders
+/// are then blamed for missing packages.
+fn dkg_round1_commitments(
+    own: u16,
+    packages: &[String],
+    threshold: u16,
+    max_signers: u16,
+    blame: &mut Blame,
+) -> BTreeMap<u16, Vec<ProjectivePoint>> {
+    let mut by_id = BTreeMap::new();
+    for package in packages.iter().filter_map(|package| parse(package)) {
+        let Some(id) = identifier(&package, "identifier") else {
+            continue;
+        };
+        if id == own || id > max_signers {
+            continue;
+        }
+        if by_id.insert(id, package).is_some() {
+            blame.participant(id, "duplicate round 1 package");
+        }
+    }
+    let mut commitments = BTreeMap::new();
+    for id in (1..=max_signers).filter(|id| *id != own) {
+        match by_id
+            .get(&id)
+            .map(|package| dkg_commitments(id, package, threshold))
+        {
+            None => blame.participant(id, "missing round 1 package"),
+            Some(None) => blame.participant(id, "invalid commitments or proof of knowledge"),
+            Some(Some(c)) => {
+                commitments.insert(id, c);
+            }
+        }
+    }
+    commitments
+}
+
+/// ### Start a distributed key generation, no party ever knowing the group private key
+///
+/// The proof of knowledge uses the custom "FROST/dkg-pok" tagged hash, the packages don't
+/// interoperate with the DKG of RFC 9591 implementations.
+///
+/// identifier:
+/// 1 to `max_signers`, distinct for each participant
+///
+/// returns the round 1 package to broadcast and the secret package to keep
+pub fn frost_dkg_part1(identifier: u16, threshold: u16, max_signers: u16) -> FrostDkgRound1 {
+    check_parameters(threshold, max_signers);
+    assert!(
+        (1..=max_signers).contains(&identifier),
+        "invalid identifier {identifier}"
+    );
+    let coefficients: Vec<Scalar> = (0..threshold).map(|_| random_scalar()).collect();
+    let commitments: Vec<String> = coefficients
+        .iter()
+        .map(|a| point_hex(&(ProjectivePoint::GENERATOR * a)))
+        .collect();
+
+    let k = random_scalar();
+    let r = ProjectivePoint::GENERATOR * k;
+    let c = dkg_challenge(
+        identifier,
+        &(ProjectivePoint::GENERATOR * coefficients[0]),
+        &r,
+    );
+    let mu = k + coefficients[0] * c;
+
+    FrostDkgRound1 {
+        secret_package: json!({
+            "identifier": identifier,
+            "threshold": threshold,
+            "max_signers": max_signers,
+            "coefficients": coefficients.iter().map(scalar_hex).collect::<Vec<_>>(),
+        })
+        .to_string(),
+        package: json!({
+            "identifier": identifier,
+            "commitments": commitments,
+            "proof": { "r": point_hex(&r), "mu": scalar_hex(&mu) },
+        })
+        .to_string(),
+    }
+}
+
+/// ### Second round of a distributed key generation
+///
+/// round1_packages:
+/// round 1 packages of every other participant, the own one is ignored if listed
+///
+/// returns a share for each other participant, to be sent over a private authenticated
+/// channel, and the secret package to keep. If a round 1 package is missing or invalid there
+/// are none, and its participants are blamed instead.
+pub fn frost_dkg_part2(secret_package: &str, round1_packages: Vec<String>) -> FrostDkgRound2 {
+    let (secret, own, threshold) = parse_own(secret_package);
+    let max_signers = secret["max_signers"].as_u64().unwrap() as u16;
+    let coefficients: Vec<Scalar> = secret["coefficients"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| parse_scalar(&Vec::<u8>::from_hex(a.as_str().unwrap()).unwrap()).unwrap())
+        .collect();
+
+    let mut blame = Blame::default();
+    let commitments =
+        dkg_round1_commitments(own, &round1_packages, threshold, max_signers, &mut blame);
+    if !blame.is_empty() {
+        return FrostDkgRound2 {
+            secret_package: None,
+            packages: vec![],
+            culprits: blame.culprits.into_iter().collect(),
+            errors: blame.errors,
+        };
+    }
+
+    FrostDkgRound2 {
+        secret_package: Some(
+            json!({
+                "identifier": own,
+                "threshold": threshold,
+                "max_signers": max_signers,
+                "commitments": coefficients
+                    .iter()
+                    .map(|a| point_hex(&(ProjectivePoint::GENERATOR * a)))
+                    .collect::<Vec<_>>(),
+                "own_share": scalar_hex(&evaluate(&coefficients, own)),
+            })
+            .to_string(),
+        ),
+        packages: commitments
+            .keys()
+            .map(|id| {
+                json!({
+                    "sender": own,
+                    "recipient": id,
+                    "share": scalar_hex(&evaluate(&coefficients, *id)),
+                })
+                .to_string()
+            })
+            .collect(),
+        culprits: vec![],
+        errors: vec![],
+    }
+}
+
+/// ### Finish a distributed key generation
+///
+/// round1_packages:
+/// the same round 1 packages given to [`frost_dkg_part2`]
+///
+/// round2_packages:
+/// round 2 packages sent to this participant by every other participant
+///
+/// returns the key share of the participant, loaded with [`FrostSigner::new`], none if a
+/// package is missing or invalid, blaming its participant
+pub fn frost_dkg_part3(
+    secret_package: &str,
+    round1_packages: Vec<String>,
+    round2_packages: Vec<String>,
+) -> FrostOutput {
+    let (secret, own, threshold) = parse_own(secret_package);
+    let max_signers = secret["max_signers"].as_u64().unwrap() as u16;
+
+    let mut blame = Blame::default();
+    let mut commitments =
+        dkg_round1_commitments(own, &round1_packages, threshold, max_signers, &mut blame);
+
+    let mut signing_share = scalar_field(&secret, "own_share").expect("invalid own share");
+    let mut senders = BTreeSet::new();
+    for package in round2_packages.iter().filter_map(|package| parse(package)) {
+        let Some(sender) = identifier(&package, "sender") else {
+            continue;
+        };
+        if sender == own || sender > max_signers {
+            continue;
+        }
+        if !senders.insert(sender) {
+            blame.participant(sender, "duplicate round 2 package");
+            continue;
+        }
+        if identifier(&package, "recipient") != Some(own) {
+            blame.participant(sender, "share of another participant");
+            continue;
+        }
+        let Some(expected) = commitments.get(&sender) else {
+            continue;
+        };
+        match scalar_field(&package, "share") {
+            Some(share)
+                if ProjectivePoint::GENERATOR * share == evaluate_commitments(expected, own) =>
+            {
+                signing_share += share
+            }
+            _ => blame.participant(sender, "invalid share"),
+        }
+    }
+    for id in (1..=max_signers).filter(|id| *id != own && !senders.contains(id)) {
+        blame.participant(id, "missing round 2 package");
+    }
+
+    blame.output(|| {
+        let own_commitments = secret["commitments"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| point_of(c).unwrap())
+            .collect();
+        commitments.insert(own, own_commitments);
+        let group_commitments: Vec<ProjectivePoint> = (0..threshold as usize)
+            .map(|k| commitments.values().map(|c| c[k]).sum())
+            .collect();
+        let verifying_shares = (1..=max_signers)
+            .map(|id| (id, evaluate_commitments(&group_commitments, id)))
+            .collect();
+
+        key_share(
+            own,
+            threshold,
+            signing_share,
+            group_commitments[0],
+            verifying_shares,
+        )
+    })
+}
+
+/// Public part of a key share, with which the coordinator checks signature shares
+struct PublicKeyPackage {
+    threshold: u16,
+    group_key: ProjectivePoint,
+    verifying_shares: BTreeMap<u16, ProjectivePoint>,
+}
+
+impl PublicKeyPackage {
+    fn parse(package: &Value) -> Self {
+        let group_key = point_of(&package["group_key"]).expect("invalid group key");
+        assert!(has_even_y(&group_key), "group key with odd Y");
+        Self {
+            threshold: package["threshold"].as_u64().expect("missing threshold") as u16,
+            group_key,
+            verifying_shares: package["verifying_shares"]
+                .as_object()
+                .expect("missing verifying shares")
+                .iter()
+                .map(|(id, share)| {
+                    let id = id.parse().expect("invalid identifier");
+                    (id, point_of(share).expect("invalid verifying share"))
+                })
+                .collect(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let verifying_shares: Map<String, Value> = self
+            .verifying_shares
+            .iter()
+            .map(|(id, share)| (id.to_string(), point_hex(share).into()))
+            .collect();
+        json!({
+            "threshold": self.threshold,
+            "group_key": point_hex(&self.group_key),
+            "verifying_shares": verifying_shares,
+        })
+        .to_string()
+    }
+
+    /// BIP86 tweak of the group key, the key path only taproot output key
+    fn taproot_tweak(&self) -> Scalar {
+        parse_scalar(&tagged_hash("TapTweak", &[&xbytes(&self.group_key)])).expect("invalid tweak")
+    }
+}
+
+/// Signing package of a coordinator, with the values derived from it by each signer
+struct SigningSession {
+    commitments: BTreeMap<u16, (ProjectivePoint, ProjectivePoint)>,
+    binding_factors: BTreeMap<u16, Scalar>,
+    r: ProjectivePoint,
+    /// signing key, the group key or its taproot output key
+    q: ProjectivePoint,
+    /// taproot tweak, zero if the group key signs
+    tweak: Scalar,
+    c: Scalar,
+}
+
+impl SigningSession {
+    /// none if the package is malformed, has signers unknown to `keys` or below threshold
+    fn new(package: &Value, keys: &PublicKeyPackage) -> Option<Self> {
+        let message: [u8; 32] = Vec::<u8>::from_hex(package["message"].as_str()?)
+            .ok()?
+            .try_into()
+            .ok()?;
+        let commitments: BTreeMap<u16, (ProjectivePoint, ProjectivePoint)> = package["commitments"]
+            .as_array()?
+            .iter()
+            .map(|c| {
+                let id = identifier(c, "identifier")
+                    .filter(|id| keys.verifying_shares.contains_key(id))?;
+                Some((id, (point_of(&c["hiding"])?, point_of(&c["binding"])?)))
+            })
+            .collect::<Option<_>>()?;
+        if commitments.len() < keys.threshold as usize {
+            return None;
+        }
+
+        let tweak = match package["taproot_tweak"].as_bool().unwrap_or_default() {
+            true => keys.taproot_tweak(),
+            false => Scalar::ZERO,
+        };
+        let q = keys.group_key + ProjectivePoint::GENERATOR * tweak;
+
+        let encoded: Vec<u8> = commitments
+            .iter()
+            .flat_map(|(id, (d, e))| {
+                [
+                    id.to_be_bytes().to_vec(),
+                    cbytes(d).to_vec(),
+                    cbytes(e).to_vec(),
+                ]
+            })
+            .flatten()
+            .collect();
+        let binding_factors: BTreeMap<u16, Scalar> = commitments
+            .keys()
+            .map(|id| {
+                let rho = reduce(tagged_hash(
+                    "FROST/rho",
+                    &[&xbytes(&q), &message, &encoded, &id.to_be_bytes()],
+                ));
+                (*id, rho)
+            })
+            .collect();
+        let r: ProjectivePoint = commitments
+            .iter()
+            .map(|(id, (d, e))| *d + *e * binding_factors[id])
+            .sum();
+        if r == ProjectivePoint::IDENTITY {
+            return None;
+        }
+        let c = reduce(tagged_hash(
+            "BIP0340/challenge",
+            &[&xbytes(&r), &xbytes(&q), &message],
+        ));
+        Some(Self {
+            commitments,
+            binding_factors,
+            r,
+            q,
+            tweak,
+            c,
+        })
+    }
+
+    fn signers(&self) -> Vec<u16> {
+        self.commitments.keys().copied().collect()
+    }
+
+    /// Factor of the signing share of `id`, with the parity of the signing key
+    fn key_coefficient(&self, id: u16) -> Scalar {
+        self.c * parity(&self.q) * lagrange(id, &self.signers())
+    }
+
+    fn verify_share(&self, id: u16, share: &Scalar, verifying_share: &ProjectivePoint) -> bool {
+        let Some((d, e)) = self.commitments.get(&id) else {
+            return false;
+        };
+        let nonce = (*d + *e * self.binding_factors[&id]) * parity(&self.r);
+        ProjectivePoint::GENERATOR * share == nonce + *verifying_share * self.key_coefficient(id)
+    }
+
+    fn aggregate(&self, shares: &[Scalar]) -> [u8; 64] {
+        let z = shares.iter().sum::<Scalar>() + self.c * parity(&self.q) * self.tweak;
+        [xbytes(&self.r).to_vec(), z.to_bytes().to_vec()]
+            .concat()
+            .try_into()
+            .unwrap()
+    }
+}
+
+/// ### Build the signing package of a coordinator
+///
+/// commitments:
+/// [`FrostSigner::commit`] of at least threshold signers
+///
+/// message_hex:
+/// 32 bytes digest to sign, e.g. a taproot sighash
+///
+/// taproot_tweak:
+/// sign for the BIP86 output key of the group key, as in a key path spend of
+/// [`FrostSigner::address`], instead of the group key itself
+///
+/// returns the signing package, none if a commitment is invalid or duplicated, blaming its
+/// signer
+pub fn frost_signing_package(
+    commitments: Vec<String>,
+    message_hex: &str,
+    taproot_tweak: bool,
+) -> FrostOutput {
+    let message = Vec::<u8>::from_hex(message_hex).unwrap();
+    assert_eq!(message.len(), 32, "invalid message length");
+    let mut blame = Blame::default();
+    let mut by_id = BTreeMap::new();
+    for (index, commitment) in commitments.iter().enumerate() {
+        let Some((id, commitment)) =
+            parse(commitment).and_then(|c| Some((identifier(&c, "identifier")?, c)))
+        else {
+            blame.package(index, "invalid commitment");
+            continue;
+        };
+        if point_of(&commitment["hiding"]).is_none() || point_of(&commitment["binding"]).is_none() {
+            blame.participant(id, "invalid commitment");
+        } else if by_id.insert(id, commitment).is_some() {
+            blame.participant(id, "duplicate commitment");
+        }
+    }
+    blame.output(|| {
+        json!({
+            "message": message_hex,
+            "taproot_tweak": taproot_tweak,
+            "commitments": by_id.into_values().collect::<Vec<_>>(),
+        })
+        .to_string()
+    })
+}
+
+/// ### Aggregate the signature shares of a signing package into a BIP340 signature
+///
+/// public_key_package:
+/// [`FrostSigner::public_key_package`] of any participant
+///
+/// returns the 64 bytes signature in hex, after checking every share so that a
+/// misbehaving signer is identified. It is none if a share is missing or invalid, blaming its
+/// signer, or if the signing package has unknown signers or too few.
+pub fn frost_aggregate(
+    signing_package: &str,
+    signature_shares: Vec<String>,
+    public_key_package: &str,
+) -> FrostOutput {
+    let keys = PublicKeyPackage::parse(&parse(public_key_package).expect("invalid package"));
+    let mut blame = Blame::default();
+    let package = parse(signing_package).expect("invalid signing package");
+    for commitment in package["commitments"].as_array().into_iter().flatten() {
+        match identifier(commitment, "identifier") {
+            Some(id) if !keys.verifying_shares.contains_key(&id) => {
+                blame.participant(id, "unknown signer")
+            }
+            _ => {}
+        }
+    }
+    let Some(session) = SigningSession::new(&package, &keys) else {
+        if blame.is_empty() {
+            blame
+                .errors
+                .push("signing package below threshold or invalid".to_string());
+        }
+        return blame.output(String::new);
+    };
+
+    let mut shares = BTreeMap::new();
+    for (index, share) in signature_shares.iter().enumerate() {
+        let Some((id, share)) = parse(share).and_then(|s| Some((identifier(&s, "identifier")?, s)))
+        else {
+            blame.package(index, "invalid signature share");
+            continue;
+        };
+        if !session.commitments.contains_key(&id) {
+            blame.participant(id, "signer not in the signing package");
+            continue;
+        }
+        match scalar_field(&share, "share") {
+            Some(z) if session.verify_share(id, &z, &keys.verifying_shares[&id]) => {
+                if shares.insert(id, z).is_some() {
+                    blame.participant(id, "duplicate signature share");
+                }
+            }
+            _ => blame.participant(id, "invalid signature share"),
+        }
+    }
+    for id in session.signers() {
+        if !shares.contains_key(&id) {
+            blame.participant(id, "missing signature share");
+        }
+    }
+    blame.output(|| {
+        let shares: Vec<Scalar> = shares.into_values().collect();
+        session.aggregate(&shares).as_hex().to_string()
+    })
+}
+
+/// Hiding and binding nonces of round 1, never serialized so that they can't be reused
+struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+/// A participant of FROST threshold signing, holding one key share.
+///
+/// Signing takes two rounds through a coordinator, every message a JSON string:
+/// [`FrostSigner::commit`] by at least threshold participants, [`frost_signing_package`],
+/// [`FrostSigner::sign`] by each of them, then [`frost_aggregate`].
+///
+/// The binding factors use the custom "FROST/rho" tagged hash rather than the hashes of the
+/// FROST(secp256k1, SHA-256) ciphersuite of RFC 9591, so signing packages and signature shares
+/// don't interoperate with RFC 9591 implementations. The signatures are plain BIP340 ones.
+pub struct FrostSigner {
+    identifier: u16,
+    signing_share: Scalar,
+    keys: PublicKeyPackage,
+    /// nonces of round 1 by hiding commitment, removed when signing so that none signs twice
+    nonces: Mutex<BTreeMap<[u8; 33], SigningNonces>>,
+}
+
+impl FrostSigner {
+    /// ### Load a key share
+    ///
+    /// key_share:
+    /// output of [`frost_trusted_dealer`] or [`frost_dkg_part3`]
+    pub fn new(key_share: &str) -> Self {
+        let (share, identifier, _) = parse_own(key_share);
+        let signing_share = scalar_field(&share, "signing_share").expect("invalid signing share");
+        let keys = PublicKeyPackage::parse(&share);
+        assert!(
+            keys.verifying_shares.get(&identifier)
+                == Some(&(ProjectivePoint::GENERATOR * signing_share)),
+            "signing share doesn't match its verifying share"
+        );
+        Self {
+            identifier,
+            signing_share,
+            keys,
+            nonces: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// Public key package shared by every participant, for [`frost_aggregate`]
+    pub fn public_key_package(&self) -> String {
+        self.keys.to_json()
+    }
+
+    /// 32 bytes x-only group public key in hex
+    pub fn group_public_key(&self) -> String {
+        xbytes(&self.keys.group_key).as_hex().to_string()
+    }
+
+    /// BIP86 key path only taproot address of the group key
+    pub fn address(&self, is_testnet: u8) -> String {
+        let network = match is_testnet {
+            0 => Network::Bitcoin,
+            1 => Network::Testnet,
+            _ => unreachable!(),
+        };
+        let internal_key = XOnlyPublicKey::from_slice(&xbytes(&self.keys.group_key)).unwrap();
+        Address::p2tr(&Secp256k1::new(), internal_key, None, network).to_string()
+    }
+
+    /// ### Round 1: commit to fresh nonces
+    ///
+    /// returns the commitments to send to the coordinator, the nonces stay in this signer
+    pub fn commit(&self) -> String {
+        let nonce = || {
+            let mut rand = [0u8; 32];
+            thread_rng().fill_bytes(&mut rand);
+            let k = reduce(tagged_hash(
+                "FROST/nonce",
+                &[&rand, &self.signing_share.to_bytes()],
+            ));
+            assert!(!bool::from(k.is_zero()), "zero nonce");
+            k
+        };
+        let nonces = SigningNonces {
+            hiding: nonce(),
+            binding: nonce(),
+        };
+        let hiding = cbytes(&(ProjectivePoint::GENERATOR * nonces.hiding));
+        let binding = cbytes(&(ProjectivePoint::GENERATOR * nonces.binding));
+        self.nonces.lock().unwrap().insert(hiding, nonces);
+        json!({
+            "identifier": self.identifier,
+            "hiding": hiding.as_hex().to_string(),
+            "binding": binding.as_hex().to_string(),
+        })
+        .to_string()
+    }
+
+    /// ### Round 2: sign a signing package
+    ///
+    /// signing_package:
+    /// output of [`frost_signing_package`] with a commitment of this signer
+    ///
+    /// returns the signature share to send to the coordinator, none if the package is invalid,
+    /// below threshold, or has no unused commitment of this signer. The nonces of the
+    /// commitment are consumed, signing the same commitment again returns none.
+    pub fn sign(&self, signing_package: &str) -> Option<String> {
+        let session = SigningSession::new(&parse(signing_package)?, &self.keys)?;
+        let (hiding, binding) = session.commitments.get(&self.identifier)?;
+
+        let nonces = self.nonces.lock().unwrap().remove(&cbytes(hiding))?;
+        if ProjectivePoint::GENERATOR * nonces.binding != *binding {
+            return None;
+        }
+
+        let k = nonces.hiding + nonces.binding * session.binding_factors[&self.identifier];
+        let z =
+            k * parity(&session.r) + self.signing_share * session.key_coefficient(self.identifier);
+        debug_assert!(session.verify_share(
+            self.identifier,
+            &z,
+            &self.keys.verifying_shares[&self.identifier]
+        ));
+        Some(
+            json!({
+                "identifier": self.identifier,
+                "share": scalar_hex(&z),
+            })
+            .to_string(),
+        )
+    }
+}
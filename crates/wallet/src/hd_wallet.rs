@@ -1,48 +1,42 @@
+use crate::bip85::{bip85_base64_password, bip85_hex, bip85_mnemonic, bip85_wif, bip85_xprv};
+use crate::evm::address::address_from_pubkey;
+use crate::evm::{
+    Authorization, KeystoreKdf, SignedAuthorization, UserOperationV06, UserOperationV07,
+};
+use crate::multisig_wallet::MultisigScriptType;
+use crate::signer::Prevout;
+use crate::slip39::{slip39_generate, slip39_recover, Slip39Group};
 use bip39::Mnemonic;
 use bitcoin::bip32::{DerivationPath, Xpriv, Xpub};
+use bitcoin::hashes::hex::FromHex;
 use bitcoin::hex::DisplayHex;
 use bitcoin::key::TapTweak;
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
 use bitcoin::secp256k1::{All, Secp256k1};
 use bitcoin::{Address, PublicKey, TapNodeHash};
+use serde_json::{json, Value};
 use std::str::FromStr;
-use tiny_keccak::{Hasher, Keccak};
 
 pub struct HDWallet {
     secp: Secp256k1<All>,
     mnemonic: Option<Mnemonic>,
+    has_passphrase: bool,
+    /// BIP32 seed the root was derived from, unknown for wallets created from a master key
+    seed: Option<Vec<u8>>,
     root: Xpriv,
 }
 
-fn checksum_address(address: &str) -> String {
-    let output = keccak256(address.as_bytes());
-
-    let digest = output.as_hex().to_string();
-    let mut new_address = String::new();
-    for (c, digest_char) in address.chars().zip(digest.chars()) {
-        new_address.push(
-            if u8::from_str_radix(digest_char.to_string().as_str(), 16).unwrap() >= 8 {
-                c.to_ascii_uppercase()
-            } else {
-                c
-            },
-        );
-    }
-
-    new_address
-}
-
-fn keccak256(data: &[u8]) -> [u8; 32] {
-    let mut output = [0u8; 32];
-
-    let mut hasher = Keccak::v256();
-    hasher.update(data);
-    hasher.finalize(&mut output);
-
-    output
-}
-
 impl HDWallet {
     pub fn new(is_testnet: u8, mnemonic_str: Option<String>) -> Self {
+        Self::new_with_passphrase(is_testnet, mnemonic_str, "")
+    }
+
+    /// Like [`HDWallet::new`], with a BIP39 passphrase mixed into the seed
+    pub fn new_with_passphrase(
+        is_testnet: u8,
+        mnemonic_str: Option<String>,
+        passphrase: &str,
+    ) -> Self {
         let mnemonic = match mnemonic_str {
             Some(m) => Mnemonic::from_str(m.as_str()).unwrap(),
             None => Mnemonic::generate(12).unwrap(),
@@ -54,7 +48,7 @@ impl HDWallet {
             _ => unreachable!(),
         };
 
-        let seed = mnemonic.to_seed("");
+        let seed = mnemonic.to_seed(passphrase);
         let root = Xpriv::new_master(network, &seed).unwrap();
 
         let secp = Secp256k1::new();
@@ -62,6 +56,8 @@ impl HDWallet {
         Self {
             secp,
             mnemonic: Some(mnemonic),
+            has_passphrase: !passphrase.is_empty(),
+            seed: Some(seed.to_vec()),
             root,
         }
     }
@@ -72,10 +68,161 @@ impl HDWallet {
         Self {
             secp,
             mnemonic: None,
+            has_passphrase: false,
+            seed: None,
             root,
         }
     }
 
+    /// ### Restore a wallet from [`HDWallet::encrypt`]
+    ///
+    /// passphrase:
+    /// the BIP39 passphrase, required when the wallet was created with one since it is never
+    /// stored
+    ///
+    /// returns `None` if the password is wrong, the envelope is malformed or a required
+    /// passphrase is missing
+    pub fn from_encrypted(
+        encrypted: &str,
+        password: &str,
+        passphrase: Option<String>,
+    ) -> Option<Self> {
+        let payload = crate::storage::open(encrypted, password)?;
+        let payload: Value = serde_json::from_slice(&payload).unwrap();
+
+        let is_testnet = match payload["network"].as_str().unwrap() {
+            "bitcoin" => 0,
+            "testnet" => 1,
+            network => panic!("unsupported network {network}"),
+        };
+        if let Some(seed) = payload["seed"].as_str() {
+            return Some(Self::from_seed(
+                is_testnet,
+                Vec::<u8>::from_hex(seed).unwrap(),
+            ));
+        }
+        let Some(mnemonic) = payload["mnemonic"].as_str() else {
+            return Some(Self::from_master_priv(
+                payload["master_priv"].as_str().unwrap(),
+            ));
+        };
+        let passphrase = match payload["passphrase"].as_bool().unwrap() {
+            true => passphrase?,
+            false => String::new(),
+        };
+        Some(Self::new_with_passphrase(
+            is_testnet,
+            Some(mnemonic.to_string()),
+            &passphrase,
+        ))
+    }
+
+    /// ### New wallet of a random SLIP-39 master secret
+    ///
+    /// the 16 bytes secret is the BIP32 seed, as done by Trezor, so its
+    /// [`HDWallet::slip39_shares`] are 20 words shares hardware wallets accept. There is no
+    /// mnemonic to export.
+    pub fn new_slip39(is_testnet: u8) -> Self {
+        let mut seed = vec![0u8; 16];
+        thread_rng().fill_bytes(&mut seed);
+        Self::from_seed(is_testnet, seed)
+    }
+
+    /// ### Restore a wallet from SLIP-39 shares
+    ///
+    /// the recovered master secret is the BIP32 seed, as done by Trezor, so shares made by
+    /// [`HDWallet::slip39_shares`] or by other SLIP-39 wallets restore the same keys. There is
+    /// no mnemonic to export.
+    ///
+    /// returns `None` if a share is invalid, the shares don't belong to the same secret or are
+    /// not enough
+    pub fn from_slip39(is_testnet: u8, shares: Vec<String>, passphrase: &str) -> Option<Self> {
+        let seed = Vec::<u8>::from_hex(&slip39_recover(shares, passphrase)?).unwrap();
+        Some(Self::from_seed(is_testnet, seed))
+    }
+
+    fn from_seed(is_testnet: u8, seed: Vec<u8>) -> Self {
+        let network = match is_testnet {
+            0 => bitcoin::Network::Bitcoin,
+            1 => bitcoin::Network::Testnet,
+            _ => unreachable!(),
+        };
+        let root = Xpriv::new_master(network, &seed).unwrap();
+
+        Self {
+            secp: Secp256k1::new(),
+            mnemonic: None,
+            has_passphrase: false,
+            seed: Some(seed),
+            root,
+        }
+    }
+
+    /// ### Split the BIP32 seed into SLIP-39 shares
+    ///
+    /// the seed is the SLIP-39 master secret, so the same keys come back from
+    /// [`HDWallet::from_slip39`]. See [`crate::slip39::slip39_generate`] for the parameters.
+    ///
+    /// returns `None` unless the seed is 16 or 32 bytes, the secrets hardware wallets accept:
+    /// wallets of [`HDWallet::new_slip39`] or restored from shares. The 64 bytes seed of a BIP39
+    /// mnemonic would give 59 words shares, and a master key has no seed.
+    pub fn slip39_shares(
+        &self,
+        passphrase: &str,
+        group_threshold: u8,
+        groups: Vec<Slip39Group>,
+        iteration_exponent: u8,
+    ) -> Option<Vec<Vec<String>>> {
+        let seed = self
+            .seed
+            .as_ref()
+            .filter(|seed| matches!(seed.len(), 16 | 32))?;
+        Some(slip39_generate(
+            &seed.as_hex().to_string(),
+            passphrase,
+            group_threshold,
+            groups,
+            iteration_exponent,
+        ))
+    }
+
+    /// ### Serialize the wallet encrypted for storage at rest
+    ///
+    /// the mnemonic, or the seed or master key when there is none, is encrypted with XChaCha20-Poly1305
+    /// under a key derived from the password by Argon2id, together with the network, whether a
+    /// BIP39 passphrase is needed and the metadata, e.g. a JSON string with the wallet name
+    ///
+    /// returns a versioned JSON envelope, see [`crate::storage::WALLET_FORMAT_VERSION`]
+    pub fn encrypt(&self, password: &str, metadata: &str) -> String {
+        let network = match self.root.network {
+            bitcoin::Network::Bitcoin => "bitcoin",
+            bitcoin::Network::Testnet => "testnet",
+            _ => unreachable!(),
+        };
+        let payload = match (&self.mnemonic, &self.seed) {
+            (Some(mnemonic), _) => json!({
+                "mnemonic": mnemonic.to_string(),
+                "passphrase": self.has_passphrase,
+                "network": network,
+                "metadata": metadata,
+            }),
+            // the seed of SLIP-39 wallets, which their shares are made from
+            (None, Some(seed)) => json!({
+                "seed": seed.as_hex().to_string(),
+                "passphrase": false,
+                "network": network,
+                "metadata": metadata,
+            }),
+            (None, None) => json!({
+                "master_priv": self.root.to_string(),
+                "passphrase": false,
+                "network": network,
+                "metadata": metadata,
+            }),
+        };
+        crate::storage::seal(payload.to_string().as_bytes(), password)
+    }
+
     pub fn export_mnemonic(&self) -> String {
         let Some(m) = &self.mnemonic else {
             return String::new();
@@ -89,14 +236,7 @@ impl HDWallet {
 
     pub fn evm_address(&self) -> String {
         let extended_prikey = self.evm_xpriv();
-        let pubkey_bytes = extended_prikey
-            .to_keypair(&self.secp)
-            .public_key()
-            .serialize_uncompressed();
-
-        let output = keccak256(&pubkey_bytes[1..]);
-
-        format!("0x{}", checksum_address(&output[12..].as_hex().to_string()))
+        address_from_pubkey(&extended_prikey.to_keypair(&self.secp).public_key())
     }
 
     fn evm_xpriv(&self) -> Xpriv {
@@ -125,6 +265,27 @@ impl HDWallet {
         self.root.derive_priv(&self.secp, &path).unwrap()
     }
 
+    pub fn bip84_address(&self) -> String {
+        let extended_prikey = self.bip84_xpriv();
+
+        let xpub = Xpub::from_priv(&self.secp, &extended_prikey);
+        let pubkey = PublicKey::new(xpub.public_key);
+        let address = Address::p2wpkh(&pubkey, self.root.network).unwrap();
+
+        address.to_string()
+    }
+
+    fn bip84_xpriv(&self) -> Xpriv {
+        let coin_type = match self.root.network {
+            bitcoin::Network::Bitcoin => "0'",
+            bitcoin::Network::Testnet => "1'",
+            _ => unreachable!(),
+        };
+
+        let path = DerivationPath::from_str(&format!("m/84'/{coin_type}/0'/0/0")).unwrap();
+        self.root.derive_priv(&self.secp, &path).unwrap()
+    }
+
     pub fn bip86_address(&self) -> String {
         let extended_prikey = self.bip86_xpriv();
         let public_key = Xpub::from_priv(&self.secp, &extended_prikey).public_key;
@@ -158,6 +319,81 @@ impl HDWallet {
             .to_string()
     }
 
+    /// Sign a message with the EVM key like `personal_sign`, see [`crate::evm::personal_sign`]
+    pub fn evm_personal_sign(&self, message: &str) -> String {
+        crate::evm::personal_sign(&self.evm_priv_hex(), message)
+    }
+
+    /// Sign `eth_signTypedData_v4` JSON with the EVM key, see [`crate::evm::sign_typed_data`]
+    pub fn evm_sign_typed_data(&self, typed_data_json: &str) -> String {
+        crate::evm::sign_typed_data(&self.evm_priv_hex(), typed_data_json)
+    }
+
+    /// Sign a user operation as the owner of an EntryPoint v0.6 smart account
+    pub fn evm_sign_user_operation_v06(
+        &self,
+        op: &UserOperationV06,
+        entry_point: &str,
+        chain_id: u64,
+    ) -> String {
+        crate::evm::sign_user_operation_v06(&self.evm_priv_hex(), op, entry_point, chain_id)
+    }
+
+    /// Sign a user operation as the owner of an EntryPoint v0.7 smart account
+    pub fn evm_sign_user_operation_v07(
+        &self,
+        op: &UserOperationV07,
+        entry_point: &str,
+        chain_id: u64,
+    ) -> String {
+        crate::evm::sign_user_operation_v07(&self.evm_priv_hex(), op, entry_point, chain_id)
+    }
+
+    /// Sign an EIP-7702 authorization delegating the EVM account to a contract
+    pub fn evm_sign_authorization(&self, auth: Authorization) -> SignedAuthorization {
+        crate::evm::sign_authorization(&self.evm_priv_hex(), auth)
+    }
+
+    /// Back up the EVM key as a V3 keystore JSON, see [`crate::evm::encrypt_keystore`]
+    pub fn evm_export_keystore(&self, password: &str, kdf: KeystoreKdf) -> String {
+        crate::evm::encrypt_keystore(&self.evm_priv_hex(), password, kdf)
+    }
+
+    /// Sweep the coins of a BIP38 paper wallet to the BIP86 address, see
+    /// [`crate::bip38::bip38_sweep`]
+    pub fn sweep_bip38(
+        &self,
+        encrypted_key: &str,
+        passphrase: &str,
+        prevouts: Vec<Prevout>,
+        fee_sat: u64,
+    ) -> Option<String> {
+        crate::bip38::bip38_sweep(
+            encrypted_key,
+            passphrase,
+            prevouts,
+            &self.bip86_address(),
+            fee_sat,
+        )
+    }
+
+    /// ### BIP48 multisig account key to share with cosigners
+    ///
+    /// returns the key with its origin, e.g. `[fingerprint/48h/0h/0h/2h]xpub...` for P2WSH
+    pub fn bip48_xpub(&self, script_type: MultisigScriptType) -> String {
+        let coin_type = match self.root.network {
+            bitcoin::Network::Bitcoin => "0h",
+            bitcoin::Network::Testnet => "1h",
+            _ => unreachable!(),
+        };
+
+        let origin = format!("48h/{coin_type}/0h/{}h", script_type.bip48_script_type());
+        let path = DerivationPath::from_str(&format!("m/{origin}")).unwrap();
+        let xpriv = self.root.derive_priv(&self.secp, &path).unwrap();
+        let xpub = Xpub::from_priv(&self.secp, &xpriv);
+        format!("[{}/{origin}]{xpub}", self.root.fingerprint(&self.secp))
+    }
+
     pub fn bip44_priv_hex(&self) -> String {
         self.bip44_xpriv()
             .private_key
@@ -166,6 +402,14 @@ impl HDWallet {
             .to_string()
     }
 
+    pub fn bip84_priv_hex(&self) -> String {
+        self.bip84_xpriv()
+            .private_key
+            .secret_bytes()
+            .as_hex()
+            .to_string()
+    }
+
     pub fn bip86_priv_hex(&self) -> String {
         self.bip86_xpriv()
             .private_key
@@ -174,10 +418,58 @@ impl HDWallet {
             .to_string()
     }
 
+    /// Compressed public key of the BIP86 key, the participant key of `Musig2Wallet`
+    pub fn musig2_pubkey(&self) -> String {
+        self.bip86_xpriv()
+            .to_priv()
+            .public_key(&self.secp)
+            .to_string()
+    }
+
     pub fn bip86_tweaked_priv_hex(&self, merkle_root_hex: Option<String>) -> String {
         let merkle_root = merkle_root_hex.map(|v| TapNodeHash::from_str(&v).unwrap());
         let keypair = self.bip86_xpriv().to_keypair(&self.secp);
         let tweaked = keypair.tap_tweak(&self.secp, merkle_root);
         tweaked.to_inner().secret_bytes().as_hex().to_string()
     }
+
+    /// Compressed WIF of the BIP44 key, to import the P2PKH address in other wallets
+    pub fn bip44_wif(&self) -> String {
+        self.bip44_xpriv().to_priv().to_wif()
+    }
+
+    /// Compressed WIF of the BIP84 key, to import the P2WPKH address in other wallets
+    pub fn bip84_wif(&self) -> String {
+        self.bip84_xpriv().to_priv().to_wif()
+    }
+
+    /// Compressed WIF of the untweaked BIP86 key, to import the P2TR address in other wallets
+    pub fn bip86_wif(&self) -> String {
+        self.bip86_xpriv().to_priv().to_wif()
+    }
+
+    /// BIP85 child mnemonic, see [`bip85_mnemonic`]
+    pub fn bip85_mnemonic(&self, language_code: u32, words: u32, index: u32) -> String {
+        bip85_mnemonic(&self.export_master_priv(), language_code, words, index)
+    }
+
+    /// BIP85 child WIF for HD-seed wallets, see [`bip85_wif`]
+    pub fn bip85_wif(&self, index: u32) -> String {
+        bip85_wif(&self.export_master_priv(), index)
+    }
+
+    /// BIP85 child root extended private key, see [`bip85_xprv`]
+    pub fn bip85_xprv(&self, index: u32) -> String {
+        bip85_xprv(&self.export_master_priv(), index)
+    }
+
+    /// BIP85 child entropy in hex, see [`bip85_hex`]
+    pub fn bip85_hex(&self, num_bytes: u32, index: u32) -> String {
+        bip85_hex(&self.export_master_priv(), num_bytes, index)
+    }
+
+    /// BIP85 base64 password, see [`bip85_base64_password`]
+    pub fn bip85_base64_password(&self, pwd_len: u32, index: u32) -> String {
+        bip85_base64_password(&self.export_master_priv(), pwd_len, index)
+    }
 }
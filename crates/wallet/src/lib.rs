@@ -1,5 +1,18 @@
+pub mod bip38;
+pub mod bip85;
+pub mod evm;
+pub mod fee_bump;
+pub mod frost;
 pub mod hd_wallet;
+pub mod miniscript;
+pub mod multisig_wallet;
+pub mod musig2;
 pub mod signer;
+pub mod silent_payments;
+pub mod single_key_wallet;
+pub mod slip39;
+pub mod storage;
+pub mod timelock;
 
 #[cfg(test)]
 mod test;
@@ -0,0 +1,958 @@
+use crate::hd_wallet::HDWallet;
+use crate::multisig_wallet::{
+    descriptor_checksum, sign_psbt_inputs, Cosigner, MultisigPrevout, SignedPsbt, NUMS_INTERNAL_KEY,
+};
+use bitcoin::bip32::{KeySource, Xpriv};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::hex::DisplayHex;
+use bitcoin::opcodes::all::*;
+use bitcoin::psbt::{Input, Psbt};
+use bitcoin::script::{Builder, Instruction};
+use bitcoin::secp256k1::{All, PublicKey, Secp256k1, XOnlyPublicKey};
+use bitcoin::taproot::{LeafVersion, TapLeafHash, TaprootBuilder, TaprootSpendInfo};
+use bitcoin::{consensus, Address, Amount, Network, ScriptBuf, Transaction, TxOut, Witness};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Maximum number of keys of a `multi` under standardness rules, larger thresholds of keys
+/// compile to `thresh`
+const MAX_MULTI_KEYS: usize = 20;
+
+/// Maximum size of a P2WSH witness script under standardness rules
+const MAX_WSH_SCRIPT_SIZE: usize = 3600;
+
+/// Maximum number of non-push opcodes of a segwit v0 script, keys of executed
+/// `OP_CHECKMULTISIG` included, under consensus rules
+const MAX_WSH_OPS: usize = 201;
+
+/// Script context a policy compiles to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiniscriptType {
+    /// segwit v0, `wsh(...)`
+    Wsh,
+    /// tapscript, `tr(NUMS,...)` with the BIP-341 NUMS point as internal key so that only the
+    /// script path spends
+    Tr,
+}
+
+/// Spending policy, in the policy language of Bitcoin Core and rust-miniscript
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Policy {
+    Key(String),
+    After(u32),
+    Older(u32),
+    Sha256([u8; 32]),
+    And(Box<Policy>, Box<Policy>),
+    Or(Box<Policy>, Box<Policy>),
+    Thresh(usize, Vec<Policy>),
+}
+
+/// Kinds of timelocks a policy uses
+#[derive(Debug, Clone, Copy, Default)]
+struct Timelocks {
+    after_height: bool,
+    after_time: bool,
+    older_height: bool,
+    older_time: bool,
+}
+
+impl Timelocks {
+    fn union(self, other: Self) -> Self {
+        Self {
+            after_height: self.after_height || other.after_height,
+            after_time: self.after_time || other.after_time,
+            older_height: self.older_height || other.older_height,
+            older_time: self.older_time || other.older_time,
+        }
+    }
+
+    /// Whether satisfying both needs a height and a time lock of the same kind, which no
+    /// transaction can do with its single `nLockTime` and `nSequence` per input
+    fn conflicts(self, other: Self) -> bool {
+        (self.after_height && other.after_time)
+            || (self.after_time && other.after_height)
+            || (self.older_height && other.older_time)
+            || (self.older_time && other.older_height)
+    }
+}
+
+/// Name and arguments of `name(arg,...)`, splitting on the commas outside of parentheses
+fn split_call(expr: &str) -> (&str, Vec<&str>) {
+    let (name, rest) = expr
+        .split_once('(')
+        .unwrap_or_else(|| panic!("invalid policy {expr}"));
+    let args = rest
+        .strip_suffix(')')
+        .unwrap_or_else(|| panic!("unbalanced parentheses in {expr}"));
+    let mut depth = 0;
+    let mut start = 0;
+    let mut parts = vec![];
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        assert!(depth >= 0, "unbalanced parentheses in {expr}");
+    }
+    assert_eq!(depth, 0, "unbalanced parentheses in {expr}");
+    parts.push(&args[start..]);
+    (name, parts)
+}
+
+impl Policy {
+    fn parse(expr: &str) -> Self {
+        let (name, args) = split_call(expr);
+        let arity = |n: usize| assert_eq!(args.len(), n, "{name} takes {n} arguments");
+        let locktime = |arg: &str| -> u32 {
+            let n: u32 = arg.parse().expect("invalid locktime");
+            assert!((1..0x8000_0000).contains(&n), "locktime {n} out of range");
+            n
+        };
+        // the relative weights of `or` branches only tune compilation for size
+        let unweighted = |arg: &str| -> Box<Policy> {
+            let arg = match arg.split_once('@') {
+                Some((weight, branch)) if weight.chars().all(|c| c.is_ascii_digit()) => branch,
+                _ => arg,
+            };
+            Box::new(Policy::parse(arg))
+        };
+        match name {
+            "pk" => {
+                arity(1);
+                Policy::Key(args[0].to_string())
+            }
+            "after" => {
+                arity(1);
+                Policy::After(locktime(args[0]))
+            }
+            "older" => {
+                arity(1);
+                let n = locktime(args[0]);
+                assert!(n & !0x0040_ffff == 0, "invalid relative locktime {n}");
+                Policy::Older(n)
+            }
+            "sha256" => {
+                arity(1);
+                let hash = <[u8; 32]>::from_hex(args[0]).expect("invalid sha256 hash");
+                Policy::Sha256(hash)
+            }
+            "and" => {
+                arity(2);
+                Policy::And(unweighted(args[0]), unweighted(args[1]))
+            }
+            "or" => {
+                arity(2);
+                Policy::Or(unweighted(args[0]), unweighted(args[1]))
+            }
+            "thresh" => {
+                assert!(args.len() >= 2, "thresh takes a threshold and policies");
+                let k: usize = args[0].parse().expect("invalid threshold");
+                let subs: Vec<Policy> = args[1..].iter().map(|arg| Policy::parse(arg)).collect();
+                assert!(
+                    k >= 1 && k <= subs.len(),
+                    "invalid threshold {k} of {}",
+                    subs.len()
+                );
+                Policy::Thresh(k, subs)
+            }
+            _ => panic!("unsupported policy {name}"),
+        }
+    }
+
+    /// Timelocks of the policy, panics if a branch mixes height and time locks of the same
+    /// kind like `and(after(100),after(500000001))`, which could never be satisfied
+    fn timelocks(&self) -> Timelocks {
+        const TYPE_FLAG: u32 = 1 << 22;
+        match self {
+            Policy::Key(_) | Policy::Sha256(_) => Timelocks::default(),
+            Policy::After(n) => Timelocks {
+                after_height: *n < 500_000_000,
+                after_time: *n >= 500_000_000,
+                ..Default::default()
+            },
+            Policy::Older(n) => Timelocks {
+                older_height: n & TYPE_FLAG == 0,
+                older_time: n & TYPE_FLAG != 0,
+                ..Default::default()
+            },
+            Policy::Or(x, y) => x.timelocks().union(y.timelocks()),
+            Policy::And(x, y) => {
+                let (x, y) = (x.timelocks(), y.timelocks());
+                assert!(
+                    !x.conflicts(y),
+                    "mixed height and time timelocks in {self:?}"
+                );
+                x.union(y)
+            }
+            Policy::Thresh(k, subs) => {
+                let subs: Vec<Timelocks> = subs.iter().map(Policy::timelocks).collect();
+                // any two of the policies are satisfied together when k > 1
+                for (i, x) in subs.iter().enumerate() {
+                    let conflict = subs[i + 1..].iter().any(|y| x.conflicts(*y));
+                    assert!(
+                        *k == 1 || !conflict,
+                        "mixed height and time timelocks in {self:?}"
+                    );
+                }
+                subs.into_iter()
+                    .fold(Timelocks::default(), Timelocks::union)
+            }
+        }
+    }
+
+    /// Miniscript of the policy, keys replaced by their index in `keys`
+    fn compile(&self, ctx: MiniscriptType, keys: &mut Vec<String>) -> Fragment {
+        match self {
+            Policy::Key(key) => {
+                assert!(!keys.contains(key), "key {key} used twice");
+                keys.push(key.clone());
+                Fragment::Pk(keys.len() - 1)
+            }
+            Policy::After(n) => Fragment::After(*n),
+            Policy::Older(n) => Fragment::Older(*n),
+            Policy::Sha256(hash) => Fragment::Sha256(*hash),
+            Policy::And(x, y) => Fragment::AndV(
+                Box::new(Fragment::Wrap('v', Box::new(x.compile(ctx, keys)))),
+                Box::new(y.compile(ctx, keys)),
+            ),
+            Policy::Or(x, z) => {
+                let (x, z) = (x.compile(ctx, keys), z.compile(ctx, keys));
+                match (x.du(), z.du()) {
+                    ((true, true), _) => Fragment::OrD(Box::new(x), Box::new(z)),
+                    (_, (true, true)) => Fragment::OrD(Box::new(z), Box::new(x)),
+                    _ => Fragment::OrI(Box::new(x), Box::new(z)),
+                }
+            }
+            Policy::Thresh(k, subs)
+                if subs.iter().all(|sub| matches!(sub, Policy::Key(_)))
+                    && (ctx == MiniscriptType::Tr || subs.len() <= MAX_MULTI_KEYS) =>
+            {
+                let indices = subs
+                    .iter()
+                    .map(|sub| match sub.compile(ctx, keys) {
+                        Fragment::Pk(i) => i,
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                match ctx {
+                    MiniscriptType::Wsh => Fragment::Multi(*k, indices),
+                    MiniscriptType::Tr => Fragment::MultiA(*k, indices),
+                }
+            }
+            Policy::Thresh(k, subs) => {
+                let subs = subs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, sub)| match (i, sub.compile(ctx, keys).into_du()) {
+                        (0, sub) => sub,
+                        (_, sub) => Fragment::Wrap('a', Box::new(sub)),
+                    })
+                    .collect();
+                Fragment::Thresh(*k, subs)
+            }
+        }
+    }
+}
+
+/// Witness stack, bottom first
+type Stack = Vec<Vec<u8>>;
+
+fn stack_size(stack: &Stack) -> usize {
+    stack.iter().map(|item| item.len() + 1).sum()
+}
+
+/// `x` executed after `y`, so with its witness on top
+fn then(y: Option<Stack>, x: Option<Stack>) -> Option<Stack> {
+    Some([y?, x?].concat())
+}
+
+fn smallest(a: Option<Stack>, b: Option<Stack>) -> Option<Stack> {
+    match (a, b) {
+        (Some(a), Some(b)) if stack_size(&b) < stack_size(&a) => Some(b),
+        (Some(a), _) => Some(a),
+        (None, b) => b,
+    }
+}
+
+/// Values a witness can satisfy a miniscript with
+struct Satisfier<'a> {
+    signature: &'a dyn Fn(usize) -> Option<Vec<u8>>,
+    preimages: &'a BTreeMap<[u8; 32], Vec<u8>>,
+    tx: &'a Transaction,
+    input: usize,
+}
+
+impl Satisfier<'_> {
+    /// `OP_CHECKLOCKTIMEVERIFY` succeeds with the locktime of the transaction
+    fn after(&self, n: u32) -> bool {
+        let lock_time = self.tx.lock_time.to_consensus_u32();
+        let is_height = |n: u32| n < 500_000_000;
+        is_height(n) == is_height(lock_time)
+            && lock_time >= n
+            && self.tx.input[self.input]
+                .sequence
+                .enables_absolute_lock_time()
+    }
+
+    /// `OP_CHECKSEQUENCEVERIFY` succeeds with the sequence of the input
+    fn older(&self, n: u32) -> bool {
+        const TYPE_FLAG: u32 = 1 << 22;
+        let sequence = self.tx.input[self.input].sequence.to_consensus_u32();
+        self.tx.version.0 >= 2
+            && sequence & (1 << 31) == 0
+            && sequence & TYPE_FLAG == n & TYPE_FLAG
+            && sequence & 0xffff >= n & 0xffff
+    }
+}
+
+/// A miniscript fragment of the subset policies compile to
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Fragment {
+    Pk(usize),
+    Multi(usize, Vec<usize>),
+    MultiA(usize, Vec<usize>),
+    After(u32),
+    Older(u32),
+    Sha256([u8; 32]),
+    AndV(Box<Fragment>, Box<Fragment>),
+    OrD(Box<Fragment>, Box<Fragment>),
+    OrI(Box<Fragment>, Box<Fragment>),
+    Thresh(usize, Vec<Fragment>),
+    /// `v:`, `n:`, `a:` or `l:` wrapper
+    Wrap(char, Box<Fragment>),
+}
+
+impl Fragment {
+    /// Dissatisfiable and unit properties, as `thresh` and the left of `or_d` require
+    fn du(&self) -> (bool, bool) {
+        match self {
+            Fragment::Pk(_)
+            | Fragment::Multi(..)
+            | Fragment::MultiA(..)
+            | Fragment::Sha256(_)
+            | Fragment::Thresh(..) => (true, true),
+            Fragment::After(_) | Fragment::Older(_) => (false, false),
+            Fragment::AndV(_, y) => (false, y.du().1),
+            Fragment::OrD(_, z) => z.du(),
+            Fragment::OrI(x, z) => {
+                let ((xd, xu), (zd, zu)) = (x.du(), z.du());
+                (xd || zd, xu && zu)
+            }
+            Fragment::Wrap('v', _) => (false, false),
+            Fragment::Wrap('n', x) => (x.du().0, true),
+            Fragment::Wrap('l', x) => (true, x.du().1),
+            Fragment::Wrap(_, x) => x.du(),
+        }
+    }
+
+    /// Number of keys of the `multi` fragments, counted as opcodes when executed
+    fn multi_keys(&self) -> usize {
+        match self {
+            Fragment::Multi(_, indices) => indices.len(),
+            Fragment::AndV(x, y) | Fragment::OrD(x, y) | Fragment::OrI(x, y) => {
+                x.multi_keys() + y.multi_keys()
+            }
+            Fragment::Thresh(_, subs) => subs.iter().map(Fragment::multi_keys).sum(),
+            Fragment::Wrap(_, x) => x.multi_keys(),
+            _ => 0,
+        }
+    }
+
+    /// Panics if the P2WSH script exceeds the size or opcode limits, so that no funds are sent to
+    /// an address that can't be spent. Tapscript has neither limit.
+    fn check_limits(&self, ctx: MiniscriptType, key_count: usize) {
+        if ctx == MiniscriptType::Tr {
+            return;
+        }
+        // sizes don't depend on the keys, all compressed
+        let key = PublicKey::from_str(
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        )
+        .unwrap();
+        let script = self
+            .encode(Builder::new(), &vec![key; key_count], ctx, false)
+            .into_script();
+        assert!(
+            script.len() <= MAX_WSH_SCRIPT_SIZE,
+            "witness script of {} bytes exceeds {MAX_WSH_SCRIPT_SIZE}",
+            script.len()
+        );
+        let ops = script
+            .instructions()
+            .filter(|instruction| match instruction {
+                Ok(Instruction::Op(op)) => op.to_u8() > OP_PUSHNUM_16.to_u8(),
+                _ => false,
+            })
+            .count()
+            + self.multi_keys();
+        assert!(
+            ops <= MAX_WSH_OPS,
+            "witness script of {ops} opcodes exceeds {MAX_WSH_OPS}"
+        );
+    }
+
+    /// Wrapped with `n:` and `l:` as needed to be dissatisfiable and unit
+    fn into_du(self) -> Self {
+        let x = match self.du().1 {
+            true => self,
+            false => Fragment::Wrap('n', Box::new(self)),
+        };
+        match x.du().0 {
+            true => x,
+            false => Fragment::Wrap('l', Box::new(x)),
+        }
+    }
+
+    fn display(&self, key: &dyn Fn(usize) -> String) -> String {
+        let list = |subs: &[&Fragment]| -> String {
+            subs.iter()
+                .map(|sub| sub.display(key))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let keys = |k: &usize, indices: &[usize]| -> String {
+            let keys: Vec<String> = indices.iter().map(|i| key(*i)).collect();
+            format!("{k},{}", keys.join(","))
+        };
+        match self {
+            Fragment::Pk(i) => format!("pk({})", key(*i)),
+            Fragment::Multi(k, indices) => format!("multi({})", keys(k, indices)),
+            Fragment::MultiA(k, indices) => format!("multi_a({})", keys(k, indices)),
+            Fragment::After(n) => format!("after({n})"),
+            Fragment::Older(n) => format!("older({n})"),
+            Fragment::Sha256(hash) => format!("sha256({})", hash.as_hex()),
+            Fragment::AndV(x, y) => format!("and_v({})", list(&[x, y])),
+            Fragment::OrD(x, z) => format!("or_d({})", list(&[x, z])),
+            Fragment::OrI(x, z) => format!("or_i({})", list(&[x, z])),
+            Fragment::Thresh(k, subs) => {
+                let subs: Vec<&Fragment> = subs.iter().collect();
+                format!("thresh({k},{})", list(&subs))
+            }
+            Fragment::Wrap(wrapper, x) => match x.as_ref() {
+                Fragment::Wrap(..) => format!("{wrapper}{}", x.display(key)),
+                _ => format!("{wrapper}:{}", x.display(key)),
+            },
+        }
+    }
+
+    /// Script of the fragment, `verify` fusing a `v:` wrapper into the last opcode when possible
+    fn encode(
+        &self,
+        builder: Builder,
+        keys: &[PublicKey],
+        ctx: MiniscriptType,
+        verify: bool,
+    ) -> Builder {
+        let fused = |opcode, verify_opcode| match verify {
+            true => verify_opcode,
+            false => opcode,
+        };
+        let push_key = |builder: Builder, i: usize| match ctx {
+            MiniscriptType::Wsh => builder.push_key(&bitcoin::PublicKey::new(keys[i])),
+            MiniscriptType::Tr => builder.push_x_only_key(&keys[i].x_only_public_key().0),
+        };
+        let builder = match self {
+            Fragment::Pk(i) => {
+                return push_key(builder, *i).push_opcode(fused(OP_CHECKSIG, OP_CHECKSIGVERIFY))
+            }
+            Fragment::Multi(k, indices) => {
+                let builder = indices
+                    .iter()
+                    .fold(builder.push_int(*k as i64), |b, i| push_key(b, *i));
+                return builder
+                    .push_int(indices.len() as i64)
+                    .push_opcode(fused(OP_CHECKMULTISIG, OP_CHECKMULTISIGVERIFY));
+            }
+            Fragment::MultiA(k, indices) => {
+                let builder = indices.iter().enumerate().fold(builder, |b, (n, i)| {
+                    let opcode = match n {
+                        0 => OP_CHECKSIG,
+                        _ => OP_CHECKSIGADD,
+                    };
+                    push_key(b, *i).push_opcode(opcode)
+                });
+                return builder
+                    .push_int(*k as i64)
+                    .push_opcode(fused(OP_NUMEQUAL, OP_NUMEQUALVERIFY));
+            }
+            Fragment::After(n) => builder.push_int(*n as i64).push_opcode(OP_CLTV),
+            Fragment::Older(n) => builder.push_int(*n as i64).push_opcode(OP_CSV),
+            Fragment::Sha256(hash) => {
+                return builder
+                    .push_opcode(OP_SIZE)
+                    .push_int(32)
+                    .push_opcode(OP_EQUALVERIFY)
+                    .push_opcode(OP_SHA256)
+                    .push_slice(hash)
+                    .push_opcode(fused(OP_EQUAL, OP_EQUALVERIFY))
+            }
+            Fragment::AndV(x, y) => {
+                let builder = x.encode(builder, keys, ctx, false);
+                return y.encode(builder, keys, ctx, verify);
+            }
+            Fragment::OrD(x, z) => {
+                let builder = x
+                    .encode(builder, keys, ctx, false)
+                    .push_opcode(OP_IFDUP)
+                    .push_opcode(OP_NOTIF);
+                z.encode(builder, keys, ctx, false).push_opcode(OP_ENDIF)
+            }
+            Fragment::OrI(x, z) => {
+                let builder = x.encode(builder.push_opcode(OP_IF), keys, ctx, false);
+                z.encode(builder.push_opcode(OP_ELSE), keys, ctx, false)
+                    .push_opcode(OP_ENDIF)
+            }
+            Fragment::Thresh(k, subs) => {
+                let builder = subs.iter().enumerate().fold(builder, |b, (n, sub)| {
+                    let b = sub.encode(b, keys, ctx, false);
+                    match n {
+                        0 => b,
+                        _ => b.push_opcode(OP_ADD),
+                    }
+                });
+                return builder
+                    .push_int(*k as i64)
+                    .push_opcode(fused(OP_EQUAL, OP_EQUALVERIFY));
+            }
+            Fragment::Wrap('v', x) => return x.encode(builder, keys, ctx, true),
+            Fragment::Wrap('n', x) => x
+                .encode(builder, keys, ctx, false)
+                .push_opcode(OP_0NOTEQUAL),
+            Fragment::Wrap('a', x) => x
+                .encode(builder.push_opcode(OP_TOALTSTACK), keys, ctx, false)
+                .push_opcode(OP_FROMALTSTACK),
+            Fragment::Wrap('l', x) => {
+                let builder = builder.push_opcode(OP_IF).push_int(0).push_opcode(OP_ELSE);
+                x.encode(builder, keys, ctx, false).push_opcode(OP_ENDIF)
+            }
+            Fragment::Wrap(wrapper, _) => unreachable!("unsupported wrapper {wrapper}"),
+        };
+        match verify {
+            true => builder.push_opcode(OP_VERIFY),
+            false => builder,
+        }
+    }
+
+    /// Smallest satisfaction and dissatisfaction witnesses, `None` if there is none
+    fn satisfy(&self, satisfier: &Satisfier) -> (Option<Stack>, Option<Stack>) {
+        match self {
+            Fragment::Pk(i) => (
+                (satisfier.signature)(*i).map(|sig| vec![sig]),
+                Some(vec![vec![]]),
+            ),
+            Fragment::Multi(k, indices) => {
+                // signatures in key order, after the extra element CHECKMULTISIG pops
+                let mut stack = vec![vec![]];
+                stack.extend(
+                    indices
+                        .iter()
+                        .filter_map(|i| (satisfier.signature)(*i))
+                        .take(*k),
+                );
+                let sat = (stack.len() == k + 1).then_some(stack);
+                (sat, Some(vec![vec![]; k + 1]))
+            }
+            Fragment::MultiA(k, indices) => {
+                // the first key checks the top of the stack, the unused ones an empty signature
+                let mut count = 0;
+                let mut stack: Stack = indices
+                    .iter()
+                    .map(|i| match (satisfier.signature)(*i) {
+                        Some(sig) if count < *k => {
+                            count += 1;
+                            sig
+                        }
+                        _ => vec![],
+                    })
+                    .collect();
+                stack.reverse();
+                let sat = (count == *k).then_some(stack);
+                (sat, Some(vec![vec![]; indices.len()]))
+            }
+            Fragment::After(n) => (satisfier.after(*n).then(Vec::new), None),
+            Fragment::Older(n) => (satisfier.older(*n).then(Vec::new), None),
+            Fragment::Sha256(hash) => (
+                satisfier.preimages.get(hash).map(|p| vec![p.clone()]),
+                Some(vec![vec![0; 32]]),
+            ),
+            Fragment::AndV(x, y) => {
+                let (x, y) = (x.satisfy(satisfier), y.satisfy(satisfier));
+                (then(y.0, x.0), None)
+            }
+            Fragment::OrD(x, z) => {
+                let (x, z) = (x.satisfy(satisfier), z.satisfy(satisfier));
+                (smallest(x.0, then(z.0, x.1.clone())), then(z.1, x.1))
+            }
+            Fragment::OrI(x, z) => {
+                let (x, z) = (x.satisfy(satisfier), z.satisfy(satisfier));
+                let one = Some(vec![vec![1]]);
+                let zero = Some(vec![vec![]]);
+                (
+                    smallest(then(x.0, one.clone()), then(z.0, zero.clone())),
+                    smallest(then(x.1, one), then(z.1, zero)),
+                )
+            }
+            Fragment::Thresh(k, subs) => {
+                let witnesses: Vec<(Option<Stack>, Option<Stack>)> =
+                    subs.iter().map(|sub| sub.satisfy(satisfier)).collect();
+                let dissat = witnesses
+                    .iter()
+                    .rev()
+                    .try_fold(vec![], |stack, (_, dissat)| {
+                        then(Some(stack), dissat.clone())
+                    });
+
+                // satisfy the k cheapest, the others dissatisfied
+                let mut order: Vec<usize> = (0..subs.len())
+                    .filter(|i| witnesses[*i].0.is_some() && witnesses[*i].1.is_some())
+                    .collect();
+                order.sort_by_key(|i| {
+                    let (sat, dissat) = &witnesses[*i];
+                    stack_size(sat.as_ref().unwrap()) as i64
+                        - stack_size(dissat.as_ref().unwrap()) as i64
+                });
+                let sat = match order.len() >= *k {
+                    true => witnesses.iter().enumerate().rev().try_fold(
+                        vec![],
+                        |stack, (i, (sat, dissat))| {
+                            let witness = match order[..*k].contains(&i) {
+                                true => sat.clone(),
+                                false => dissat.clone(),
+                            };
+                            then(Some(stack), witness)
+                        },
+                    ),
+                    false => None,
+                };
+                (sat, dissat)
+            }
+            Fragment::Wrap('v', x) => (x.satisfy(satisfier).0, None),
+            Fragment::Wrap('l', x) => (
+                then(x.satisfy(satisfier).0, Some(vec![vec![]])),
+                Some(vec![vec![1]]),
+            ),
+            Fragment::Wrap(_, x) => x.satisfy(satisfier),
+        }
+    }
+}
+
+/// Miniscript of a policy, keys replaced by their index in the returned keys
+fn compile_policy(policy: &str, ctx: MiniscriptType) -> (Fragment, Vec<String>) {
+    let policy: String = policy.chars().filter(|c| !c.is_whitespace()).collect();
+    let policy = Policy::parse(&policy);
+    policy.timelocks();
+    let mut keys = vec![];
+    let fragment = policy.compile(ctx, &mut keys);
+    fragment.check_limits(ctx, keys.len());
+    (fragment, keys)
+}
+
+/// ### Compile a policy to miniscript
+///
+/// policy:
+/// e.g. `or(thresh(2,pk(A),pk(B),pk(C)),and(pk(D),older(26280)))` for 2-of-3, or D alone after
+/// about 6 months. Supports `pk`, `after`, `older`, `sha256`, `and`, `or` with optional weights
+/// and `thresh`.
+///
+/// Policies mixing height and time timelocks of the same kind in one branch, or whose P2WSH
+/// script exceeds 3600 bytes or 201 opcodes, are rejected.
+///
+/// returns the miniscript with the keys as given, e.g.
+/// `or_d(multi(2,A,B,C),and_v(v:pk(D),older(26280)))`
+pub fn miniscript_compile(policy: &str, script_type: MiniscriptType) -> String {
+    let (fragment, keys) = compile_policy(policy, script_type);
+    fragment.display(&|i| keys[i].clone())
+}
+
+/// A wallet of outputs locked by a miniscript spending policy, such as a multisig with a
+/// timelocked recovery key.
+///
+/// The keys of the policy are account xpubs with origin like the cosigners of
+/// [`MultisigWallet`](crate::multisig_wallet::MultisigWallet), derived at
+/// `/<is_change>/<index>`. The local `HDWallet` signs for the ones of its master fingerprint.
+pub struct MiniscriptWallet {
+    secp: Secp256k1<All>,
+    root: Xpriv,
+    script_type: MiniscriptType,
+    fragment: Fragment,
+    keys: Vec<Cosigner>,
+}
+
+impl MiniscriptWallet {
+    /// ### Create a miniscript wallet
+    ///
+    /// policy:
+    /// see [`miniscript_compile`], with keys as `[fingerprint/path]xpub...`, e.g.
+    /// `HDWallet::bip48_xpub` of each participant
+    pub fn new(wallet: &HDWallet, policy: &str, script_type: MiniscriptType) -> Self {
+        let root = Xpriv::from_str(&wallet.export_master_priv()).unwrap();
+        let (fragment, keys) = compile_policy(policy, script_type);
+        let keys: Vec<Cosigner> = keys.iter().map(|key| Cosigner::parse(key)).collect();
+        assert!(
+            keys.iter().all(|c| c.xpub.network == root.network),
+            "key on another network"
+        );
+        Self {
+            secp: Secp256k1::new(),
+            root,
+            script_type,
+            fragment,
+            keys,
+        }
+    }
+
+    fn network(&self) -> Network {
+        self.root.network
+    }
+
+    /// ### Output descriptor of the receive or change addresses, with checksum
+    pub fn descriptor(&self, is_change: bool) -> String {
+        let miniscript = self
+            .fragment
+            .display(&|i| format!("{}/{}/*", self.keys[i].descriptor_key(), is_change as u32));
+        let descriptor = match self.script_type {
+            MiniscriptType::Wsh => format!("wsh({miniscript})"),
+            MiniscriptType::Tr => format!("tr({NUMS_INTERNAL_KEY},{miniscript})"),
+        };
+        let checksum = descriptor_checksum(&descriptor);
+        format!("{descriptor}#{checksum}")
+    }
+
+    /// Public keys at `/<is_change>/<index>` in policy order, with their key origins
+    fn derive(&self, is_change: bool, index: u32) -> Vec<(PublicKey, KeySource)> {
+        self.keys
+            .iter()
+            .map(|c| c.derive(&self.secp, is_change, index))
+            .collect()
+    }
+
+    /// Witness script of P2WSH, or the single leaf of taproot
+    fn witness_script(&self, is_change: bool, index: u32) -> ScriptBuf {
+        let keys: Vec<PublicKey> = self
+            .derive(is_change, index)
+            .into_iter()
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+        self.fragment
+            .encode(Builder::new(), &keys, self.script_type, false)
+            .into_script()
+    }
+
+    fn spend_info(&self, is_change: bool, index: u32) -> TaprootSpendInfo {
+        TaprootBuilder::new()
+            .add_leaf(0, self.witness_script(is_change, index))
+            .unwrap()
+            .finalize(
+                &self.secp,
+                XOnlyPublicKey::from_str(NUMS_INTERNAL_KEY).unwrap(),
+            )
+            .unwrap()
+    }
+
+    fn script_address(&self, is_change: bool, index: u32) -> Address {
+        match self.script_type {
+            MiniscriptType::Wsh => {
+                Address::p2wsh(&self.witness_script(is_change, index), self.network())
+            }
+            MiniscriptType::Tr => Address::p2tr_tweaked(
+                self.spend_info(is_change, index).output_key(),
+                self.network(),
+            ),
+        }
+    }
+
+    pub fn address(&self, is_change: bool, index: u32) -> String {
+        self.script_address(is_change, index).to_string()
+    }
+
+    /// ### Create a PSBT spending outputs of the wallet
+    ///
+    /// tx_hex:
+    /// unsigned transaction, with the `nLockTime` and `nSequence` of the timelocks to satisfy
+    ///
+    /// prevouts:
+    /// the outputs spent by each input, in input order
+    ///
+    /// returns the PSBT in base64
+    pub fn create_psbt(&self, tx_hex: &str, prevouts: Vec<MultisigPrevout>) -> String {
+        let tx: Transaction =
+            consensus::deserialize(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap();
+        assert_eq!(tx.input.len(), prevouts.len());
+
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for (i, prevout) in prevouts.iter().enumerate() {
+            let outpoint = psbt.unsigned_tx.input[i].previous_output;
+            assert_eq!(outpoint.txid.to_string(), prevout.txid);
+            assert_eq!(outpoint.vout, prevout.vout);
+
+            let (is_change, index) = (prevout.is_change, prevout.index);
+            let witness_script = self.witness_script(is_change, index);
+            let keys = self.derive(is_change, index);
+            let input = &mut psbt.inputs[i];
+            input.witness_utxo = Some(TxOut {
+                value: Amount::from_btc(prevout.amount).unwrap(),
+                script_pubkey: self.script_address(is_change, index).script_pubkey(),
+            });
+            match self.script_type {
+                MiniscriptType::Wsh => {
+                    input.witness_script = Some(witness_script);
+                    input.bip32_derivation = keys.into_iter().collect();
+                }
+                MiniscriptType::Tr => {
+                    let spend_info = self.spend_info(is_change, index);
+                    let leaf = (witness_script, LeafVersion::TapScript);
+                    let leaf_hash = TapLeafHash::from_script(&leaf.0, leaf.1);
+                    let control_block = spend_info.control_block(&leaf).unwrap();
+                    input.tap_internal_key = Some(spend_info.internal_key());
+                    input.tap_merkle_root = spend_info.merkle_root();
+                    input.tap_scripts.insert(control_block, leaf);
+                    for (pubkey, origin) in keys {
+                        let (x_only, _) = pubkey.x_only_public_key();
+                        input
+                            .tap_key_origins
+                            .insert(x_only, (vec![leaf_hash], origin));
+                    }
+                }
+            }
+        }
+        psbt.to_string()
+    }
+
+    /// ### Add the local signatures to a PSBT
+    ///
+    /// inputs that can't be signed, e.g. missing their UTXO, are left unsigned and reported in
+    /// [`SignedPsbt::errors`]
+    ///
+    /// returns the PSBT with the indexes of the signed inputs
+    pub fn sign_psbt(&self, psbt: &str) -> SignedPsbt {
+        sign_psbt_inputs(&self.secp, &self.root, psbt)
+    }
+
+    /// ### Finalize a PSBT, satisfying the policy with the smallest witness available
+    ///
+    /// preimages:
+    /// preimages in hex of the `sha256` hashes of the policy, in addition to those of the PSBT
+    ///
+    /// timelocks are satisfied when the `nLockTime` and `nSequence` of the transaction allow
+    ///
+    /// returns the signed transaction in hex, ready to broadcast, `None` if an input can't be
+    /// satisfied yet, e.g. missing signatures or before its timelock, or misses its scripts
+    pub fn finalize_psbt(&self, psbt: &str, preimages: Vec<String>) -> Option<String> {
+        let mut psbt = Psbt::from_str(psbt).unwrap();
+        let preimages: Vec<Vec<u8>> = preimages
+            .iter()
+            .map(|preimage| Vec::<u8>::from_hex(preimage).ok())
+            .collect::<Option<_>>()?;
+        for i in 0..psbt.inputs.len() {
+            let witness = self.witness(&psbt.unsigned_tx, i, &psbt.inputs[i], &preimages)?;
+            let input = &mut psbt.inputs[i];
+            *input = Input {
+                witness_utxo: input.witness_utxo.take(),
+                final_script_witness: Some(witness),
+                ..Default::default()
+            };
+        }
+        let tx = psbt.extract_tx().ok()?;
+        Some(consensus::serialize(&tx).as_hex().to_string())
+    }
+
+    /// Smallest witness satisfying the policy for an input, `None` if there is none yet
+    fn witness(
+        &self,
+        tx: &Transaction,
+        i: usize,
+        input: &Input,
+        preimages: &[Vec<u8>],
+    ) -> Option<Witness> {
+        let preimages: BTreeMap<[u8; 32], Vec<u8>> = preimages
+            .iter()
+            .chain(input.sha256_preimages.values())
+            .map(|preimage| {
+                (
+                    sha256::Hash::hash(preimage).to_byte_array(),
+                    preimage.clone(),
+                )
+            })
+            .collect();
+
+        let (mut witness, script, control_block) = match self.script_type {
+            MiniscriptType::Wsh => {
+                let script = input.witness_script.clone()?;
+                let keys = self.input_keys(input)?;
+                let signature = |k: usize| {
+                    let pubkey = bitcoin::PublicKey::new(keys[k]);
+                    input.partial_sigs.get(&pubkey).map(|sig| sig.to_vec())
+                };
+                let satisfier = Satisfier {
+                    signature: &signature,
+                    preimages: &preimages,
+                    tx,
+                    input: i,
+                };
+                let sat = self.fragment.satisfy(&satisfier).0;
+                (sat, script, None)
+            }
+            MiniscriptType::Tr => {
+                let (control_block, (script, leaf_version)) = input.tap_scripts.iter().next()?;
+                let leaf_hash = TapLeafHash::from_script(script, *leaf_version);
+                let keys = self.input_keys(input)?;
+                let signature = |k: usize| {
+                    let (x_only, _) = keys[k].x_only_public_key();
+                    input
+                        .tap_script_sigs
+                        .get(&(x_only, leaf_hash))
+                        .map(|sig| sig.to_vec())
+                };
+                let satisfier = Satisfier {
+                    signature: &signature,
+                    preimages: &preimages,
+                    tx,
+                    input: i,
+                };
+                let sat = self.fragment.satisfy(&satisfier).0;
+                (sat, script.clone(), Some(control_block.serialize()))
+            }
+        };
+        let witness = witness.as_mut()?;
+        witness.push(script.to_bytes());
+        witness.extend(control_block);
+        Some(Witness::from_slice(witness))
+    }
+
+    /// Keys of the policy in the script of an input, found from their origins in the PSBT,
+    /// `None` if one is missing
+    fn input_keys(&self, input: &Input) -> Option<Vec<PublicKey>> {
+        let origins: BTreeMap<KeySource, PublicKey> = input
+            .bip32_derivation
+            .iter()
+            .map(|(pubkey, origin)| (origin.clone(), *pubkey))
+            .chain(input.tap_key_origins.iter().map(|(x_only, (_, origin))| {
+                // the parity is lost in x-only keys and not needed in tapscript
+                (
+                    origin.clone(),
+                    x_only.public_key(bitcoin::secp256k1::Parity::Even),
+                )
+            }))
+            .collect();
+        self.keys
+            .iter()
+            .map(|c| {
+                let (fingerprint, path) = &c.origin;
+                origins
+                    .iter()
+                    .find(|((f, p), _)| {
+                        f == fingerprint
+                            && p.len() == path.len() + 2
+                            && p.as_ref().starts_with(path.as_ref())
+                    })
+                    .map(|(_, pubkey)| *pubkey)
+            })
+            .collect()
+    }
+}
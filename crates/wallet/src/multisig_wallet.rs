@@ -0,0 +1,674 @@
+use crate::hd_wallet::HDWallet;
+use bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint, KeySource, Xpriv, Xpub};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::Hash;
+use bitcoin::hex::DisplayHex;
+use bitcoin::key::TapTweak;
+use bitcoin::opcodes::all::{OP_CHECKMULTISIG, OP_CHECKSIG, OP_CHECKSIGADD, OP_NUMEQUAL};
+use bitcoin::psbt::Psbt;
+use bitcoin::script::{Builder, Instruction, PushBytesBuf};
+use bitcoin::secp256k1::{All, Message, PublicKey, Secp256k1, XOnlyPublicKey};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::taproot::{LeafVersion, TapLeafHash, TaprootBuilder, TaprootSpendInfo};
+use bitcoin::{
+    consensus, taproot, Address, Amount, Network, ScriptBuf, TapSighashType, Transaction, TxOut,
+    Witness,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+/// Maximum number of keys of a `sortedmulti` in P2WSH under standardness rules
+const MAX_KEYS: usize = 20;
+
+/// BIP-341 NUMS point `H`, an internal key without known private key disabling the key path
+pub(crate) const NUMS_INTERNAL_KEY: &str =
+    "50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+
+/// Script type of a multisig wallet, with the BIP48 script type of its account keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultisigScriptType {
+    /// P2SH-P2WSH, `sh(wsh(sortedmulti(...)))`, BIP48 script type 1'
+    P2shP2wsh,
+    /// native P2WSH, `wsh(sortedmulti(...))`, BIP48 script type 2'
+    P2wsh,
+    /// taproot script path, `tr(internal_key,sortedmulti_a(...))`, BIP48 script type 3'
+    P2tr,
+}
+
+impl MultisigScriptType {
+    pub(crate) fn bip48_script_type(&self) -> u32 {
+        match self {
+            MultisigScriptType::P2shP2wsh => 1,
+            MultisigScriptType::P2wsh => 2,
+            MultisigScriptType::P2tr => 3,
+        }
+    }
+}
+
+/// Output being spent by a multisig input, like [`crate::signer::Prevout`] plus the address
+/// it pays to
+pub struct MultisigPrevout {
+    pub txid: String,
+    pub vout: u32,
+    pub amount: f64,
+    pub is_change: bool,
+    pub index: u32,
+}
+
+/// A cosigner account key with its key origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Cosigner {
+    pub(crate) origin: KeySource,
+    pub(crate) xpub: Xpub,
+}
+
+impl Cosigner {
+    /// Parse `[fingerprint/48h/0h/0h/2h]xpub...` as exported by Sparrow and Bitcoin Core, or a
+    /// bare xpub. A trailing `/0/*` or `/<0;1>/*` is ignored.
+    pub(crate) fn parse(key: &str) -> Self {
+        let (origin, key) = match key.strip_prefix('[') {
+            Some(rest) => rest.split_once(']').expect("unterminated key origin"),
+            None => ("", key),
+        };
+        let xpub = Xpub::from_str(key.split('/').next().unwrap()).unwrap();
+        let origin = match origin.split_once('/') {
+            _ if origin.is_empty() => (xpub.fingerprint(), DerivationPath::master()),
+            Some((fingerprint, path)) => (
+                Fingerprint::from_str(fingerprint).unwrap(),
+                DerivationPath::from_str(&format!("m/{path}")).unwrap(),
+            ),
+            None => (
+                Fingerprint::from_str(origin).unwrap(),
+                DerivationPath::master(),
+            ),
+        };
+        Self { origin, xpub }
+    }
+
+    pub(crate) fn descriptor_key(&self) -> String {
+        let (fingerprint, path) = &self.origin;
+        let path: String = path.into_iter().map(|c| format!("/{c:#}")).collect();
+        format!("[{fingerprint}{path}]{}", self.xpub)
+    }
+
+    pub(crate) fn derive(
+        &self,
+        secp: &Secp256k1<All>,
+        is_change: bool,
+        index: u32,
+    ) -> (PublicKey, KeySource) {
+        let children = [
+            ChildNumber::from_normal_idx(is_change as u32).unwrap(),
+            ChildNumber::from_normal_idx(index).unwrap(),
+        ];
+        let pubkey = self.xpub.derive_pub(secp, &children).unwrap().public_key;
+        let (fingerprint, path) = &self.origin;
+        (pubkey, (*fingerprint, path.extend(children)))
+    }
+}
+
+/// BIP-380 descriptor checksum
+pub(crate) fn descriptor_checksum(descriptor: &str) -> String {
+    const INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+    const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+    const GENERATOR: [u64; 5] = [
+        0xf5dee51989,
+        0xa9fdca3312,
+        0x1bab10e32d,
+        0x3706b1677a,
+        0x644d626ffd,
+    ];
+
+    fn polymod(c: u64, value: u64) -> u64 {
+        let c0 = c >> 35;
+        let mut c = ((c & 0x7ffffffff) << 5) ^ value;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (c0 >> i) & 1 == 1 {
+                c ^= generator;
+            }
+        }
+        c
+    }
+
+    let mut c = 1;
+    let mut class = 0;
+    let mut class_count = 0;
+    for ch in descriptor.chars() {
+        let pos = INPUT_CHARSET
+            .find(ch)
+            .expect("invalid descriptor character") as u64;
+        c = polymod(c, pos & 31);
+        class = class * 3 + (pos >> 5);
+        class_count += 1;
+        if class_count == 3 {
+            c = polymod(c, class);
+            class = 0;
+            class_count = 0;
+        }
+    }
+    if class_count > 0 {
+        c = polymod(c, class);
+    }
+    for _ in 0..8 {
+        c = polymod(c, 0);
+    }
+    c ^= 1;
+    (0..8)
+        .map(|i| CHECKSUM_CHARSET[((c >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect()
+}
+
+/// ### Combine partial signatures of several copies of the same PSBT
+///
+/// returns the merged PSBT in base64
+pub fn combine_psbts(psbts: Vec<String>) -> String {
+    let mut psbts = psbts.iter().map(|psbt| Psbt::from_str(psbt).unwrap());
+    let mut combined = psbts.next().expect("no psbt");
+    for psbt in psbts {
+        combined.combine(psbt).unwrap();
+    }
+    combined.to_string()
+}
+
+/// A PSBT with the local signatures added, see [`MultisigWallet::sign_psbt`]
+pub struct SignedPsbt {
+    /// the PSBT in base64, to be merged with [`combine_psbts`]
+    pub psbt: String,
+    /// indexes of the inputs that got a local signature
+    pub signed_inputs: Vec<u32>,
+    /// `input <index>: <reason>` for each input with a local key that couldn't be signed
+    pub errors: Vec<String>,
+}
+
+/// Add the schnorr signatures of the keys of `root` listed in the taproot key origins of the
+/// inputs, through the key path when one is the internal key
+///
+/// returns the indexes of the signed inputs, and the errors of the inputs that couldn't be
+/// signed, e.g. when an input misses its witness UTXO, which all taproot sighashes commit to
+fn sign_taproot(
+    secp: &Secp256k1<All>,
+    root: &Xpriv,
+    psbt: &mut Psbt,
+) -> (BTreeSet<usize>, BTreeMap<usize, String>) {
+    let mut signed = BTreeSet::new();
+    let mut errors = BTreeMap::new();
+    let utxos = psbt
+        .inputs
+        .iter()
+        .map(|input| input.witness_utxo.clone())
+        .collect::<Option<Vec<TxOut>>>();
+    let prevouts = utxos.as_deref().map(Prevouts::All);
+    let mut cache = SighashCache::new(&psbt.unsigned_tx);
+    let fingerprint = root.fingerprint(secp);
+    let hash_ty = TapSighashType::Default;
+
+    for (i, input) in psbt.inputs.iter_mut().enumerate() {
+        for (x_only, (leaf_hashes, (key_fingerprint, path))) in &input.tap_key_origins {
+            if *key_fingerprint != fingerprint {
+                continue;
+            }
+            let keypair = root.derive_priv(secp, path).unwrap().to_keypair(secp);
+            if keypair.x_only_public_key().0 != *x_only {
+                continue;
+            }
+            let Some(prevouts) = &prevouts else {
+                errors.insert(i, "missing witness utxo of an input".to_string());
+                break;
+            };
+
+            if input.tap_internal_key == Some(*x_only) {
+                match cache.taproot_key_spend_signature_hash(i, prevouts, hash_ty) {
+                    Ok(sighash) => {
+                        let msg = Message::from_digest(sighash.to_byte_array());
+                        let tweaked = keypair.tap_tweak(secp, input.tap_merkle_root);
+                        input.tap_key_sig = Some(taproot::Signature {
+                            sig: secp.sign_schnorr(&msg, &tweaked.to_inner()),
+                            hash_ty,
+                        });
+                        signed.insert(i);
+                    }
+                    Err(e) => {
+                        errors.insert(i, e.to_string());
+                    }
+                }
+            }
+            for leaf_hash in leaf_hashes {
+                match cache.taproot_script_spend_signature_hash(i, prevouts, *leaf_hash, hash_ty) {
+                    Ok(sighash) => {
+                        let msg = Message::from_digest(sighash.to_byte_array());
+                        input.tap_script_sigs.insert(
+                            (*x_only, *leaf_hash),
+                            taproot::Signature {
+                                sig: secp.sign_schnorr(&msg, &keypair),
+                                hash_ty,
+                            },
+                        );
+                        signed.insert(i);
+                    }
+                    Err(e) => {
+                        errors.insert(i, e.to_string());
+                    }
+                }
+            }
+        }
+    }
+    (signed, errors)
+}
+
+/// Whether the key origins of an input list a key of the wallet with this fingerprint
+fn has_local_key(input: &bitcoin::psbt::Input, fingerprint: Fingerprint) -> bool {
+    input
+        .bip32_derivation
+        .values()
+        .any(|(key_fingerprint, _)| *key_fingerprint == fingerprint)
+        || input
+            .tap_key_origins
+            .values()
+            .any(|(_, (key_fingerprint, _))| *key_fingerprint == fingerprint)
+}
+
+/// Sign every input of a PSBT whose key origins list a key of `root`, with ECDSA through the
+/// PSBT signer of rust-bitcoin and with schnorr through [`sign_taproot`]
+pub(crate) fn sign_psbt_inputs(secp: &Secp256k1<All>, root: &Xpriv, psbt: &str) -> SignedPsbt {
+    let mut psbt = Psbt::from_str(psbt).unwrap();
+    let fingerprint = root.fingerprint(secp);
+
+    // the PSBT signer of rust-bitcoin skips taproot inputs and inputs missing their UTXO, and
+    // keeps the signatures of the other inputs when some fail
+    let (used, errors) = match psbt.sign(root, secp) {
+        Ok(used) => (used, BTreeMap::new()),
+        Err((used, errors)) => (used, errors),
+    };
+    let mut errors: BTreeMap<usize, String> = errors
+        .into_iter()
+        .map(|(i, e)| (i, e.to_string()))
+        .collect();
+    let (mut signed, taproot_errors) = sign_taproot(secp, root, &mut psbt);
+    errors.extend(taproot_errors);
+    signed.extend(
+        used.into_iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(i, _)| i),
+    );
+
+    for (i, input) in psbt.inputs.iter().enumerate() {
+        if !signed.contains(&i) && !errors.contains_key(&i) && has_local_key(input, fingerprint) {
+            let reason = match input.witness_utxo.is_none() && input.non_witness_utxo.is_none() {
+                true => "missing utxo",
+                false => "no key of the wallet could sign it",
+            };
+            errors.insert(i, reason.to_string());
+        }
+    }
+
+    SignedPsbt {
+        psbt: psbt.to_string(),
+        signed_inputs: signed.into_iter().map(|i| i as u32).collect(),
+        errors: errors
+            .into_iter()
+            .map(|(i, e)| format!("input {i}: {e}"))
+            .collect(),
+    }
+}
+
+/// An m-of-n `sortedmulti` wallet of cosigner account xpubs and the local `HDWallet`, compatible
+/// with the multisig descriptors of Sparrow and Bitcoin Core
+pub struct MultisigWallet {
+    secp: Secp256k1<All>,
+    root: Xpriv,
+    threshold: u8,
+    script_type: MultisigScriptType,
+    cosigners: Vec<Cosigner>,
+    /// key path key of taproot wallets, the NUMS point if `None`
+    internal_key: Option<Cosigner>,
+}
+
+impl MultisigWallet {
+    /// ### Create a multisig wallet
+    ///
+    /// wallet:
+    /// the local signer, its BIP48 account key `HDWallet::bip48_xpub` is added to the cosigners
+    /// unless already listed
+    ///
+    /// cosigner_xpubs:
+    /// account xpubs of the other cosigners, as `[fingerprint/48h/0h/0h/2h]xpub...` or bare
+    pub fn new(
+        wallet: &HDWallet,
+        threshold: u8,
+        cosigner_xpubs: Vec<String>,
+        script_type: MultisigScriptType,
+    ) -> Self {
+        let root = Xpriv::from_str(&wallet.export_master_priv()).unwrap();
+        let local = Cosigner::parse(&wallet.bip48_xpub(script_type));
+        let mut cosigners: Vec<Cosigner> = cosigner_xpubs
+            .iter()
+            .map(|key| Cosigner::parse(key))
+            .collect();
+        if !cosigners.iter().any(|c| c.xpub == local.xpub) {
+            cosigners.push(local);
+        }
+        // the descriptor is the same whatever order each cosigner lists the keys in
+        cosigners.sort_by_key(|c| c.xpub.to_string());
+
+        assert!(
+            cosigners.iter().all(|c| c.xpub.network == root.network),
+            "cosigner on another network"
+        );
+        assert!(cosigners.len() <= MAX_KEYS, "too many cosigners");
+        assert!(
+            threshold >= 1 && threshold as usize <= cosigners.len(),
+            "invalid threshold {threshold}"
+        );
+
+        Self {
+            secp: Secp256k1::new(),
+            root,
+            threshold,
+            script_type,
+            cosigners,
+            internal_key: None,
+        }
+    }
+
+    /// ### Create a taproot multisig wallet
+    ///
+    /// the cosigners spend through a single `sortedmulti_a` leaf, see [`MultisigWallet::new`]
+    ///
+    /// internal_key:
+    /// xpub with origin like the cosigners', able to spend alone through the key path, e.g. a
+    /// MuSig2 aggregate. `None` uses the BIP-341 NUMS point so that only the leaf can spend.
+    pub fn new_with_internal_key(
+        wallet: &HDWallet,
+        threshold: u8,
+        cosigner_xpubs: Vec<String>,
+        internal_key: Option<String>,
+    ) -> Self {
+        let mut multisig = Self::new(wallet, threshold, cosigner_xpubs, MultisigScriptType::P2tr);
+        multisig.internal_key = internal_key.map(|key| Cosigner::parse(&key));
+        multisig
+    }
+
+    fn network(&self) -> Network {
+        self.root.network
+    }
+
+    /// ### Output descriptor of the receive or change addresses, with checksum
+    ///
+    /// can be imported in Sparrow or with Bitcoin Core's `importdescriptors`
+    pub fn descriptor(&self, is_change: bool) -> String {
+        let keys: Vec<String> = self
+            .cosigners
+            .iter()
+            .map(|c| format!("{}/{}/*", c.descriptor_key(), is_change as u32))
+            .collect();
+        let keys = keys.join(",");
+        let threshold = self.threshold;
+        let descriptor = match self.script_type {
+            MultisigScriptType::P2shP2wsh => format!("sh(wsh(sortedmulti({threshold},{keys})))"),
+            MultisigScriptType::P2wsh => format!("wsh(sortedmulti({threshold},{keys}))"),
+            MultisigScriptType::P2tr => {
+                let internal_key = match &self.internal_key {
+                    Some(c) => format!("{}/{}/*", c.descriptor_key(), is_change as u32),
+                    None => NUMS_INTERNAL_KEY.to_string(),
+                };
+                format!("tr({internal_key},sortedmulti_a({threshold},{keys}))")
+            }
+        };
+        let checksum = descriptor_checksum(&descriptor);
+        format!("{descriptor}#{checksum}")
+    }
+
+    /// Public keys at `/<is_change>/<index>` sorted as in the script, with their key origins
+    fn derive(&self, is_change: bool, index: u32) -> Vec<(PublicKey, KeySource)> {
+        let mut keys: Vec<(PublicKey, KeySource)> = self
+            .cosigners
+            .iter()
+            .map(|c| c.derive(&self.secp, is_change, index))
+            .collect();
+        match self.script_type {
+            MultisigScriptType::P2tr => {
+                keys.sort_by_key(|(pubkey, _)| pubkey.x_only_public_key().0.serialize())
+            }
+            _ => keys.sort_by_key(|(pubkey, _)| pubkey.serialize()),
+        }
+        keys
+    }
+
+    /// `OP_CHECKMULTISIG` script of P2WSH, or the `OP_CHECKSIGADD` leaf of taproot wallets
+    fn witness_script(&self, is_change: bool, index: u32) -> ScriptBuf {
+        let keys = self.derive(is_change, index);
+        if self.script_type == MultisigScriptType::P2tr {
+            let mut builder = Builder::new();
+            for (i, (pubkey, _)) in keys.iter().enumerate() {
+                let opcode = match i {
+                    0 => OP_CHECKSIG,
+                    _ => OP_CHECKSIGADD,
+                };
+                builder = builder
+                    .push_x_only_key(&pubkey.x_only_public_key().0)
+                    .push_opcode(opcode);
+            }
+            return builder
+                .push_int(self.threshold as i64)
+                .push_opcode(OP_NUMEQUAL)
+                .into_script();
+        }
+
+        let mut builder = Builder::new().push_int(self.threshold as i64);
+        for (pubkey, _) in &keys {
+            builder = builder.push_key(&bitcoin::PublicKey::new(*pubkey));
+        }
+        builder
+            .push_int(keys.len() as i64)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    /// Internal key of a taproot wallet at `/<is_change>/<index>`, with its key origin unless NUMS
+    fn internal_key(&self, is_change: bool, index: u32) -> (XOnlyPublicKey, Option<KeySource>) {
+        match &self.internal_key {
+            Some(c) => {
+                let (pubkey, origin) = c.derive(&self.secp, is_change, index);
+                (pubkey.x_only_public_key().0, Some(origin))
+            }
+            None => (XOnlyPublicKey::from_str(NUMS_INTERNAL_KEY).unwrap(), None),
+        }
+    }
+
+    fn spend_info(&self, is_change: bool, index: u32) -> TaprootSpendInfo {
+        let (internal_key, _) = self.internal_key(is_change, index);
+        TaprootBuilder::new()
+            .add_leaf(0, self.witness_script(is_change, index))
+            .unwrap()
+            .finalize(&self.secp, internal_key)
+            .unwrap()
+    }
+
+    fn script_address(&self, is_change: bool, index: u32) -> Address {
+        match self.script_type {
+            MultisigScriptType::P2shP2wsh => {
+                Address::p2shwsh(&self.witness_script(is_change, index), self.network())
+            }
+            MultisigScriptType::P2wsh => {
+                Address::p2wsh(&self.witness_script(is_change, index), self.network())
+            }
+            MultisigScriptType::P2tr => Address::p2tr_tweaked(
+                self.spend_info(is_change, index).output_key(),
+                self.network(),
+            ),
+        }
+    }
+
+    pub fn address(&self, is_change: bool, index: u32) -> String {
+        self.script_address(is_change, index).to_string()
+    }
+
+    /// ### Create a PSBT spending multisig outputs
+    ///
+    /// tx_hex:
+    /// unsigned transaction
+    ///
+    /// prevouts:
+    /// the outputs spent by each input, in input order
+    ///
+    /// returns the PSBT in base64 with the scripts and key origins every cosigner needs to sign
+    pub fn create_psbt(&self, tx_hex: &str, prevouts: Vec<MultisigPrevout>) -> String {
+        let tx: Transaction =
+            consensus::deserialize(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap();
+        assert_eq!(tx.input.len(), prevouts.len());
+
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for (i, prevout) in prevouts.iter().enumerate() {
+            let outpoint = psbt.unsigned_tx.input[i].previous_output;
+            assert_eq!(outpoint.txid.to_string(), prevout.txid);
+            assert_eq!(outpoint.vout, prevout.vout);
+
+            let (is_change, index) = (prevout.is_change, prevout.index);
+            let witness_script = self.witness_script(is_change, index);
+            let input = &mut psbt.inputs[i];
+            input.witness_utxo = Some(TxOut {
+                value: Amount::from_btc(prevout.amount).unwrap(),
+                script_pubkey: self.script_address(is_change, index).script_pubkey(),
+            });
+            let keys = self.derive(is_change, index);
+            match self.script_type {
+                MultisigScriptType::P2tr => {
+                    let spend_info = self.spend_info(is_change, index);
+                    let leaf = (witness_script, LeafVersion::TapScript);
+                    let leaf_hash = TapLeafHash::from_script(&leaf.0, leaf.1);
+                    let control_block = spend_info.control_block(&leaf).unwrap();
+
+                    let (internal_key, internal_origin) = self.internal_key(is_change, index);
+                    input.tap_internal_key = Some(internal_key);
+                    input.tap_merkle_root = spend_info.merkle_root();
+                    input.tap_scripts.insert(control_block, leaf);
+                    for (pubkey, origin) in keys {
+                        let (x_only, _) = pubkey.x_only_public_key();
+                        input
+                            .tap_key_origins
+                            .insert(x_only, (vec![leaf_hash], origin));
+                    }
+                    if let Some(origin) = internal_origin {
+                        input
+                            .tap_key_origins
+                            .entry(internal_key)
+                            .or_insert((vec![], origin));
+                    }
+                }
+                _ => {
+                    if self.script_type == MultisigScriptType::P2shP2wsh {
+                        input.redeem_script = Some(witness_script.to_p2wsh());
+                    }
+                    input.witness_script = Some(witness_script);
+                    input.bip32_derivation = keys.into_iter().collect();
+                }
+            }
+        }
+        psbt.to_string()
+    }
+
+    /// ### Add the local signatures to a PSBT
+    ///
+    /// signs every input whose key origins include the local key, see [`MultisigWallet::create_psbt`].
+    /// Taproot inputs get a key path signature too when the internal key is local. Inputs that
+    /// can't be signed, e.g. missing their UTXO, are left unsigned and reported in
+    /// [`SignedPsbt::errors`].
+    ///
+    /// returns the PSBT with the indexes of the signed inputs
+    pub fn sign_psbt(&self, psbt: &str) -> SignedPsbt {
+        sign_psbt_inputs(&self.secp, &self.root, psbt)
+    }
+
+    /// ### Finalize a PSBT with enough signatures
+    ///
+    /// taproot inputs are spent through the key path when signed by the internal key, else
+    /// through the `sortedmulti_a` leaf
+    ///
+    /// returns the signed transaction in hex, ready to broadcast
+    pub fn finalize_psbt(&self, psbt: &str) -> String {
+        let mut psbt = Psbt::from_str(psbt).unwrap();
+        for input in psbt.inputs.iter_mut() {
+            if self.script_type == MultisigScriptType::P2tr {
+                input.final_script_witness = Some(self.taproot_witness(input));
+                input.tap_key_sig = None;
+                input.tap_script_sigs.clear();
+                input.tap_scripts.clear();
+                input.tap_key_origins.clear();
+                input.tap_internal_key = None;
+                input.tap_merkle_root = None;
+                continue;
+            }
+
+            let witness_script = input.witness_script.take().expect("missing witness script");
+            let signatures: Vec<Vec<u8>> = witness_script
+                .instructions()
+                .filter_map(|instruction| match instruction.unwrap() {
+                    Instruction::PushBytes(bytes) => {
+                        bitcoin::PublicKey::from_slice(bytes.as_bytes()).ok()
+                    }
+                    Instruction::Op(_) => None,
+                })
+                .filter_map(|pubkey| input.partial_sigs.get(&pubkey))
+                .take(self.threshold as usize)
+                .map(|signature| signature.to_vec())
+                .collect();
+            assert_eq!(
+                signatures.len(),
+                self.threshold as usize,
+                "not enough signatures"
+            );
+
+            // CHECKMULTISIG pops one extra element
+            let mut witness = vec![vec![]];
+            witness.extend(signatures);
+            witness.push(witness_script.to_bytes());
+            input.final_script_witness = Some(Witness::from_slice(&witness));
+            if let Some(redeem_script) = input.redeem_script.take() {
+                let redeem_script = PushBytesBuf::try_from(redeem_script.to_bytes()).unwrap();
+                input.final_script_sig =
+                    Some(Builder::new().push_slice(redeem_script).into_script());
+            }
+            input.partial_sigs.clear();
+            input.bip32_derivation.clear();
+            input.sighash_type = None;
+        }
+        consensus::serialize(&psbt.extract_tx().unwrap())
+            .as_hex()
+            .to_string()
+    }
+
+    fn taproot_witness(&self, input: &bitcoin::psbt::Input) -> Witness {
+        if let Some(signature) = input.tap_key_sig {
+            return Witness::from_slice(&[signature.to_vec()]);
+        }
+
+        let (control_block, (script, leaf_version)) =
+            input.tap_scripts.iter().next().expect("missing tap script");
+        let leaf_hash = TapLeafHash::from_script(script, *leaf_version);
+        let keys: Vec<XOnlyPublicKey> = script
+            .instructions()
+            .filter_map(|instruction| match instruction.unwrap() {
+                Instruction::PushBytes(bytes) => XOnlyPublicKey::from_slice(bytes.as_bytes()).ok(),
+                Instruction::Op(_) => None,
+            })
+            .collect();
+
+        // the first key checks the top of the stack, and the leaf requires exactly `threshold`
+        // valid signatures with the others empty
+        let mut witness = vec![];
+        let mut count = 0;
+        for key in keys.iter().rev() {
+            match input.tap_script_sigs.get(&(*key, leaf_hash)) {
+                Some(signature) if count < self.threshold => {
+                    witness.push(signature.to_vec());
+                    count += 1;
+                }
+                _ => witness.push(vec![]),
+            }
+        }
+        assert_eq!(count, self.threshold, "not enough signatures");
+        witness.push(script.to_bytes());
+        witness.push(control_block.serialize());
+        Witness::from_slice(&witness)
+    }
+}
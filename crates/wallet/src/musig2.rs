@@ -0,0 +1,664 @@
+use crate::hd_wallet::HDWallet;
+use crate::multisig_wallet::{descriptor_checksum, MultisigPrevout};
+use bitcoin::bip32::{ChainCode, ChildNumber, DerivationPath, Fingerprint, Xpriv, Xpub};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::hex::DisplayHex;
+use bitcoin::psbt::{raw, Input, Psbt};
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
+use bitcoin::secp256k1::{All, Secp256k1, XOnlyPublicKey};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::{
+    consensus, taproot, Address, Amount, Network, TapSighashType, Transaction, TxOut, Witness,
+};
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::point::AffineCoordinates;
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::elliptic_curve::PrimeField;
+use k256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar, U256};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// BIP-328 chain code of aggregate xpubs
+const AGGREGATE_CHAIN_CODE: &str =
+    "868087ca02a6f974c4598924c36b57762d32cb45717167e300622c7167e38965";
+
+/// BIP-373 PSBT input fields
+const PSBT_IN_MUSIG2_PARTICIPANT_PUBKEYS: u8 = 0x1a;
+const PSBT_IN_MUSIG2_PUB_NONCE: u8 = 0x1b;
+const PSBT_IN_MUSIG2_PARTIAL_SIG: u8 = 0x1c;
+
+pub(crate) fn tagged_hash(tag: &str, data: &[&[u8]]) -> [u8; 32] {
+    let tag = sha256::Hash::hash(tag.as_bytes());
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    for data in data {
+        engine.input(data);
+    }
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// Integer of 32 bytes mod n
+pub(crate) fn reduce(bytes: [u8; 32]) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(&bytes.into())
+}
+
+/// Integer of 32 bytes, `None` if not below n
+pub(crate) fn parse_scalar(bytes: &[u8]) -> Option<Scalar> {
+    Option::from(Scalar::from_repr(*FieldBytes::from_slice(bytes)))
+}
+
+/// Point of a 33 bytes compressed key
+pub(crate) fn parse_point(bytes: &[u8]) -> Option<ProjectivePoint> {
+    let encoded = EncodedPoint::from_bytes(bytes).ok()?;
+    if bytes.len() != 33 {
+        return None;
+    }
+    Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&encoded)).map(Into::into)
+}
+
+/// Like [`parse_point`], with 33 zero bytes for the point at infinity
+fn parse_point_ext(bytes: &[u8]) -> Option<ProjectivePoint> {
+    match bytes.iter().all(|b| *b == 0) {
+        true => Some(ProjectivePoint::IDENTITY),
+        false => parse_point(bytes),
+    }
+}
+
+pub(crate) fn cbytes(point: &ProjectivePoint) -> [u8; 33] {
+    point
+        .to_affine()
+        .to_encoded_point(true)
+        .as_bytes()
+        .try_into()
+        .unwrap()
+}
+
+fn cbytes_ext(point: &ProjectivePoint) -> [u8; 33] {
+    match *point == ProjectivePoint::IDENTITY {
+        true => [0; 33],
+        false => cbytes(point),
+    }
+}
+
+pub(crate) fn xbytes(point: &ProjectivePoint) -> [u8; 32] {
+    point.to_affine().x().into()
+}
+
+pub(crate) fn has_even_y(point: &ProjectivePoint) -> bool {
+    !bool::from(point.to_affine().y_is_odd())
+}
+
+/// `1` if the point has an even Y, else `-1`
+pub(crate) fn parity(point: &ProjectivePoint) -> Scalar {
+    match has_even_y(point) {
+        true => Scalar::ONE,
+        false => -Scalar::ONE,
+    }
+}
+
+fn parse_pubkey(pubkey_hex: &str) -> [u8; 33] {
+    let pubkey: [u8; 33] = Vec::<u8>::from_hex(pubkey_hex)
+        .unwrap()
+        .try_into()
+        .expect("invalid public key length");
+    parse_point(&pubkey).expect("invalid public key");
+    pubkey
+}
+
+/// BIP-327 key aggregation context, with the tweaks applied so far
+#[derive(Clone)]
+struct KeyAggContext {
+    pubkeys: Vec<[u8; 33]>,
+    list_hash: [u8; 32],
+    second_key: Option<[u8; 33]>,
+    q: ProjectivePoint,
+    gacc: Scalar,
+    tacc: Scalar,
+}
+
+impl KeyAggContext {
+    fn new(pubkeys: Vec<[u8; 33]>) -> Self {
+        let list_hash = tagged_hash("KeyAgg list", &[&pubkeys.concat()]);
+        let second_key = pubkeys.iter().find(|pk| **pk != pubkeys[0]).copied();
+        let mut ctx = Self {
+            pubkeys,
+            list_hash,
+            second_key,
+            q: ProjectivePoint::IDENTITY,
+            gacc: Scalar::ONE,
+            tacc: Scalar::ZERO,
+        };
+        ctx.q = ctx
+            .pubkeys
+            .iter()
+            .map(|pk| parse_point(pk).expect("invalid public key") * ctx.coefficient(pk))
+            .sum();
+        assert!(
+            ctx.q != ProjectivePoint::IDENTITY,
+            "aggregate key at infinity"
+        );
+        ctx
+    }
+
+    fn coefficient(&self, pubkey: &[u8; 33]) -> Scalar {
+        if Some(*pubkey) == self.second_key {
+            return Scalar::ONE;
+        }
+        reduce(tagged_hash(
+            "KeyAgg coefficient",
+            &[&self.list_hash, pubkey],
+        ))
+    }
+
+    fn apply_tweak(&mut self, tweak: Scalar, is_xonly: bool) {
+        let g = match is_xonly {
+            true => parity(&self.q),
+            false => Scalar::ONE,
+        };
+        self.q = self.q * g + ProjectivePoint::GENERATOR * tweak;
+        assert!(
+            self.q != ProjectivePoint::IDENTITY,
+            "tweaked key at infinity"
+        );
+        self.gacc = g * self.gacc;
+        self.tacc = tweak + g * self.tacc;
+    }
+
+    /// BIP-341 tweak of a key path only output, as BIP86 does
+    fn apply_taproot_tweak(&mut self) {
+        let tweak = tagged_hash("TapTweak", &[&xbytes(&self.q)]);
+        self.apply_tweak(parse_scalar(&tweak).expect("invalid tweak"), true);
+    }
+
+    /// BIP-328 xpub of the aggregate key, without tweaks
+    fn xpub(&self, network: Network) -> Xpub {
+        Xpub {
+            network,
+            depth: 0,
+            parent_fingerprint: Fingerprint::default(),
+            child_number: ChildNumber::from_normal_idx(0).unwrap(),
+            public_key: bitcoin::secp256k1::PublicKey::from_slice(&cbytes(&self.q)).unwrap(),
+            chain_code: ChainCode::from_hex(AGGREGATE_CHAIN_CODE).unwrap(),
+        }
+    }
+
+    /// Apply the plain tweaks of an unhardened BIP32 derivation of [`KeyAggContext::xpub`]
+    fn derive(&mut self, secp: &Secp256k1<All>, network: Network, path: &DerivationPath) {
+        let mut xpub = self.xpub(network);
+        for child in path {
+            let (tweak, _) = xpub.ckd_pub_tweak(*child).unwrap();
+            self.apply_tweak(parse_scalar(&tweak.secret_bytes()).unwrap(), false);
+            xpub = xpub.ckd_pub(secp, *child).unwrap();
+        }
+    }
+}
+
+/// Secret nonce of round 1, never serialized so that it can't be reused
+struct SecNonce {
+    k1: Scalar,
+    k2: Scalar,
+    pubkey: [u8; 33],
+}
+
+impl SecNonce {
+    fn pubnonce(&self) -> [u8; 66] {
+        let r1 = cbytes(&(ProjectivePoint::GENERATOR * self.k1));
+        let r2 = cbytes(&(ProjectivePoint::GENERATOR * self.k2));
+        [r1, r2].concat().try_into().unwrap()
+    }
+}
+
+/// BIP-327 NonceGen with the secret key, aggregate key and message all given
+fn nonce_gen(secret: &Scalar, pubkey: &[u8; 33], aggpk: &[u8; 32], msg: &[u8; 32]) -> SecNonce {
+    let mut rand = [0u8; 32];
+    thread_rng().fill_bytes(&mut rand);
+    let aux = tagged_hash("MuSig/aux", &[&rand]);
+    let secret: [u8; 32] = secret.to_bytes().into();
+    let rand: Vec<u8> = secret.iter().zip(aux).map(|(s, a)| s ^ a).collect();
+
+    let mut msg_prefixed = vec![1];
+    msg_prefixed.extend((msg.len() as u64).to_be_bytes());
+    msg_prefixed.extend(msg);
+    let k = |i: u8| {
+        let k = reduce(tagged_hash(
+            "MuSig/nonce",
+            &[
+                &rand,
+                &[33],
+                pubkey,
+                &[32],
+                aggpk,
+                &msg_prefixed,
+                &0u32.to_be_bytes(),
+                &[i],
+            ],
+        ));
+        assert!(!bool::from(k.is_zero()), "zero nonce");
+        k
+    };
+    SecNonce {
+        k1: k(0),
+        k2: k(1),
+        pubkey: *pubkey,
+    }
+}
+
+fn nonce_agg(pubnonces: &[[u8; 66]]) -> [u8; 66] {
+    let aggregate = |offset: usize| -> ProjectivePoint {
+        pubnonces
+            .iter()
+            .map(|nonce| parse_point(&nonce[offset..offset + 33]).expect("invalid public nonce"))
+            .sum()
+    };
+    [cbytes_ext(&aggregate(0)), cbytes_ext(&aggregate(33))]
+        .concat()
+        .try_into()
+        .unwrap()
+}
+
+/// BIP-327 session values of a message signed by the tweaked aggregate key
+struct Session {
+    keyagg: KeyAggContext,
+    b: Scalar,
+    r: ProjectivePoint,
+    e: Scalar,
+}
+
+impl Session {
+    fn new(aggnonce: &[u8; 66], keyagg: KeyAggContext, msg: &[u8; 32]) -> Self {
+        let q = xbytes(&keyagg.q);
+        let b = reduce(tagged_hash("MuSig/noncecoef", &[aggnonce, &q, msg]));
+        let r1 = parse_point_ext(&aggnonce[..33]).expect("invalid aggregate nonce");
+        let r2 = parse_point_ext(&aggnonce[33..]).expect("invalid aggregate nonce");
+        let r = match r1 + r2 * b {
+            r if r == ProjectivePoint::IDENTITY => ProjectivePoint::GENERATOR,
+            r => r,
+        };
+        let e = reduce(tagged_hash("BIP0340/challenge", &[&xbytes(&r), &q, msg]));
+        Self { keyagg, b, r, e }
+    }
+
+    fn coefficient(&self, pubkey: &[u8; 33]) -> Scalar {
+        assert!(self.keyagg.pubkeys.contains(pubkey), "not a participant");
+        self.keyagg.coefficient(pubkey)
+    }
+
+    /// Consumes the secret nonce
+    fn sign(&self, secnonce: SecNonce, secret: &Scalar) -> Scalar {
+        let pubkey = cbytes(&(ProjectivePoint::GENERATOR * secret));
+        assert_eq!(pubkey, secnonce.pubkey, "nonce of another key");
+        let (k1, k2) = match has_even_y(&self.r) {
+            true => (secnonce.k1, secnonce.k2),
+            false => (-secnonce.k1, -secnonce.k2),
+        };
+        let d = parity(&self.keyagg.q) * self.keyagg.gacc * secret;
+        k1 + self.b * k2 + self.e * self.coefficient(&pubkey) * d
+    }
+
+    fn verify(&self, psig: &Scalar, pubnonce: &[u8; 66], pubkey: &[u8; 33]) -> bool {
+        let (Some(r1), Some(r2), Some(p)) = (
+            parse_point(&pubnonce[..33]),
+            parse_point(&pubnonce[33..]),
+            parse_point(pubkey),
+        ) else {
+            return false;
+        };
+        let re = match has_even_y(&self.r) {
+            true => r1 + r2 * self.b,
+            false => -(r1 + r2 * self.b),
+        };
+        let g = parity(&self.keyagg.q) * self.keyagg.gacc;
+        ProjectivePoint::GENERATOR * psig == re + p * (self.e * self.coefficient(pubkey) * g)
+    }
+
+    /// BIP340 signature of the partial signatures of every participant
+    fn aggregate(&self, psigs: &[Scalar]) -> [u8; 64] {
+        let s: Scalar =
+            psigs.iter().sum::<Scalar>() + self.e * parity(&self.keyagg.q) * self.keyagg.tacc;
+        [xbytes(&self.r).to_vec(), s.to_bytes().to_vec()]
+            .concat()
+            .try_into()
+            .unwrap()
+    }
+}
+
+/// ### Aggregate public keys with BIP-327 KeyAgg
+///
+/// pubkeys:
+/// 33 bytes compressed keys, aggregated in the given order
+///
+/// returns the 32 bytes x-only aggregate key
+pub fn musig2_key_agg(pubkeys: Vec<String>) -> String {
+    let pubkeys = pubkeys.iter().map(|pk| parse_pubkey(pk)).collect();
+    xbytes(&KeyAggContext::new(pubkeys).q).as_hex().to_string()
+}
+
+/// ### BIP-328 xpub of the aggregate of public keys
+///
+/// the keys are sorted first, as in `musig()` descriptors
+pub fn musig2_aggregate_xpub(pubkeys: Vec<String>, is_testnet: u8) -> String {
+    let network = match is_testnet {
+        0 => Network::Bitcoin,
+        1 => Network::Testnet,
+        _ => unreachable!(),
+    };
+    let mut pubkeys: Vec<[u8; 33]> = pubkeys.iter().map(|pk| parse_pubkey(pk)).collect();
+    pubkeys.sort();
+    KeyAggContext::new(pubkeys).xpub(network).to_string()
+}
+
+/// ### Aggregate the 66 bytes public nonces of every participant with BIP-327 NonceAgg
+pub fn musig2_nonce_agg(pubnonces: Vec<String>) -> String {
+    let pubnonces: Vec<[u8; 66]> = pubnonces
+        .iter()
+        .map(|nonce| {
+            Vec::<u8>::from_hex(nonce)
+                .unwrap()
+                .try_into()
+                .expect("invalid public nonce length")
+        })
+        .collect();
+    nonce_agg(&pubnonces).as_hex().to_string()
+}
+
+fn musig2_key(type_value: u8, pubkey: &[u8; 33], aggregate: &[u8; 33]) -> raw::Key {
+    raw::Key {
+        type_value,
+        key: [pubkey.as_slice(), aggregate].concat(),
+    }
+}
+
+/// An n-of-n MuSig2 taproot wallet of the BIP86 keys of the participants' `HDWallet`s.
+///
+/// Outputs are `tr(musig(...)/<is_change>/*)` key path only, indistinguishable from single-sig,
+/// and spent in two rounds over a PSBT carrying the BIP-373 fields:
+/// [`Musig2Wallet::nonce_psbt`] by every participant, [`combine_psbts`](crate::multisig_wallet::combine_psbts),
+/// then [`Musig2Wallet::sign_psbt`] by every participant, combine again and
+/// [`Musig2Wallet::finalize_psbt`].
+pub struct Musig2Wallet {
+    secp: Secp256k1<All>,
+    network: Network,
+    secret: Scalar,
+    pubkey: [u8; 33],
+    keyagg: KeyAggContext,
+    /// secret nonces of round 1 by sighash, removed when signing so that none signs twice
+    secnonces: Mutex<BTreeMap<[u8; 32], SecNonce>>,
+}
+
+impl Musig2Wallet {
+    /// ### Create a MuSig2 wallet
+    ///
+    /// cosigner_pubkeys:
+    /// `HDWallet::musig2_pubkey` of the other participants, the local one is added unless
+    /// already listed
+    pub fn new(wallet: &HDWallet, cosigner_pubkeys: Vec<String>) -> Self {
+        let secret = parse_scalar(&Vec::<u8>::from_hex(&wallet.bip86_priv_hex()).unwrap())
+            .expect("invalid private key");
+        let pubkey = parse_pubkey(&wallet.musig2_pubkey());
+        let network = Xpriv::from_str(&wallet.export_master_priv())
+            .unwrap()
+            .network;
+
+        let mut pubkeys: Vec<[u8; 33]> =
+            cosigner_pubkeys.iter().map(|pk| parse_pubkey(pk)).collect();
+        if !pubkeys.contains(&pubkey) {
+            pubkeys.push(pubkey);
+        }
+        pubkeys.sort();
+        pubkeys.dedup();
+        assert!(pubkeys.len() >= 2, "no cosigner");
+
+        Self {
+            secp: Secp256k1::new(),
+            network,
+            secret,
+            pubkey,
+            keyagg: KeyAggContext::new(pubkeys),
+            secnonces: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// BIP-328 xpub of the aggregate key, the parent of every address
+    pub fn aggregate_xpub(&self) -> String {
+        self.keyagg.xpub(self.network).to_string()
+    }
+
+    /// ### BIP-390 `musig()` descriptor of the receive or change addresses, with checksum
+    pub fn descriptor(&self, is_change: bool) -> String {
+        let keys: Vec<String> = self
+            .keyagg
+            .pubkeys
+            .iter()
+            .map(|pk| pk.as_hex().to_string())
+            .collect();
+        let descriptor = format!("tr(musig({})/{}/*)", keys.join(","), is_change as u32);
+        let checksum = descriptor_checksum(&descriptor);
+        format!("{descriptor}#{checksum}")
+    }
+
+    fn path(is_change: bool, index: u32) -> DerivationPath {
+        DerivationPath::from(vec![
+            ChildNumber::from_normal_idx(is_change as u32).unwrap(),
+            ChildNumber::from_normal_idx(index).unwrap(),
+        ])
+    }
+
+    /// Aggregate key derived at `path`, the taproot internal key
+    fn derive(&self, path: &DerivationPath) -> KeyAggContext {
+        let mut keyagg = self.keyagg.clone();
+        keyagg.derive(&self.secp, self.network, path);
+        keyagg
+    }
+
+    pub fn address(&self, is_change: bool, index: u32) -> String {
+        let internal_key = self.derive(&Self::path(is_change, index)).q;
+        let internal_key = XOnlyPublicKey::from_slice(&xbytes(&internal_key)).unwrap();
+        Address::p2tr(&self.secp, internal_key, None, self.network).to_string()
+    }
+
+    /// ### Create a PSBT spending MuSig2 outputs
+    ///
+    /// prevouts:
+    /// the outputs spent by each input, in input order
+    ///
+    /// returns the PSBT in base64 with the BIP-373 participant keys of each input
+    pub fn create_psbt(&self, tx_hex: &str, prevouts: Vec<MultisigPrevout>) -> String {
+        let tx: Transaction =
+            consensus::deserialize(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap();
+        assert_eq!(tx.input.len(), prevouts.len());
+
+        let aggregate = cbytes(&self.keyagg.q);
+        let fingerprint = self.keyagg.xpub(self.network).fingerprint();
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for (i, prevout) in prevouts.iter().enumerate() {
+            let outpoint = psbt.unsigned_tx.input[i].previous_output;
+            assert_eq!(outpoint.txid.to_string(), prevout.txid);
+            assert_eq!(outpoint.vout, prevout.vout);
+
+            let path = Self::path(prevout.is_change, prevout.index);
+            let internal_key = XOnlyPublicKey::from_slice(&xbytes(&self.derive(&path).q)).unwrap();
+            let input = &mut psbt.inputs[i];
+            input.witness_utxo = Some(TxOut {
+                value: Amount::from_btc(prevout.amount).unwrap(),
+                script_pubkey: Address::p2tr(&self.secp, internal_key, None, self.network)
+                    .script_pubkey(),
+            });
+            input.tap_internal_key = Some(internal_key);
+            input
+                .tap_key_origins
+                .insert(internal_key, (vec![], (fingerprint, path)));
+            input.unknown.insert(
+                raw::Key {
+                    type_value: PSBT_IN_MUSIG2_PARTICIPANT_PUBKEYS,
+                    key: aggregate.to_vec(),
+                },
+                self.keyagg.pubkeys.concat(),
+            );
+        }
+        psbt.to_string()
+    }
+
+    /// Sighash of each input of this wallet, with its aggregate key context before the taproot
+    /// tweak
+    fn sessions(&self, psbt: &Psbt) -> Vec<Option<([u8; 32], KeyAggContext)>> {
+        let utxos: Vec<TxOut> = psbt
+            .inputs
+            .iter()
+            .map(|input| input.witness_utxo.clone().expect("missing witness utxo"))
+            .collect();
+        let prevouts = Prevouts::All(&utxos);
+        let mut cache = SighashCache::new(&psbt.unsigned_tx);
+        let participants = raw::Key {
+            type_value: PSBT_IN_MUSIG2_PARTICIPANT_PUBKEYS,
+            key: cbytes(&self.keyagg.q).to_vec(),
+        };
+        let fingerprint = self.keyagg.xpub(self.network).fingerprint();
+
+        psbt.inputs
+            .iter()
+            .enumerate()
+            .map(|(i, input)| {
+                if input.unknown.get(&participants) != Some(&self.keyagg.pubkeys.concat()) {
+                    return None;
+                }
+                let internal_key = input.tap_internal_key?;
+                let (_, (key_fingerprint, path)) = input.tap_key_origins.get(&internal_key)?;
+                if *key_fingerprint != fingerprint {
+                    return None;
+                }
+                let keyagg = self.derive(path);
+                assert_eq!(xbytes(&keyagg.q), internal_key.serialize());
+                let sighash = cache
+                    .taproot_key_spend_signature_hash(i, &prevouts, TapSighashType::Default)
+                    .unwrap();
+                Some((sighash.to_byte_array(), keyagg))
+            })
+            .collect()
+    }
+
+    /// Public nonces of every participant of an input, in key order
+    fn pubnonces(&self, input: &Input, aggregate: &[u8; 33]) -> Vec<[u8; 66]> {
+        self.keyagg
+            .pubkeys
+            .iter()
+            .map(|pk| {
+                let key = musig2_key(PSBT_IN_MUSIG2_PUB_NONCE, pk, aggregate);
+                input
+                    .unknown
+                    .get(&key)
+                    .unwrap_or_else(|| panic!("missing nonce of {}", pk.as_hex()))
+                    .clone()
+                    .try_into()
+                    .expect("invalid public nonce length")
+            })
+            .collect()
+    }
+
+    /// ### Round 1, add the local public nonces to a PSBT
+    ///
+    /// the secret nonces stay in this wallet until [`Musig2Wallet::sign_psbt`]. Running round 1
+    /// again replaces them, invalidating the previous public nonces.
+    pub fn nonce_psbt(&self, psbt: &str) -> String {
+        let mut psbt = Psbt::from_str(psbt).unwrap();
+        let sessions = self.sessions(&psbt);
+        for (input, session) in psbt.inputs.iter_mut().zip(sessions) {
+            let Some((msg, mut keyagg)) = session else {
+                continue;
+            };
+            let aggregate = cbytes(&keyagg.q);
+            keyagg.apply_taproot_tweak();
+            let secnonce = nonce_gen(&self.secret, &self.pubkey, &xbytes(&keyagg.q), &msg);
+            input.unknown.insert(
+                musig2_key(PSBT_IN_MUSIG2_PUB_NONCE, &self.pubkey, &aggregate),
+                secnonce.pubnonce().to_vec(),
+            );
+            self.secnonces.lock().unwrap().insert(msg, secnonce);
+        }
+        psbt.to_string()
+    }
+
+    /// ### Round 2, add the local partial signatures to a PSBT with the nonces of everyone
+    ///
+    /// each secret nonce of round 1 signs once, signing again panics
+    pub fn sign_psbt(&self, psbt: &str) -> String {
+        let mut psbt = Psbt::from_str(psbt).unwrap();
+        let sessions = self.sessions(&psbt);
+        for (input, session) in psbt.inputs.iter_mut().zip(sessions) {
+            let Some((msg, mut keyagg)) = session else {
+                continue;
+            };
+            let aggregate = cbytes(&keyagg.q);
+            keyagg.apply_taproot_tweak();
+            let pubnonces = self.pubnonces(input, &aggregate);
+
+            let secnonce = self.secnonces.lock().unwrap().remove(&msg);
+            let secnonce = secnonce.expect("no unused nonce, run round 1 first");
+            let pubnonce = secnonce.pubnonce();
+            let own = self.keyagg.pubkeys.iter().position(|pk| *pk == self.pubkey);
+            assert_eq!(
+                pubnonces[own.unwrap()],
+                pubnonce,
+                "public nonce from another round"
+            );
+
+            let session = Session::new(&nonce_agg(&pubnonces), keyagg, &msg);
+            let psig = session.sign(secnonce, &self.secret);
+            assert!(session.verify(&psig, &pubnonce, &self.pubkey));
+            input.unknown.insert(
+                musig2_key(PSBT_IN_MUSIG2_PARTIAL_SIG, &self.pubkey, &aggregate),
+                psig.to_bytes().to_vec(),
+            );
+        }
+        psbt.to_string()
+    }
+
+    /// ### Aggregate the partial signatures of every participant
+    ///
+    /// returns the signed transaction in hex, ready to broadcast
+    pub fn finalize_psbt(&self, psbt: &str) -> String {
+        let mut psbt = Psbt::from_str(psbt).unwrap();
+        let sessions = self.sessions(&psbt);
+        for (input, session) in psbt.inputs.iter_mut().zip(sessions) {
+            let Some((msg, mut keyagg)) = session else {
+                continue;
+            };
+            let aggregate = cbytes(&keyagg.q);
+            keyagg.apply_taproot_tweak();
+            let pubnonces = self.pubnonces(input, &aggregate);
+            let session = Session::new(&nonce_agg(&pubnonces), keyagg, &msg);
+
+            let psigs: Vec<Scalar> = self
+                .keyagg
+                .pubkeys
+                .iter()
+                .zip(&pubnonces)
+                .map(|(pk, pubnonce)| {
+                    let key = musig2_key(PSBT_IN_MUSIG2_PARTIAL_SIG, pk, &aggregate);
+                    let psig = input
+                        .unknown
+                        .get(&key)
+                        .and_then(|psig| parse_scalar(psig))
+                        .unwrap_or_else(|| panic!("missing signature of {}", pk.as_hex()));
+                    assert!(
+                        session.verify(&psig, pubnonce, pk),
+                        "invalid signature of {}",
+                        pk.as_hex()
+                    );
+                    psig
+                })
+                .collect();
+
+            let signature = taproot::Signature::from_slice(&session.aggregate(&psigs)).unwrap();
+            input.final_script_witness = Some(Witness::from_slice(&[signature.to_vec()]));
+            input.tap_internal_key = None;
+            input.tap_key_origins.clear();
+            input.unknown.clear();
+        }
+        consensus::serialize(&psbt.extract_tx().unwrap())
+            .as_hex()
+            .to_string()
+    }
+}
@@ -5,13 +5,17 @@ use bitcoin::{
     hex::DisplayHex,
     key::{TapTweak, TweakedKeypair},
     script,
-    secp256k1::{Keypair, Message, Secp256k1, SecretKey},
+    secp256k1::{
+        ecdsa::{RecoverableSignature, RecoveryId},
+        Keypair, Message, Secp256k1, SecretKey,
+    },
     sighash::{Prevouts, SighashCache},
     taproot, Address, Amount, EcdsaSighashType, OutPoint, PublicKey, TapSighashType, Transaction,
-    TxOut, Txid,
+    TxOut, Txid, Witness,
 };
 use std::str::FromStr;
 
+#[derive(Clone)]
 pub struct Prevout {
     pub txid: String,
     pub vout: u32,
@@ -118,7 +122,11 @@ pub fn p2pkh_sign(address: &str, priv_hex: &str, tx_hex: &str) -> String {
     let address = Address::from_str(address).unwrap().assume_checked();
     let network = address.network().clone();
     let private_key = SecretKey::from_str(priv_hex).unwrap();
-    let pubkey = PublicKey::new(private_key.public_key(&secp));
+    let mut pubkey = PublicKey::new(private_key.public_key(&secp));
+    // paper wallets may use the uncompressed public key
+    if Address::p2pkh(&pubkey, network) != address {
+        pubkey.compressed = false;
+    }
     let private_addr = Address::p2pkh(&pubkey, network);
 
     assert_eq!(address, private_addr);
@@ -163,13 +171,117 @@ pub fn p2pkh_sign(address: &str, priv_hex: &str, tx_hex: &str) -> String {
     tx_hex
 }
 
+/// ### Sign a tx with p2wpkh address
+///
+/// address:
+/// bip84 address
+///
+/// priv_hex:
+/// private key in hex, the tx inputs are locked by the p2wpkh address of this private key
+///
+/// tx_hex:
+/// unsigned transaction in hex
+///
+/// tx_prevouts:
+/// responding prevouts of tx inputs, segwit signatures commit to the amounts
+pub fn p2wpkh_sign(
+    address: &str,
+    priv_hex: &str,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+) -> String {
+    let mut unsigned_tx =
+        consensus::deserialize::<Transaction>(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap();
+
+    let secp = Secp256k1::new();
+    let address = Address::from_str(address).unwrap().assume_checked();
+    let network = *address.network();
+    let private_key = SecretKey::from_str(priv_hex).unwrap();
+    let pubkey = PublicKey::new(private_key.public_key(&secp));
+    let private_addr = Address::p2wpkh(&pubkey, network).unwrap();
+
+    assert_eq!(address, private_addr);
+    assert_eq!(unsigned_tx.input.len(), tx_prevouts.len());
+
+    let hash_ty = EcdsaSighashType::All;
+    let mut sighash_cache = SighashCache::new(&mut unsigned_tx);
+    for (i, prevout) in tx_prevouts.iter().enumerate() {
+        let sighash = sighash_cache
+            .p2wpkh_signature_hash(
+                i,
+                private_addr.script_pubkey().as_script(),
+                Amount::from_btc(prevout.amount).unwrap(),
+                hash_ty,
+            )
+            .unwrap();
+
+        let msg = Message::from_digest(sighash.to_byte_array());
+
+        let signature = ecdsa::Signature {
+            sig: secp.sign_ecdsa(&msg, &private_key),
+            hash_ty,
+        };
+
+        *sighash_cache.witness_mut(i).unwrap() = Witness::p2wpkh(&signature, &pubkey.inner);
+    }
+
+    let tx = sighash_cache.into_transaction();
+    consensus::serialize(&tx).as_hex().to_string()
+}
+
+/// DER encoded signature of a 32 bytes digest, `s` is always normalized to the lower half of the
+/// curve order
 pub fn ecdsa_sign(priv_hex: &str, digest_hex: &str) -> String {
     let secp = Secp256k1::new();
     let digest = Vec::<u8>::from_hex(digest_hex).unwrap();
     let msg = Message::from_digest_slice(&digest).unwrap();
     let private_key = SecretKey::from_str(priv_hex).unwrap();
-    secp.sign_ecdsa(&msg, &private_key)
-        .serialize_der()
+    let mut sig = secp.sign_ecdsa(&msg, &private_key);
+    sig.normalize_s();
+    sig.serialize_der().as_hex().to_string()
+}
+
+/// ### Sign a digest with a recoverable signature
+///
+/// returns the 65 bytes compact signature `r || s || recid` in hex where `recid` is 0 to 3,
+/// `s` is always in the lower half of the curve order
+pub fn ecdsa_sign_recoverable(priv_hex: &str, digest_hex: &str) -> String {
+    let secp = Secp256k1::new();
+    let digest = Vec::<u8>::from_hex(digest_hex).unwrap();
+    let msg = Message::from_digest_slice(&digest).unwrap();
+    let private_key = SecretKey::from_str(priv_hex).unwrap();
+    // libsecp256k1 only produces low-S signatures, the recovery id matches that `s`
+    let (recid, sig) = secp
+        .sign_ecdsa_recoverable(&msg, &private_key)
+        .serialize_compact();
+    let mut out = sig.to_vec();
+    out.push(recid.to_i32() as u8);
+    out.as_hex().to_string()
+}
+
+/// ### Recover the public key of a recoverable signature
+///
+/// sig_hex:
+/// 65 bytes `r || s || recid`, `recid` is either 0 to 3 or the Ethereum style 27 to 30
+///
+/// returns the compressed public key in hex
+pub fn recover_pubkey(digest_hex: &str, sig_hex: &str) -> String {
+    let secp = Secp256k1::new();
+    let digest = Vec::<u8>::from_hex(digest_hex).unwrap();
+    let msg = Message::from_digest_slice(&digest).unwrap();
+    let sig = Vec::<u8>::from_hex(sig_hex).unwrap();
+    assert_eq!(sig.len(), 65, "invalid recoverable signature length");
+    let recid = match sig[64] {
+        v @ 0..=3 => v,
+        v @ 27..=30 => v - 27,
+        v => panic!("invalid recovery id {v}"),
+    };
+    let sig =
+        RecoverableSignature::from_compact(&sig[..64], RecoveryId::from_i32(recid as i32).unwrap())
+            .unwrap();
+    secp.recover_ecdsa(&msg, &sig)
+        .unwrap()
+        .serialize()
         .as_hex()
         .to_string()
 }
@@ -0,0 +1,500 @@
+use crate::hd_wallet::HDWallet;
+use crate::multisig_wallet::NUMS_INTERNAL_KEY;
+use crate::musig2::tagged_hash;
+use bitcoin::bip32::{DerivationPath, Xpriv};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::{hash160, Hash};
+use bitcoin::hex::DisplayHex;
+use bitcoin::key::TweakedPublicKey;
+use bitcoin::script::Instruction;
+use bitcoin::secp256k1::{All, Parity, PublicKey, Scalar, Secp256k1, SecretKey, XOnlyPublicKey};
+use bitcoin::{consensus, Address, Network, OutPoint, ScriptBuf, Transaction, TxIn, Txid};
+use std::str::FromStr;
+
+const BECH32M_CONST: u32 = 0x2bc830a3;
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut chk = 1u32;
+    for value in values {
+        let top = chk >> 25;
+        chk = (chk & 0x1ffffff) << 5 ^ *value as u32;
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut values: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|c| c & 31));
+    values
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let mut out = vec![];
+    let max = (1 << to) - 1;
+    for value in data {
+        acc = acc << from | *value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push((acc >> bits & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push((acc << (to - bits) & max) as u8);
+        }
+    } else if bits >= from || acc << (to - bits) & max != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+/// Bech32m of BIP-350, without the 90 characters limit, silent payment addresses are longer
+fn bech32m_encode(hrp: &str, data: &[u8]) -> String {
+    let mut values = hrp_expand(hrp);
+    values.extend(data);
+    values.extend([0; 6]);
+    let chk = polymod(&values) ^ BECH32M_CONST;
+    let checksum = (0..6).map(|i| (chk >> (5 * (5 - i)) & 31) as u8);
+    let data: String = data
+        .iter()
+        .copied()
+        .chain(checksum)
+        .map(|value| CHARSET[value as usize] as char)
+        .collect();
+    format!("{hrp}1{data}")
+}
+
+fn bech32m_decode(s: &str) -> (String, Vec<u8>) {
+    assert!(
+        s.to_lowercase() == s || s.to_uppercase() == s,
+        "mixed case address"
+    );
+    let s = s.to_lowercase();
+    let (hrp, data) = s.rsplit_once('1').expect("no separator");
+    assert!(data.len() >= 6, "too short");
+    let data: Vec<u8> = data
+        .bytes()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|v| *v == c)
+                .expect("invalid character") as u8
+        })
+        .collect();
+    let mut values = hrp_expand(hrp);
+    values.extend(&data);
+    assert_eq!(polymod(&values), BECH32M_CONST, "invalid checksum");
+    (hrp.to_string(), data[..data.len() - 6].to_vec())
+}
+
+/// Silent payment address of BIP-352
+struct SilentPaymentAddress {
+    network: Network,
+    scan: PublicKey,
+    spend: PublicKey,
+}
+
+impl SilentPaymentAddress {
+    fn parse(address: &str) -> Self {
+        let (hrp, data) = bech32m_decode(address);
+        let network = match hrp.as_str() {
+            "sp" => Network::Bitcoin,
+            "tsp" => Network::Testnet,
+            _ => panic!("not a silent payment address"),
+        };
+        let (version, data) = data.split_first().expect("no version");
+        let data = convert_bits(data, 5, 8, false).expect("invalid padding");
+        // later versions stay readable by a version 0 sender
+        match version {
+            0 => assert_eq!(data.len(), 66, "invalid length"),
+            1..=30 => assert!(data.len() >= 66, "invalid length"),
+            _ => panic!("unsupported version"),
+        }
+        Self {
+            network,
+            scan: PublicKey::from_slice(&data[..33]).unwrap(),
+            spend: PublicKey::from_slice(&data[33..66]).unwrap(),
+        }
+    }
+
+    fn encode(&self) -> String {
+        let hrp = match self.network {
+            Network::Bitcoin => "sp",
+            Network::Testnet => "tsp",
+            _ => unreachable!(),
+        };
+        let mut data = vec![0];
+        let keys = [self.scan.serialize(), self.spend.serialize()].concat();
+        data.extend(convert_bits(&keys, 8, 5, true).unwrap());
+        bech32m_encode(hrp, &data)
+    }
+}
+
+fn scalar(bytes: [u8; 32]) -> Scalar {
+    Scalar::from_be_bytes(bytes).expect("hash above the curve order")
+}
+
+/// `hash_BIP0352/Inputs(outpoint_L || A)`
+fn input_hash(outpoints: &[OutPoint], sum: &PublicKey) -> Scalar {
+    let smallest = outpoints
+        .iter()
+        .map(consensus::serialize)
+        .min()
+        .expect("no input");
+    scalar(tagged_hash(
+        "BIP0352/Inputs",
+        &[&smallest, &sum.serialize()],
+    ))
+}
+
+/// `t_k = hash_BIP0352/SharedSecret(ecdh_shared_secret || k)`
+fn shared_secret_tweak(ecdh_shared_secret: &PublicKey, k: u32) -> Scalar {
+    scalar(tagged_hash(
+        "BIP0352/SharedSecret",
+        &[&ecdh_shared_secret.serialize(), &k.to_be_bytes()],
+    ))
+}
+
+/// `hash_BIP0352/Label(b_scan || m)`
+fn label_tweak(scan_key: &SecretKey, m: u32) -> Scalar {
+    scalar(tagged_hash(
+        "BIP0352/Label",
+        &[&scan_key.secret_bytes(), &m.to_be_bytes()],
+    ))
+}
+
+/// Input of a transaction, for the sender
+pub struct SilentPaymentInput {
+    pub txid: String,
+    pub vout: u32,
+    /// private key spending the input, none for inputs the receivers don't scan
+    pub priv_hex: Option<String>,
+    /// script pubkey in hex of the output spent. The key of a P2TR key path spend is negated to
+    /// have an even Y.
+    pub script_pubkey: String,
+}
+
+/// Whether a script pubkey is a segwit output of a version above 1. A transaction spending one
+/// can't pay silent payments, the inputs of later versions may not have keys to share.
+fn is_future_segwit(script_pubkey: &ScriptBuf) -> bool {
+    script_pubkey.is_witness_program()
+        && script_pubkey
+            .witness_version()
+            .is_some_and(|version| version.to_num() > 1)
+}
+
+/// ### Outputs paying silent payment addresses
+///
+/// inputs:
+/// every input of the transaction. P2TR key path, P2WPKH, P2SH-P2WPKH and P2PKH of compressed
+/// keys are the inputs a receiver scans and need their private key, the outpoints of the others
+/// still count.
+///
+/// recipients:
+/// silent payment addresses, repeated to pay one several times
+///
+/// returns the responding P2TR addresses, in order of `recipients`. The outputs can't be
+/// found by the receivers once the inputs change. None if an input spends a segwit output of a
+/// version above 1, or the input keys sum to zero, both of which the receivers skip.
+pub fn silent_payment_outputs(
+    inputs: Vec<SilentPaymentInput>,
+    recipients: Vec<String>,
+) -> Option<Vec<String>> {
+    let secp = Secp256k1::new();
+    let mut sum: Option<SecretKey> = None;
+    let mut outpoints = vec![];
+    for input in &inputs {
+        outpoints.push(OutPoint {
+            txid: Txid::from_str(&input.txid).unwrap(),
+            vout: input.vout,
+        });
+        let script_pubkey = ScriptBuf::from_hex(&input.script_pubkey).unwrap();
+        if is_future_segwit(&script_pubkey) {
+            return None;
+        }
+        let Some(priv_hex) = &input.priv_hex else {
+            continue;
+        };
+        let mut key = SecretKey::from_str(priv_hex).unwrap();
+        if script_pubkey.is_p2tr() && key.x_only_public_key(&secp).1 == Parity::Odd {
+            key = key.negate();
+        }
+        sum = Some(match sum {
+            None => key,
+            Some(sum) => sum.add_tweak(&Scalar::from(key)).ok()?,
+        });
+    }
+    let sum = sum.expect("no input to scan");
+    let input_hash = input_hash(&outpoints, &sum.public_key(&secp));
+    let tweaked_sum = sum.mul_tweak(&input_hash).unwrap();
+
+    let recipients: Vec<SilentPaymentAddress> = recipients
+        .iter()
+        .map(|recipient| SilentPaymentAddress::parse(recipient))
+        .collect();
+    // k counts the outputs of a scan key
+    let mut counts: Vec<(PublicKey, u32)> = vec![];
+    let outputs = recipients
+        .iter()
+        .map(|recipient| {
+            let k = match counts.iter_mut().find(|(scan, _)| *scan == recipient.scan) {
+                Some((_, count)) => {
+                    *count += 1;
+                    *count
+                }
+                None => {
+                    counts.push((recipient.scan, 0));
+                    0
+                }
+            };
+            let ecdh_shared_secret = recipient
+                .scan
+                .mul_tweak(&secp, &Scalar::from(tweaked_sum))
+                .unwrap();
+            let output_key = recipient
+                .spend
+                .add_exp_tweak(&secp, &shared_secret_tweak(&ecdh_shared_secret, k))
+                .unwrap();
+            Address::p2tr_tweaked(
+                TweakedPublicKey::dangerous_assume_tweaked(output_key.into()),
+                recipient.network,
+            )
+            .to_string()
+        })
+        .collect();
+    Some(outputs)
+}
+
+/// Output of a transaction paying a silent payment wallet
+pub struct SilentPaymentOutput {
+    pub vout: u32,
+    /// tweak of the spend key, with the tweak of the label if any
+    pub tweak: String,
+    /// label paid, none for the address without label
+    pub label: Option<u32>,
+    /// private key of the output, to sign its key path spend without taproot tweak
+    pub priv_hex: String,
+}
+
+/// Public key a receiver reads from an input, none for inputs that don't count
+fn input_pubkey(input: &TxIn, script_pubkey: &ScriptBuf) -> Option<PublicKey> {
+    let compressed = |bytes: &[u8]| {
+        if bytes.len() == 33 {
+            PublicKey::from_slice(bytes).ok()
+        } else {
+            None
+        }
+    };
+    if script_pubkey.is_p2tr() {
+        let mut witness: Vec<&[u8]> = input.witness.iter().collect();
+        if witness.len() > 1 && witness.last().is_some_and(|v| v.first() == Some(&0x50)) {
+            witness.pop();
+        }
+        // script path spends whose internal key is the NUMS point have no key to share
+        if witness.len() > 1 {
+            let control_block = witness.last().unwrap();
+            let nums = XOnlyPublicKey::from_str(NUMS_INTERNAL_KEY)
+                .unwrap()
+                .serialize();
+            if control_block.get(1..33) == Some(&nums[..]) {
+                return None;
+            }
+        }
+        let key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).ok()?;
+        Some(key.public_key(Parity::Even))
+    } else if script_pubkey.is_p2wpkh() {
+        compressed(input.witness.last()?)
+    } else if script_pubkey.is_p2sh() {
+        let redeem_script = input.script_sig.instructions().last()?.ok()?;
+        match redeem_script {
+            Instruction::PushBytes(bytes)
+                if ScriptBuf::from(bytes.as_bytes().to_vec()).is_p2wpkh() =>
+            {
+                compressed(input.witness.last()?)
+            }
+            _ => None,
+        }
+    } else if script_pubkey.is_p2pkh() {
+        // last 33 bytes of the script sig hashing to the key hash, pushed or not, as the
+        // reference implementation reads malleated script sigs
+        let hash = &script_pubkey.as_bytes()[3..23];
+        let bytes = input.script_sig.as_bytes();
+        let key = bytes
+            .windows(33)
+            .rev()
+            .find(|window| hash160::Hash::hash(window).as_byte_array() == hash)?;
+        compressed(key)
+    } else {
+        None
+    }
+}
+
+/// Silent payment wallet of BIP-352, with the scan and spend keys of an [`HDWallet`]
+pub struct SilentPaymentWallet {
+    secp: Secp256k1<All>,
+    network: Network,
+    scan_key: SecretKey,
+    spend_key: SecretKey,
+}
+
+impl SilentPaymentWallet {
+    pub fn new(wallet: &HDWallet) -> Self {
+        let secp = Secp256k1::new();
+        let root = Xpriv::from_str(&wallet.export_master_priv()).unwrap();
+        let coin_type = match root.network {
+            Network::Bitcoin => "0'",
+            Network::Testnet => "1'",
+            _ => unreachable!(),
+        };
+        let derive = |path: &str| {
+            let path = DerivationPath::from_str(&format!("m/352'/{coin_type}/0'/{path}")).unwrap();
+            root.derive_priv(&secp, &path).unwrap().private_key
+        };
+        Self::from_keys(root.network, derive("1'/0"), derive("0'/0"))
+    }
+
+    /// Wallet of raw scan and spend keys, as in the BIP-352 test vectors
+    pub(crate) fn from_keys(network: Network, scan_key: SecretKey, spend_key: SecretKey) -> Self {
+        Self {
+            secp: Secp256k1::new(),
+            network,
+            scan_key,
+            spend_key,
+        }
+    }
+
+    fn spend_pubkey(&self, label: Option<u32>) -> PublicKey {
+        let spend = self.spend_key.public_key(&self.secp);
+        match label {
+            None => spend,
+            Some(m) => spend
+                .add_exp_tweak(&self.secp, &label_tweak(&self.scan_key, m))
+                .unwrap(),
+        }
+    }
+
+    /// ### Reusable address to publish
+    ///
+    /// label:
+    /// number telling apart the payments to this address from the others, label 0 is kept for
+    /// change
+    pub fn address(&self, label: Option<u32>) -> String {
+        SilentPaymentAddress {
+            network: self.network,
+            scan: self.scan_key.public_key(&self.secp),
+            spend: self.spend_pubkey(label),
+        }
+        .encode()
+    }
+
+    /// ### Outputs of a transaction paying this wallet
+    ///
+    /// tx_hex:
+    /// signed transaction in hex
+    ///
+    /// prevout_script_pubkeys:
+    /// script pubkeys in hex of the outputs spent by tx inputs, in order
+    ///
+    /// labels:
+    /// labels of the addresses given out, to find their payments too
+    ///
+    /// returns nothing for transactions spending a segwit output of a version above 1, which
+    /// aren't silent payments
+    pub fn scan(
+        &self,
+        tx_hex: &str,
+        prevout_script_pubkeys: Vec<String>,
+        labels: Vec<u32>,
+    ) -> Vec<SilentPaymentOutput> {
+        let tx =
+            consensus::deserialize::<Transaction>(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap();
+        assert_eq!(tx.input.len(), prevout_script_pubkeys.len());
+        let prevout_script_pubkeys: Vec<ScriptBuf> = prevout_script_pubkeys
+            .iter()
+            .map(|script_pubkey| ScriptBuf::from_hex(script_pubkey).unwrap())
+            .collect();
+        if prevout_script_pubkeys.iter().any(is_future_segwit) {
+            return vec![];
+        }
+
+        let pubkeys: Vec<PublicKey> = tx
+            .input
+            .iter()
+            .zip(&prevout_script_pubkeys)
+            .filter_map(|(input, script_pubkey)| input_pubkey(input, script_pubkey))
+            .collect();
+        let outpoints: Vec<OutPoint> = tx.input.iter().map(|input| input.previous_output).collect();
+        let Ok(sum) = PublicKey::combine_keys(&pubkeys.iter().collect::<Vec<_>>()) else {
+            // no eligible input, or keys summing to the point at infinity
+            return vec![];
+        };
+        let input_hash = input_hash(&outpoints, &sum);
+        let ecdh_shared_secret = sum
+            .mul_tweak(&self.secp, &input_hash)
+            .unwrap()
+            .mul_tweak(&self.secp, &Scalar::from(self.scan_key))
+            .unwrap();
+
+        let mut outputs: Vec<(u32, XOnlyPublicKey)> = tx
+            .output
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.script_pubkey.is_p2tr())
+            .filter_map(|(vout, output)| {
+                let key = XOnlyPublicKey::from_slice(&output.script_pubkey.as_bytes()[2..]).ok()?;
+                Some((vout as u32, key))
+            })
+            .collect();
+        let spend = self.spend_key.public_key(&self.secp);
+        let mut found = vec![];
+        for k in 0.. {
+            let tweak = shared_secret_tweak(&ecdh_shared_secret, k);
+            let output_key = spend.add_exp_tweak(&self.secp, &tweak).unwrap();
+            let candidates = std::iter::once((None, output_key)).chain(labels.iter().map(|m| {
+                let label = PublicKey::from_secret_key(
+                    &self.secp,
+                    &SecretKey::from_slice(&label_tweak(&self.scan_key, *m).to_be_bytes()).unwrap(),
+                );
+                (Some(*m), output_key.combine(&label).unwrap())
+            }));
+            let matched = candidates.into_iter().find_map(|(label, key)| {
+                let position = outputs
+                    .iter()
+                    .position(|(_, output)| *output == key.x_only_public_key().0)?;
+                Some((label, position))
+            });
+            let Some((label, position)) = matched else {
+                break;
+            };
+            let (vout, _) = outputs.remove(position);
+
+            let mut priv_key = self.spend_key.add_tweak(&tweak).unwrap();
+            if let Some(m) = label {
+                priv_key = priv_key.add_tweak(&label_tweak(&self.scan_key, m)).unwrap();
+            }
+            let tweak = SecretKey::from_slice(&tweak.to_be_bytes()).unwrap();
+            let tweak = match label {
+                None => tweak,
+                Some(m) => tweak.add_tweak(&label_tweak(&self.scan_key, m)).unwrap(),
+            };
+            found.push(SilentPaymentOutput {
+                vout,
+                tweak: tweak.secret_bytes().as_hex().to_string(),
+                label,
+                priv_hex: priv_key.secret_bytes().as_hex().to_string(),
+            });
+        }
+        found
+    }
+}
@@ -0,0 +1,121 @@
+use crate::evm::address::address_from_pubkey;
+use crate::signer::{p2pkh_sign, p2tr_sign, p2wpkh_sign, Prevout};
+use bitcoin::hex::DisplayHex;
+use bitcoin::key::TapTweak;
+use bitcoin::secp256k1::{All, Keypair, Secp256k1, SecretKey};
+use bitcoin::{Address, PrivateKey, PublicKey, TapNodeHash};
+use std::str::FromStr;
+
+/// ### Encode a private key as WIF
+///
+/// compressed:
+/// whether the key's addresses use the compressed public key, always the case for keys derived
+/// by `HDWallet`
+pub fn encode_wif(priv_hex: &str, is_testnet: u8, compressed: bool) -> String {
+    let network = match is_testnet {
+        0 => bitcoin::Network::Bitcoin,
+        1 => bitcoin::Network::Testnet,
+        _ => unreachable!(),
+    };
+    let mut private_key = PrivateKey::new(SecretKey::from_str(priv_hex).unwrap(), network);
+    private_key.compressed = compressed;
+    private_key.to_wif()
+}
+
+/// ### Decode a WIF private key
+///
+/// returns the private key in hex as taken by the signers
+pub fn decode_wif(wif: &str) -> String {
+    PrivateKey::from_wif(wif)
+        .unwrap()
+        .inner
+        .secret_bytes()
+        .as_hex()
+        .to_string()
+}
+
+/// A wallet of a single imported private key, e.g. from a WIF or a paper wallet
+pub struct SingleKeyWallet {
+    secp: Secp256k1<All>,
+    private_key: PrivateKey,
+}
+
+impl SingleKeyWallet {
+    pub fn from_wif(wif: &str) -> Self {
+        Self {
+            secp: Secp256k1::new(),
+            private_key: PrivateKey::from_wif(wif).unwrap(),
+        }
+    }
+
+    /// A wallet of a raw private key, using the compressed public key
+    pub fn from_priv_hex(priv_hex: &str, is_testnet: u8) -> Self {
+        Self::from_wif(&encode_wif(priv_hex, is_testnet, true))
+    }
+
+    pub fn export_wif(&self) -> String {
+        self.private_key.to_wif()
+    }
+
+    pub fn priv_hex(&self) -> String {
+        self.private_key.inner.secret_bytes().as_hex().to_string()
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.private_key.compressed
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.private_key.public_key(&self.secp)
+    }
+
+    /// P2PKH address, of the uncompressed public key for uncompressed WIFs
+    pub fn p2pkh_address(&self) -> String {
+        Address::p2pkh(&self.public_key(), self.private_key.network).to_string()
+    }
+
+    /// P2WPKH address, panics for uncompressed keys which segwit does not allow
+    pub fn p2wpkh_address(&self) -> String {
+        Address::p2wpkh(&self.public_key(), self.private_key.network)
+            .expect("uncompressed key")
+            .to_string()
+    }
+
+    /// BIP86 style P2TR address of the key without script path
+    pub fn p2tr_address(&self) -> String {
+        let (x_only, _) = self.public_key().inner.x_only_public_key();
+        Address::p2tr(&self.secp, x_only, None, self.private_key.network).to_string()
+    }
+
+    pub fn evm_address(&self) -> String {
+        address_from_pubkey(&self.public_key().inner)
+    }
+
+    /// Tweaked private key for `schnorr_sign`, see `HDWallet::bip86_tweaked_priv_hex`
+    pub fn tweaked_priv_hex(&self, merkle_root_hex: Option<String>) -> String {
+        let merkle_root = merkle_root_hex.map(|v| TapNodeHash::from_str(&v).unwrap());
+        let keypair = Keypair::from_secret_key(&self.secp, &self.private_key.inner);
+        let tweaked = keypair.tap_tweak(&self.secp, merkle_root);
+        tweaked.to_inner().secret_bytes().as_hex().to_string()
+    }
+
+    /// Sign the inputs locked by [`SingleKeyWallet::p2pkh_address`], see [`p2pkh_sign`]
+    pub fn p2pkh_sign(&self, tx_hex: &str) -> String {
+        p2pkh_sign(&self.p2pkh_address(), &self.priv_hex(), tx_hex)
+    }
+
+    /// Sign the inputs locked by [`SingleKeyWallet::p2wpkh_address`], see [`p2wpkh_sign`]
+    pub fn p2wpkh_sign(&self, tx_hex: &str, tx_prevouts: Vec<Prevout>) -> String {
+        p2wpkh_sign(
+            &self.p2wpkh_address(),
+            &self.priv_hex(),
+            tx_hex,
+            tx_prevouts,
+        )
+    }
+
+    /// Sign the inputs locked by [`SingleKeyWallet::p2tr_address`], see [`p2tr_sign`]
+    pub fn p2tr_sign(&self, tx_hex: &str, tx_prevouts: Vec<Prevout>) -> String {
+        p2tr_sign(&self.p2tr_address(), &self.priv_hex(), tx_hex, tx_prevouts)
+    }
+}
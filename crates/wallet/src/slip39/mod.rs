@@ -0,0 +1,494 @@
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use bitcoin::hex::DisplayHex;
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+const RADIX_BITS: usize = 10;
+/// identifier, extendable flag, iteration exponent, group index, group threshold, group count,
+/// member index and member threshold
+const METADATA_WORDS: usize = 4;
+const CHECKSUM_WORDS: usize = 3;
+const MIN_SECRET_BYTES: usize = 16;
+const BASE_ITERATION_COUNT: u32 = 10000;
+const ROUND_COUNT: u8 = 4;
+const DIGEST_LENGTH: usize = 4;
+const SECRET_INDEX: u8 = 255;
+const DIGEST_INDEX: u8 = 254;
+const MAX_SHARE_COUNT: u8 = 16;
+
+fn wordlist() -> &'static Vec<&'static str> {
+    static WORDLIST: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDLIST.get_or_init(|| include_str!("wordlist.txt").split_whitespace().collect())
+}
+
+/// Whether the word is in the SLIP-39 wordlist
+pub fn is_slip39_word(word: &str) -> bool {
+    wordlist().binary_search(&word).is_ok()
+}
+
+/// Member threshold and count of a group of shares
+#[derive(Debug, Clone, Copy)]
+pub struct Slip39Group {
+    pub member_threshold: u8,
+    pub member_count: u8,
+}
+
+fn customization_string(extendable: bool) -> &'static [u8] {
+    match extendable {
+        true => b"shamir_extendable",
+        false => b"shamir",
+    }
+}
+
+fn rs1024_polymod(values: impl Iterator<Item = u32>) -> u32 {
+    const GEN: [u32; 10] = [
+        0xe0e040, 0x1c1c080, 0x3838100, 0x7070200, 0xe0e0009, 0x1c0c2412, 0x38086c24, 0x3090fc48,
+        0x21b1f890, 0x3f3f120,
+    ];
+    let mut chk = 1u32;
+    for v in values {
+        let b = chk >> 20;
+        chk = ((chk & 0xfffff) << 10) ^ v;
+        for (i, g) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn rs1024_checksum(extendable: bool, data: &[u16]) -> [u16; CHECKSUM_WORDS] {
+    let values = customization_string(extendable)
+        .iter()
+        .map(|b| *b as u32)
+        .chain(data.iter().map(|v| *v as u32))
+        .chain([0; CHECKSUM_WORDS]);
+    let polymod = rs1024_polymod(values) ^ 1;
+    [2, 1, 0].map(|i| ((polymod >> (RADIX_BITS * i)) & 1023) as u16)
+}
+
+fn rs1024_verify(extendable: bool, data: &[u16]) -> bool {
+    let values = customization_string(extendable)
+        .iter()
+        .map(|b| *b as u32)
+        .chain(data.iter().map(|v| *v as u32));
+    rs1024_polymod(values) == 1
+}
+
+/// GF(256) with the Rijndael polynomial x^8 + x^4 + x^3 + x + 1
+struct Gf256 {
+    exp: [u8; 255],
+    log: [u8; 256],
+}
+
+impl Gf256 {
+    fn get() -> &'static Self {
+        static TABLES: OnceLock<Gf256> = OnceLock::new();
+        TABLES.get_or_init(|| {
+            let mut exp = [0u8; 255];
+            let mut log = [0u8; 256];
+            let mut poly = 1u16;
+            for (i, e) in exp.iter_mut().enumerate() {
+                *e = poly as u8;
+                log[poly as usize] = i as u8;
+                // multiply by the generator x + 1
+                poly = (poly << 1) ^ poly;
+                if poly & 0x100 != 0 {
+                    poly ^= 0x11b;
+                }
+            }
+            Gf256 { exp, log }
+        })
+    }
+
+    /// Lagrange interpolation of the shares at `x`
+    fn interpolate(&self, shares: &[(u8, Vec<u8>)], x: u8) -> Vec<u8> {
+        if let Some((_, value)) = shares.iter().find(|(i, _)| *i == x) {
+            return value.clone();
+        }
+        let log_prod: u32 = shares
+            .iter()
+            .map(|(i, _)| self.log[(*i ^ x) as usize] as u32)
+            .sum();
+
+        let mut result = vec![0u8; shares[0].1.len()];
+        for (i, value) in shares {
+            let log_basis = (log_prod + 255 * (shares.len() as u32 + 1)
+                - self.log[(*i ^ x) as usize] as u32
+                - shares
+                    .iter()
+                    .map(|(j, _)| self.log[(*i ^ *j) as usize] as u32)
+                    .sum::<u32>())
+                % 255;
+            for (r, v) in result.iter_mut().zip(value) {
+                if *v != 0 {
+                    *r ^= self.exp[((self.log[*v as usize] as u32 + log_basis) % 255) as usize];
+                }
+            }
+        }
+        result
+    }
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn share_digest(random: &[u8], secret: &[u8]) -> Vec<u8> {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(random);
+    engine.input(secret);
+    hmac::Hmac::<sha256::Hash>::from_engine(engine)[..DIGEST_LENGTH].to_vec()
+}
+
+fn split_secret(threshold: u8, share_count: u8, secret: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    assert!(
+        threshold >= 1 && threshold <= share_count && share_count <= MAX_SHARE_COUNT,
+        "invalid threshold or share count"
+    );
+    if threshold == 1 {
+        return (0..share_count).map(|i| (i, secret.to_vec())).collect();
+    }
+
+    let random_share_count = threshold - 2;
+    let mut shares = (0..random_share_count)
+        .map(|i| (i, random_bytes(secret.len())))
+        .collect::<Vec<_>>();
+    let random_part = random_bytes(secret.len() - DIGEST_LENGTH);
+    let mut digest_share = share_digest(&random_part, secret);
+    digest_share.extend(&random_part);
+
+    let mut base_shares = shares.clone();
+    base_shares.push((DIGEST_INDEX, digest_share));
+    base_shares.push((SECRET_INDEX, secret.to_vec()));
+
+    let gf = Gf256::get();
+    for i in random_share_count..share_count {
+        shares.push((i, gf.interpolate(&base_shares, i)));
+    }
+    shares
+}
+
+/// none if the digest of the interpolated secret doesn't match, the shares are corrupt or mixed
+fn recover_secret(threshold: u8, shares: &[(u8, Vec<u8>)]) -> Option<Vec<u8>> {
+    if threshold == 1 {
+        return Some(shares[0].1.clone());
+    }
+    let gf = Gf256::get();
+    let secret = gf.interpolate(shares, SECRET_INDEX);
+    let digest_share = gf.interpolate(shares, DIGEST_INDEX);
+    (digest_share[..DIGEST_LENGTH] == share_digest(&digest_share[DIGEST_LENGTH..], &secret)[..])
+        .then_some(secret)
+}
+
+/// One round of the Feistel network, PBKDF2 of the round, passphrase and right half
+fn round_function(round: u8, passphrase: &[u8], e: u8, salt: &[u8], r: &[u8]) -> Vec<u8> {
+    let mut password = vec![round];
+    password.extend(passphrase);
+    let mut salt = salt.to_vec();
+    salt.extend(r);
+    let mut output = vec![0u8; r.len()];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(
+        &password,
+        &salt,
+        (BASE_ITERATION_COUNT << e) / ROUND_COUNT as u32,
+        &mut output,
+    );
+    output
+}
+
+fn feistel_salt(identifier: u16, extendable: bool) -> Vec<u8> {
+    match extendable {
+        true => Vec::new(),
+        false => {
+            let mut salt = b"shamir".to_vec();
+            salt.extend(identifier.to_be_bytes());
+            salt
+        }
+    }
+}
+
+fn feistel(
+    input: &[u8],
+    passphrase: &str,
+    identifier: u16,
+    extendable: bool,
+    e: u8,
+    rounds: impl Iterator<Item = u8>,
+) -> Vec<u8> {
+    let half = input.len() / 2;
+    let mut l = input[..half].to_vec();
+    let mut r = input[half..].to_vec();
+    let salt = feistel_salt(identifier, extendable);
+    for round in rounds {
+        let f = round_function(round, passphrase.as_bytes(), e, &salt, &r);
+        let new_r = l.iter().zip(&f).map(|(a, b)| a ^ b).collect();
+        l = std::mem::replace(&mut r, new_r);
+    }
+    r.extend(l);
+    r
+}
+
+struct Share {
+    identifier: u16,
+    extendable: bool,
+    iteration_exponent: u8,
+    group_index: u8,
+    group_threshold: u8,
+    group_count: u8,
+    member_index: u8,
+    member_threshold: u8,
+    value: Vec<u8>,
+}
+
+impl Share {
+    fn to_mnemonic(&self) -> String {
+        // pack the metadata and value as 10 bits words, the value padded at the front
+        let mut bits = Vec::<bool>::new();
+        let mut push = |value: u32, len: usize| {
+            bits.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        push(self.identifier as u32, 15);
+        push(self.extendable as u32, 1);
+        push(self.iteration_exponent as u32, 4);
+        push(self.group_index as u32, 4);
+        push(self.group_threshold as u32 - 1, 4);
+        push(self.group_count as u32 - 1, 4);
+        push(self.member_index as u32, 4);
+        push(self.member_threshold as u32 - 1, 4);
+        let value_bits = self.value.len() * 8;
+        let padding = (RADIX_BITS - value_bits % RADIX_BITS) % RADIX_BITS;
+        push(0, padding);
+        for b in &self.value {
+            push(*b as u32, 8);
+        }
+
+        let mut data = bits
+            .chunks(RADIX_BITS)
+            .map(|chunk| chunk.iter().fold(0u16, |acc, b| (acc << 1) | *b as u16))
+            .collect::<Vec<_>>();
+        data.extend(rs1024_checksum(self.extendable, &data));
+        data.iter()
+            .map(|i| wordlist()[*i as usize])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        let data = mnemonic
+            .split_whitespace()
+            .map(|word| {
+                wordlist()
+                    .binary_search(&word.to_lowercase().as_str())
+                    .ok()
+                    .map(|i| i as u16)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let min_words =
+            METADATA_WORDS + (MIN_SECRET_BYTES * 8).div_ceil(RADIX_BITS) + CHECKSUM_WORDS;
+        if data.len() < min_words {
+            return None;
+        }
+        let padding = (RADIX_BITS * (data.len() - METADATA_WORDS - CHECKSUM_WORDS)) % 16;
+        if padding > 8 {
+            return None;
+        }
+
+        let bits = data[..data.len() - CHECKSUM_WORDS]
+            .iter()
+            .flat_map(|w| (0..RADIX_BITS).rev().map(move |i| (w >> i) & 1 == 1))
+            .collect::<Vec<_>>();
+        let read = |start: usize, len: usize| {
+            bits[start..start + len]
+                .iter()
+                .fold(0u32, |acc, b| (acc << 1) | *b as u32)
+        };
+        let extendable = read(15, 1) == 1;
+        if !rs1024_verify(extendable, &data) {
+            return None;
+        }
+
+        let value_start = METADATA_WORDS * RADIX_BITS + padding;
+        if bits[METADATA_WORDS * RADIX_BITS..value_start]
+            .iter()
+            .any(|b| *b)
+        {
+            return None;
+        }
+        let value = bits[value_start..]
+            .chunks(8)
+            .map(|chunk| chunk.iter().fold(0u8, |acc, b| (acc << 1) | *b as u8))
+            .collect();
+
+        let share = Share {
+            identifier: read(0, 15) as u16,
+            extendable,
+            iteration_exponent: read(16, 4) as u8,
+            group_index: read(20, 4) as u8,
+            group_threshold: read(24, 4) as u8 + 1,
+            group_count: read(28, 4) as u8 + 1,
+            member_index: read(32, 4) as u8,
+            member_threshold: read(36, 4) as u8 + 1,
+            value,
+        };
+        (share.group_threshold <= share.group_count).then_some(share)
+    }
+}
+
+/// ### Whether a share mnemonic is valid
+///
+/// checks the words against the wordlist, the length, padding and RS1024 checksum
+pub fn slip39_validate_share(share: &str) -> bool {
+    Share::from_mnemonic(share).is_some()
+}
+
+/// ### Split a master secret into SLIP-39 shares
+///
+/// master_secret_hex:
+/// 16 to 32 bytes secret of an even length, e.g. the entropy of a BIP39 mnemonic
+///
+/// group_threshold, groups:
+/// number of groups needed to recover, and the member threshold and count of each group.
+/// A member threshold of 1 only allows a single member.
+///
+/// iteration_exponent:
+/// the passphrase encryption does `10000 << iteration_exponent` PBKDF2 iterations
+///
+/// returns the share mnemonics of each group
+pub fn slip39_generate(
+    master_secret_hex: &str,
+    passphrase: &str,
+    group_threshold: u8,
+    groups: Vec<Slip39Group>,
+    iteration_exponent: u8,
+) -> Vec<Vec<String>> {
+    let master_secret = Vec::<u8>::from_hex(master_secret_hex).unwrap();
+    assert!(
+        master_secret.len() >= MIN_SECRET_BYTES && master_secret.len() % 2 == 0,
+        "master secret must be at least 16 bytes of an even length"
+    );
+    assert!(
+        passphrase.chars().all(|c| (' '..='~').contains(&c)),
+        "passphrase must be printable ASCII"
+    );
+    assert!(iteration_exponent < 16, "invalid iteration exponent");
+    assert!(
+        group_threshold as usize <= groups.len(),
+        "group threshold exceeds the number of groups"
+    );
+    for group in &groups {
+        assert!(
+            group.member_threshold != 1 || group.member_count == 1,
+            "a member threshold of 1 only allows a single member"
+        );
+    }
+
+    let identifier = (thread_rng().next_u32() & 0x7fff) as u16;
+    let extendable = true;
+    let encrypted = feistel(
+        &master_secret,
+        passphrase,
+        identifier,
+        extendable,
+        iteration_exponent,
+        0..ROUND_COUNT,
+    );
+
+    let group_secrets = split_secret(group_threshold, groups.len() as u8, &encrypted);
+    groups
+        .iter()
+        .zip(group_secrets)
+        .map(|(group, (group_index, group_secret))| {
+            split_secret(group.member_threshold, group.member_count, &group_secret)
+                .into_iter()
+                .map(|(member_index, value)| {
+                    Share {
+                        identifier,
+                        extendable,
+                        iteration_exponent,
+                        group_index,
+                        group_threshold,
+                        group_count: groups.len() as u8,
+                        member_index,
+                        member_threshold: group.member_threshold,
+                        value,
+                    }
+                    .to_mnemonic()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// ### Recover the master secret from SLIP-39 shares
+///
+/// shares:
+/// share mnemonics of enough groups, in any order
+///
+/// returns the master secret in hex, none if a share is invalid, the shares don't belong to the
+/// same secret or are not enough. A wrong passphrase gives a different secret, it can't be
+/// detected.
+pub fn slip39_recover(shares: Vec<String>, passphrase: &str) -> Option<String> {
+    let shares = shares
+        .iter()
+        .map(|s| Share::from_mnemonic(s))
+        .collect::<Option<Vec<_>>>()?;
+    let first = shares.first()?;
+    let same_secret = shares.iter().all(|share| {
+        share.identifier == first.identifier
+            && share.extendable == first.extendable
+            && share.iteration_exponent == first.iteration_exponent
+            && share.group_threshold == first.group_threshold
+            && share.group_count == first.group_count
+            && share.value.len() == first.value.len()
+    });
+    if !same_secret {
+        return None;
+    }
+
+    let mut groups = BTreeMap::<u8, (u8, BTreeMap<u8, Vec<u8>>)>::new();
+    for share in &shares {
+        let (member_threshold, members) = groups
+            .entry(share.group_index)
+            .or_insert((share.member_threshold, BTreeMap::new()));
+        // shares of a group with different thresholds
+        if *member_threshold != share.member_threshold {
+            return None;
+        }
+        members.insert(share.member_index, share.value.clone());
+    }
+
+    let group_secrets = groups
+        .into_iter()
+        .filter(|(_, (threshold, members))| members.len() >= *threshold as usize)
+        .take(first.group_threshold as usize)
+        .map(|(group_index, (threshold, members))| {
+            let members = members
+                .into_iter()
+                .take(threshold as usize)
+                .collect::<Vec<_>>();
+            Some((group_index, recover_secret(threshold, &members)?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if group_secrets.len() < first.group_threshold as usize {
+        return None;
+    }
+
+    let encrypted = recover_secret(first.group_threshold, &group_secrets)?;
+    Some(
+        feistel(
+            &encrypted,
+            passphrase,
+            first.identifier,
+            first.extendable,
+            first.iteration_exponent,
+            (0..ROUND_COUNT).rev(),
+        )
+        .as_hex()
+        .to_string(),
+    )
+}
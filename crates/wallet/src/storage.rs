@@ -0,0 +1,163 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hex::DisplayHex;
+use bitcoin::secp256k1::rand::{thread_rng, RngCore};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::XChaCha20Poly1305;
+use serde_json::{json, Value};
+
+/// Current version of the encrypted wallet format, bumped whenever the layout changes.
+/// Older versions stay readable by [`open`].
+pub const WALLET_FORMAT_VERSION: u64 = 2;
+
+/// Argon2id parameters since version 1, the OWASP recommended m = 19 MiB, t = 2, p = 1
+const ARGON2_M_COST: u32 = 19456;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+
+/// Upper bounds on the Argon2id parameters read from an envelope, so a crafted wallet can't
+/// make `open` allocate or spin without limit
+const MAX_ARGON2_M_COST: u32 = 1 << 20;
+const MAX_ARGON2_T_COST: u32 = 16;
+const MAX_ARGON2_P_COST: u32 = 16;
+
+/// `kdfparams` of an envelope
+struct KdfParams {
+    m: u32,
+    t: u32,
+    p: u32,
+    salt: Vec<u8>,
+}
+
+impl KdfParams {
+    /// `None` if a parameter is missing or out of bounds
+    fn parse(params: &Value) -> Option<Self> {
+        let param = |name: &str| u32::try_from(params[name].as_u64()?).ok();
+        let params = Self {
+            m: param("m")?,
+            t: param("t")?,
+            p: param("p")?,
+            salt: hex_field(params, "salt")?,
+        };
+        let in_bounds = params.m <= MAX_ARGON2_M_COST
+            && params.t <= MAX_ARGON2_T_COST
+            && params.p <= MAX_ARGON2_P_COST;
+        in_bounds.then_some(params)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "m": self.m,
+            "t": self.t,
+            "p": self.p,
+            "salt": self.salt.as_hex().to_string(),
+        })
+    }
+}
+
+fn hex_field(value: &Value, name: &str) -> Option<Vec<u8>> {
+    Vec::<u8>::from_hex(value[name].as_str()?).ok()
+}
+
+/// `None` if the parameters are rejected by Argon2, e.g. a too small memory cost or salt
+fn argon2id(password: &str, params: &KdfParams) -> Option<[u8; 32]> {
+    let argon2_params = Params::new(params.m, params.t, params.p, Some(32)).ok()?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params)
+        .hash_password_into(password.as_bytes(), &params.salt, &mut key)
+        .ok()?;
+    Some(key)
+}
+
+/// Additional data binding the ciphertext to the format version and, since version 2, to the
+/// kdf parameters
+fn aad(version: u64, params: &KdfParams) -> Vec<u8> {
+    match version {
+        1 => format!("hdwallet-v{version}").into_bytes(),
+        _ => format!(
+            "hdwallet-v{version}:argon2id:m={}:t={}:p={}:salt={}:xchacha20-poly1305",
+            params.m,
+            params.t,
+            params.p,
+            params.salt.as_hex(),
+        )
+        .into_bytes(),
+    }
+}
+
+/// Encrypt a payload with a key derived from the password by Argon2id, using
+/// XChaCha20-Poly1305, into the versioned JSON envelope
+pub(crate) fn seal(plaintext: &[u8], password: &str) -> String {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 24];
+    thread_rng().fill_bytes(&mut salt);
+    thread_rng().fill_bytes(&mut nonce);
+
+    let kdf_params = KdfParams {
+        m: ARGON2_M_COST,
+        t: ARGON2_T_COST,
+        p: ARGON2_P_COST,
+        salt: salt.to_vec(),
+    };
+    let key = argon2id(password, &kdf_params).unwrap();
+    let ciphertext = XChaCha20Poly1305::new(&key.into())
+        .encrypt(
+            &nonce.into(),
+            Payload {
+                msg: plaintext,
+                aad: &aad(WALLET_FORMAT_VERSION, &kdf_params),
+            },
+        )
+        .unwrap();
+
+    json!({
+        "version": WALLET_FORMAT_VERSION,
+        "kdf": "argon2id",
+        "kdfparams": kdf_params.to_json(),
+        "cipher": "xchacha20-poly1305",
+        "nonce": nonce.as_hex().to_string(),
+        "ciphertext": ciphertext.as_hex().to_string(),
+    })
+    .to_string()
+}
+
+/// Decrypt the payload of an envelope written by [`seal`], `None` if the password is wrong or
+/// the envelope is malformed, of an unknown version or uses unsupported parameters
+pub(crate) fn open(encrypted: &str, password: &str) -> Option<Vec<u8>> {
+    let envelope: Value = serde_json::from_str(encrypted).ok()?;
+    let version = envelope["version"].as_u64()?;
+    let supported = matches!(version, 1 | 2)
+        && envelope["kdf"] == "argon2id"
+        && envelope["cipher"] == "xchacha20-poly1305";
+    if !supported {
+        return None;
+    }
+
+    let params = KdfParams::parse(&envelope["kdfparams"])?;
+    let nonce: [u8; 24] = hex_field(&envelope, "nonce")?.try_into().ok()?;
+    let ciphertext = hex_field(&envelope, "ciphertext")?;
+    let key = argon2id(password, &params)?;
+    XChaCha20Poly1305::new(&key.into())
+        .decrypt(
+            &nonce.into(),
+            Payload {
+                msg: &ciphertext,
+                aad: &aad(version, &params),
+            },
+        )
+        .ok()
+}
+
+/// ### Read the metadata of an encrypted wallet
+///
+/// encrypted:
+/// output of `HDWallet::encrypt`
+///
+/// returns the metadata stored with the wallet, `None` if the password is wrong or the wallet
+/// is malformed.
+/// Also useful to check the password before `HDWallet::from_encrypted`.
+pub fn decrypt_wallet_metadata(encrypted: &str, password: &str) -> Option<String> {
+    let payload = open(encrypted, password)?;
+    let payload: Value = serde_json::from_slice(&payload).unwrap();
+    Some(payload["metadata"].as_str().unwrap_or_default().to_string())
+}
@@ -1,5 +1,50 @@
+use crate::bip38::{
+    bip38_decrypt, bip38_encrypt, bip38_generate_from_intermediate, bip38_intermediate_code,
+    bip38_verify_confirmation,
+};
+use crate::bip85::bip85_entropy;
+use crate::evm::abi::{
+    decode_log, decode_output, encode_call, erc20_transfer, find_abi_item, Event, Function,
+};
+use crate::evm::rlp::Item;
+use crate::evm::typed_data::TypedData;
+use crate::evm::{
+    create2_address, create_address, decode_transaction, decrypt_keystore, encrypt_keystore,
+    ens_addr_call, ens_labelhash, ens_namehash, ens_normalize_ascii, hash_personal_message,
+    is_checksum_address, is_valid_address, pubkey_to_address, recover_authority,
+    recover_personal_signer, recover_typed_data_signer, sign_access_list_transaction,
+    sign_eip1559_transaction, sign_legacy_transaction, sign_set_code_transaction, sign_typed_data,
+    to_checksum_address, user_operation_v06_hash, user_operation_v07_hash,
+    verify_personal_signature, AccessListItem, AccessListTransaction, Authorization,
+    Eip1559Transaction, KeystoreKdf, LegacyTransaction, SetCodeTransaction, SignedAuthorization,
+    UserOperationV06, UserOperationV07,
+};
+use crate::fee_bump::{cpfp_child, rbf_bump_fee};
+use crate::frost::{
+    frost_aggregate, frost_dkg_part1, frost_dkg_part2, frost_dkg_part3, frost_signing_package,
+    frost_trusted_dealer, FrostSigner,
+};
 use crate::hd_wallet::HDWallet;
-use crate::signer::{ecdsa_sign, p2pkh_sign, p2tr_sign, schnorr_sign, Prevout};
+use crate::miniscript::{miniscript_compile, MiniscriptType, MiniscriptWallet};
+use crate::multisig_wallet::{
+    combine_psbts, descriptor_checksum, MultisigPrevout, MultisigScriptType, MultisigWallet,
+};
+use crate::musig2::{musig2_aggregate_xpub, musig2_key_agg, musig2_nonce_agg, Musig2Wallet};
+use crate::signer::{
+    ecdsa_sign, ecdsa_sign_recoverable, p2pkh_sign, p2tr_sign, p2wpkh_sign, recover_pubkey,
+    schnorr_sign, Prevout,
+};
+use crate::silent_payments::{silent_payment_outputs, SilentPaymentInput, SilentPaymentWallet};
+use crate::single_key_wallet::{decode_wif, encode_wif, SingleKeyWallet};
+use crate::slip39::{
+    is_slip39_word, slip39_generate, slip39_recover, slip39_validate_share, Slip39Group,
+};
+use crate::storage::decrypt_wallet_metadata;
+use crate::timelock::{
+    inheritance_address, inheritance_script, inheritance_spend, timelock_address, timelock_script,
+    timelock_spend, TimelockScriptType, TimelockType,
+};
+use bitcoin::hashes::hex::FromHex;
 use bitcoin::hashes::{sha256, Hash};
 use bitcoin::hex::DisplayHex;
 use bitcoin::key::TapTweak;
@@ -31,6 +76,29 @@ fn test_ecdsa_sign() {
         .is_ok());
 }
 
+#[test]
+fn test_ecdsa_sign_recoverable() {
+    let priv_hex = "6cd9dc64451b6652203df996e255859aa9eefac8e99b9143510fafe5cae27822";
+    let secp = Secp256k1::new();
+    let pubkey = SecretKey::from_str(priv_hex).unwrap().public_key(&secp);
+    // half of the curve order
+    let half_order = "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0";
+
+    for message in ["hello world", "thing", "recoverable"] {
+        let hash_hex = sha256::Hash::hash(message.as_bytes())
+            .to_byte_array()
+            .as_hex()
+            .to_string();
+        let sig = ecdsa_sign_recoverable(priv_hex, &hash_hex);
+        assert_eq!(sig.len(), 130);
+        assert!(&sig[64..128] <= half_order);
+        assert_eq!(recover_pubkey(&hash_hex, &sig), pubkey.to_string());
+
+        let der = ecdsa::Signature::from_str(&ecdsa_sign(priv_hex, &hash_hex)).unwrap();
+        assert_eq!(der.serialize_compact().as_hex().to_string(), sig[..128]);
+    }
+}
+
 #[test]
 fn test_schnorr_sign() {
     let message = "hello world";
@@ -107,6 +175,3670 @@ fn test_evm() {
     );
 }
 
+#[test]
+fn test_rlp_encode() {
+    assert_eq!(
+        Item::Bytes(b"dog".to_vec()).encode(),
+        [0x83, b'd', b'o', b'g']
+    );
+    assert_eq!(Item::uint(0).encode(), [0x80]);
+    assert_eq!(Item::uint(1024).encode(), [0x82, 0x04, 0x00]);
+    assert_eq!(Item::List(vec![]).encode(), [0xc0]);
+    let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec();
+    assert_eq!(Item::Bytes(lorem).encode()[..2], [0xb8, 0x38]);
+    assert_eq!(
+        Item::List(vec![
+            Item::List(vec![]),
+            Item::List(vec![Item::List(vec![])]),
+            Item::List(vec![
+                Item::List(vec![]),
+                Item::List(vec![Item::List(vec![])])
+            ]),
+        ])
+        .encode(),
+        [0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0]
+    );
+}
+
+#[test]
+fn test_evm_sign_legacy_transaction() {
+    // example from EIP-155
+    let signed = sign_legacy_transaction(
+        "4646464646464646464646464646464646464646464646464646464646464646",
+        LegacyTransaction {
+            nonce: 9,
+            gas_price: "20000000000".to_string(),
+            gas_limit: 21000,
+            to: Some("0x3535353535353535353535353535353535353535".to_string()),
+            value: "1000000000000000000".to_string(),
+            data: String::new(),
+            chain_id: 1,
+        },
+    );
+    assert_eq!(signed.raw_tx_hex, "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+    assert_eq!(
+        signed.tx_hash,
+        "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788"
+    );
+}
+
+#[test]
+fn test_evm_decode_legacy_transaction() {
+    let raw_tx = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    let decoded = decode_transaction(raw_tx).unwrap();
+    assert_eq!(decoded.tx_type, 0);
+    assert_eq!(decoded.chain_id, 1);
+    assert_eq!(decoded.nonce, 9);
+    assert_eq!(decoded.gas_price.as_deref(), Some("20000000000"));
+    assert_eq!(decoded.gas_limit, 21000);
+    assert_eq!(
+        decoded.to.as_deref(),
+        Some("0x3535353535353535353535353535353535353535")
+    );
+    assert_eq!(decoded.value, "1000000000000000000");
+    assert_eq!(decoded.data, "0x");
+    assert_eq!(decoded.y_parity, 0);
+    assert_eq!(decoded.from, "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+
+    // malformed input is an error
+    for i in (2..raw_tx.len()).step_by(2) {
+        assert!(decode_transaction(&raw_tx[..i]).is_none());
+    }
+    for raw_tx in [
+        "",
+        "0x",
+        "0xzz",
+        "0x03",
+        "0x02",
+        "0x80",
+        "0xc0",
+        "0xb9",
+        "0xbf0000000000000000",
+        "0xff0000000000000000",
+        "0xf9ffff",
+        "0xc3c2c1",
+        "0x02c0",
+    ] {
+        assert!(decode_transaction(raw_tx).is_none(), "{raw_tx}");
+    }
+}
+
+#[test]
+fn test_evm_sign_typed_transaction() {
+    let wallet = HDWallet::new(
+        0,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let access_list = vec![AccessListItem {
+        address: "0xde0b295669a9fd93d5f28d9ec85e40f4cb697bae".to_string(),
+        storage_keys: vec![
+            "0x0000000000000000000000000000000000000000000000000000000000000003".to_string(),
+            "0x0000000000000000000000000000000000000000000000000000000000000007".to_string(),
+        ],
+    }];
+
+    let signed = sign_eip1559_transaction(
+        &wallet.evm_priv_hex(),
+        Eip1559Transaction {
+            chain_id: 1,
+            nonce: 42,
+            max_priority_fee_per_gas: "2000000000".to_string(),
+            max_fee_per_gas: "0x174876e800".to_string(),
+            gas_limit: 60000,
+            to: Some("0x3535353535353535353535353535353535353535".to_string()),
+            value: "0".to_string(),
+            data: "0xa9059cbb".to_string(),
+            access_list: access_list.clone(),
+        },
+    );
+    assert!(signed.raw_tx_hex.starts_with("0x02"));
+    let decoded = decode_transaction(&signed.raw_tx_hex).unwrap();
+    assert_eq!(decoded.tx_type, 2);
+    assert_eq!(decoded.tx_hash, signed.tx_hash);
+    assert_eq!(decoded.from, wallet.evm_address());
+    assert_eq!(decoded.nonce, 42);
+    assert_eq!(decoded.gas_price, None);
+    assert_eq!(
+        decoded.max_priority_fee_per_gas.as_deref(),
+        Some("2000000000")
+    );
+    assert_eq!(decoded.max_fee_per_gas.as_deref(), Some("100000000000"));
+    assert_eq!(decoded.data, "0xa9059cbb");
+    assert_eq!(
+        decoded.access_list[0].storage_keys,
+        access_list[0].storage_keys
+    );
+    assert_eq!(
+        decoded.access_list[0].address,
+        "0xde0B295669a9FD93d5F28D9Ec85E40f4cb697BAe"
+    );
+
+    let signed = sign_access_list_transaction(
+        &wallet.evm_priv_hex(),
+        AccessListTransaction {
+            chain_id: 5,
+            nonce: 0,
+            gas_price: "1000000000".to_string(),
+            gas_limit: 100000,
+            to: None,
+            value: "1".to_string(),
+            data: "0x6000".to_string(),
+            access_list,
+        },
+    );
+    assert!(signed.raw_tx_hex.starts_with("0x01"));
+    let decoded = decode_transaction(&signed.raw_tx_hex).unwrap();
+    assert_eq!(decoded.tx_type, 1);
+    assert_eq!(decoded.chain_id, 5);
+    assert_eq!(decoded.to, None);
+    assert_eq!(decoded.gas_price.as_deref(), Some("1000000000"));
+    assert_eq!(decoded.from, wallet.evm_address());
+
+    // mainnet transaction 0xce4dc6d7a7549a98ee3b071b67e970879ff51b5b95d1c340bacd80fa1e1aab31
+    let decoded = decode_transaction("0x02f86f0102843b9aca0085029e7822d68298f094d9e1459a7a482635700cbc20bbaf52d495ab9c9680841b55ba3ac080a0c199674fcb29f353693dd779c017823b954b3c69dffa3cd6b2a6ff7888798039a028ca912de909e7e6cdef9cdcaf24c54dd8c1032946dfa1d85c206b32a9064fe8").unwrap();
+    assert_eq!(
+        decoded.tx_hash,
+        "0xce4dc6d7a7549a98ee3b071b67e970879ff51b5b95d1c340bacd80fa1e1aab31"
+    );
+    assert_eq!(decoded.tx_type, 2);
+    assert_eq!(decoded.chain_id, 1);
+    assert_eq!(decoded.nonce, 2);
+    assert_eq!(
+        decoded.max_priority_fee_per_gas.as_deref(),
+        Some("1000000000")
+    );
+    assert_eq!(decoded.max_fee_per_gas.as_deref(), Some("11248607958"));
+    assert_eq!(decoded.gas_limit, 39152);
+    assert_eq!(
+        decoded.to.as_deref(),
+        Some("0xD9e1459A7A482635700cBc20BBAF52D495Ab9C96")
+    );
+    assert_eq!(decoded.value, "0");
+    assert_eq!(decoded.data, "0x1b55ba3a");
+    assert!(decoded.access_list.is_empty());
+    assert_eq!(decoded.y_parity, 0);
+    assert_eq!(decoded.from, "0x001e2b7dE757bA469a57bF6b23d982458a07eFcE");
+
+    // y_parity above 1, and the high s twin of the signature
+    assert!(decode_transaction("0x02f86f0102843b9aca0085029e7822d68298f094d9e1459a7a482635700cbc20bbaf52d495ab9c9680841b55ba3ac002a0c199674fcb29f353693dd779c017823b954b3c69dffa3cd6b2a6ff7888798039a028ca912de909e7e6cdef9cdcaf24c54dd8c1032946dfa1d85c206b32a9064fe8").is_none());
+    assert!(decode_transaction("0x02f86f0102843b9aca0085029e7822d68298f094d9e1459a7a482635700cbc20bbaf52d495ab9c9680841b55ba3ac001a0c199674fcb29f353693dd779c017823b954b3c69dffa3cd6b2a6ff7888798039a0d7356ed216f618193210632350db3ab0e1edd9bd6868fe6363b1f35a272ff159").is_none());
+}
+
+#[test]
+fn test_evm_personal_sign() {
+    assert_eq!(
+        hash_personal_message("Hello World").as_hex().to_string(),
+        "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+    );
+    assert_eq!(
+        hash_personal_message("0x48656c6c6f20576f726c64"),
+        hash_personal_message("Hello World")
+    );
+
+    let wallet = HDWallet::new(
+        0,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let signature = wallet.evm_personal_sign("Hello World");
+    assert_eq!(signature.len(), 2 + 65 * 2);
+    assert!(signature.ends_with("1b") || signature.ends_with("1c"));
+    assert_eq!(
+        recover_personal_signer("Hello World", &signature).unwrap(),
+        wallet.evm_address()
+    );
+    assert!(verify_personal_signature(
+        &wallet.evm_address().to_lowercase(),
+        "Hello World",
+        &signature
+    ));
+    assert!(!verify_personal_signature(
+        &wallet.evm_address(),
+        "Hello World!",
+        &signature
+    ));
+
+    // malformed signatures don't verify
+    for signature in [
+        "",
+        "0xzz",
+        &signature[..signature.len() - 2],
+        &format!("{}1d", &signature[..signature.len() - 2]),
+        &format!("0x{}1b", "00".repeat(64)),
+    ] {
+        assert!(recover_personal_signer("Hello World", signature).is_none());
+        assert!(!verify_personal_signature(
+            &wallet.evm_address(),
+            "Hello World",
+            signature
+        ));
+    }
+
+    // text starting with 0x which isn't hex is signed as UTF-8
+    assert_eq!(
+        hash_personal_message("0x is cool").as_hex().to_string(),
+        crate::evm::keccak256(b"\x19Ethereum Signed Message:\n100x is cool")
+            .as_hex()
+            .to_string()
+    );
+    let signature = wallet.evm_personal_sign("0x is cool");
+    assert!(verify_personal_signature(
+        &wallet.evm_address(),
+        "0x is cool",
+        &signature
+    ));
+}
+
+#[test]
+fn test_evm_sign_typed_data() {
+    // example from EIP-712
+    let typed_data = r#"{
+        "types": {
+            "EIP712Domain": [
+                {"name": "name", "type": "string"},
+                {"name": "version", "type": "string"},
+                {"name": "chainId", "type": "uint256"},
+                {"name": "verifyingContract", "type": "address"}
+            ],
+            "Person": [
+                {"name": "name", "type": "string"},
+                {"name": "wallet", "type": "address"}
+            ],
+            "Mail": [
+                {"name": "from", "type": "Person"},
+                {"name": "to", "type": "Person"},
+                {"name": "contents", "type": "string"}
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+            "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    let data = TypedData::from_json(typed_data);
+    assert_eq!(
+        data.encode_type("Mail"),
+        "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+    );
+    assert_eq!(
+        data.domain_separator().as_hex().to_string(),
+        "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+    );
+    assert_eq!(
+        data.signing_hash().as_hex().to_string(),
+        "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+    );
+
+    // private key is keccak256("cow")
+    let signature = sign_typed_data(
+        "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4",
+        typed_data,
+    );
+    assert_eq!(signature, "0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c");
+    assert_eq!(
+        recover_typed_data_signer(typed_data, &signature).unwrap(),
+        "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+    );
+    assert!(recover_typed_data_signer(typed_data, &format!("0x{}1b", "00".repeat(64))).is_none());
+}
+
+#[test]
+fn test_evm_abi_encode() {
+    // examples from the solidity abi specification
+    assert_eq!(
+        encode_call("baz(uint32 x, bool y)", "[69, true]").unwrap(),
+        "0xcdcd77c000000000000000000000000000000000000000000000000000000000000000450000000000000000000000000000000000000000000000000000000000000001"
+    );
+    let function = "f(uint256,uint32[],bytes10,bytes)";
+    let calldata = encode_call(
+        function,
+        r#"["0x123", ["0x456", "0x789"], "0x31323334353637383930", "0x48656c6c6f2c20776f726c6421"]"#,
+    )
+    .unwrap();
+    assert_eq!(
+        calldata,
+        concat!(
+            "0x8be65246",
+            "0000000000000000000000000000000000000000000000000000000000000123",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "3132333435363738393000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000000000000000000000000000000000e0",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000456",
+            "0000000000000000000000000000000000000000000000000000000000000789",
+            "000000000000000000000000000000000000000000000000000000000000000d",
+            "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+        )
+    );
+    assert_eq!(
+        decode_output(
+            "f() returns (uint256 a, uint32[] b, bytes10 c, bytes d)",
+            &calldata[10..]
+        )
+        .unwrap(),
+        r#"["291",["1110","1929"],"0x31323334353637383930","0x48656c6c6f2c20776f726c6421"]"#
+    );
+    // truncated data
+    for words in 0..9 {
+        assert!(decode_output(
+            "f() returns (uint256 a, uint32[] b, bytes10 c, bytes d)",
+            &calldata[10..10 + words * 64]
+        )
+        .is_none());
+    }
+
+    assert_eq!(
+        erc20_transfer("0x3535353535353535353535353535353535353535", "1000000").unwrap(),
+        "0xa9059cbb000000000000000000000000353535353535353535353535353535353535353500000000000000000000000000000000000000000000000000000000000f4240"
+    );
+
+    // values out of range for their type
+    assert!(encode_call("baz(uint32 x, bool y)", "[4294967296, true]").is_none());
+    assert!(encode_call("baz(uint32 x, bool y)", "[-1, true]").is_none());
+    assert!(encode_call("baz(uint32 x, bool y)", "[69]").is_none());
+    assert!(encode_call("baz(uint32 x, bool y)", "[69, 1]").is_none());
+    for amount in ["-5", "1.5", "", "0x1g"] {
+        assert!(
+            erc20_transfer("0x3535353535353535353535353535353535353535", amount).is_none(),
+            "{amount}"
+        );
+    }
+    assert!(erc20_transfer("0x35", "1").is_none());
+    assert_eq!(
+        encode_call("f(int8)", "[-128]").unwrap(),
+        format!(
+            "{}{}80",
+            &encode_call("f(int8)", "[0]").unwrap()[..10],
+            "ff".repeat(31)
+        )
+    );
+    assert!(encode_call("f(int8)", "[128]").is_none());
+    assert!(encode_call("f(int8)", "[-129]").is_none());
+    assert!(encode_call("f(int256)", &format!("[\"0x8{}\"]", "0".repeat(63))).is_none());
+    assert!(encode_call("f(int256)", &format!("[\"-0x8{}\"]", "0".repeat(63))).is_some());
+
+    // malformed return data from the network
+    let word = |value: &str| format!("{value:0>64}");
+    for (function, data) in [
+        // invalid UTF-8
+        (
+            "f() returns (string)",
+            [word("20"), word("1"), format!("ff{}", "0".repeat(62))].concat(),
+        ),
+        // array length beyond the data
+        (
+            "f() returns (uint256[])",
+            [word("20"), word("ffffffff")].concat(),
+        ),
+        (
+            "f() returns (bytes)",
+            [word("20"), word("ffffffffffffffff")].concat(),
+        ),
+        // offset beyond the data
+        ("f() returns (bytes)", word("ffff")),
+        // dirty high bits
+        ("f() returns (uint32)", word("100000000")),
+        ("f() returns (int8)", word("80")),
+        ("f() returns (bool)", word("2")),
+        (
+            "f() returns (address)",
+            word(&format!("1{}", "0".repeat(40))),
+        ),
+        ("f() returns (bytes1)", word("1")),
+        ("f() returns (uint256)", "0x1".to_string()),
+    ] {
+        assert!(
+            decode_output(function, &data).is_none(),
+            "{function} {data}"
+        );
+    }
+    assert_eq!(
+        decode_output("f() returns (int8)", &"ff".repeat(32)).unwrap(),
+        r#"["-1"]"#
+    );
+
+    // names starting with the keyword
+    for function in [
+        "functionCall(address,bytes)",
+        "function functionCall(address target, bytes data)",
+    ] {
+        assert_eq!(
+            Function::parse(function).signature(),
+            "functionCall(address,bytes)"
+        );
+    }
+    assert_eq!(
+        Function::parse("functionCall(address,bytes)").selector(),
+        Function::parse("function functionCall(address,bytes)").selector()
+    );
+    assert_eq!(
+        Event::parse("eventually(uint256)").signature(),
+        "eventually(uint256)"
+    );
+    assert_eq!(
+        Event::parse("event eventually(uint256 indexed id)").signature(),
+        "eventually(uint256)"
+    );
+
+    let abi = r#"[
+        {"type": "function", "name": "swap", "inputs": [
+            {"name": "order", "type": "tuple", "components": [
+                {"name": "maker", "type": "address"},
+                {"name": "amounts", "type": "int128[]"}
+            ]},
+            {"name": "memo", "type": "string"}
+        ], "outputs": [{"name": "", "type": "bool"}]}
+    ]"#;
+    let item = find_abi_item(abi, "swap");
+    let function = Function::parse(&item);
+    assert_eq!(function.signature(), "swap((address,int128[]),string)");
+    let args = r#"[["0x3535353535353535353535353535353535353535", [-1, "7"]], "gm"]"#;
+    let calldata = encode_call(&item, args).unwrap();
+    let values = crate::evm::abi::decode(
+        &function
+            .inputs
+            .iter()
+            .map(|p| p.kind.clone())
+            .collect::<Vec<_>>(),
+        &crate::evm::decode_hex(&calldata)[4..],
+    )
+    .unwrap();
+    assert_eq!(
+        serde_json::Value::Array(values).to_string(),
+        r#"[["0x3535353535353535353535353535353535353535",["-1","7"]],"gm"]"#
+    );
+}
+
+#[test]
+fn test_evm_abi_decode_log() {
+    let decoded = decode_log(
+        "event Transfer(address indexed from, address indexed to, uint256 value)",
+        vec![
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string(),
+            "0x000000000000000000000000cd2a3d9f938e13cd947ec05abc7fe734df8dd826".to_string(),
+            "0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".to_string(),
+        ],
+        "0x00000000000000000000000000000000000000000000000000000000000f4240",
+    )
+    .unwrap();
+    assert_eq!(
+        decoded,
+        r#"{"from":"0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826","to":"0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB","value":"1000000"}"#
+    );
+
+    // logs of another event, or missing a topic
+    let topics = vec![
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string(),
+        "0x000000000000000000000000cd2a3d9f938e13cd947ec05abc7fe734df8dd826".to_string(),
+    ];
+    let data = "0x00000000000000000000000000000000000000000000000000000000000f4240";
+    assert!(decode_log(
+        "event Approval(address indexed owner, address indexed spender, uint256 value)",
+        topics.clone(),
+        data
+    )
+    .is_none());
+    assert!(decode_log(
+        "event Transfer(address indexed from, address indexed to, uint256 value)",
+        topics,
+        data
+    )
+    .is_none());
+    assert!(decode_log(
+        "event Transfer(address indexed from, address indexed to, uint256 value)",
+        vec![],
+        data
+    )
+    .is_none());
+}
+
+#[test]
+fn test_evm_address() {
+    // examples from EIP-55
+    for address in [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ] {
+        assert_eq!(
+            to_checksum_address(&address.to_lowercase(), None).as_deref(),
+            Some(address)
+        );
+        assert!(is_checksum_address(address, None));
+        assert!(is_valid_address(address, None));
+        assert!(is_valid_address(&address.to_lowercase(), None));
+    }
+    assert!(!is_valid_address(
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD",
+        None
+    ));
+    assert!(!is_valid_address(
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAe",
+        None
+    ));
+    assert!(!is_valid_address(
+        "0xzaAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        None
+    ));
+    assert_eq!(
+        to_checksum_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg", None),
+        None
+    );
+
+    // examples from EIP-1191
+    assert_eq!(
+        to_checksum_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", Some(30)).as_deref(),
+        Some("0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD")
+    );
+    assert!(is_checksum_address(
+        "0x5aAeb6053F3e94c9b9A09F33669435E7EF1BEaEd",
+        Some(31)
+    ));
+    assert!(!is_checksum_address(
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        Some(30)
+    ));
+
+    let priv_hex = "4646464646464646464646464646464646464646464646464646464646464646";
+    let pubkey = SecretKey::from_str(priv_hex)
+        .unwrap()
+        .public_key(&Secp256k1::new());
+    assert_eq!(
+        pubkey_to_address(&pubkey.to_string()),
+        "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"
+    );
+    assert_eq!(
+        pubkey_to_address(&pubkey.serialize_uncompressed().as_hex().to_string()),
+        "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"
+    );
+}
+
+#[test]
+fn test_evm_contract_address() {
+    let sender = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0";
+    assert_eq!(
+        create_address(sender, 0).to_lowercase(),
+        "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"
+    );
+    assert_eq!(
+        create_address(sender, 1).to_lowercase(),
+        "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8"
+    );
+    assert_eq!(
+        create_address(sender, 2).to_lowercase(),
+        "0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91"
+    );
+
+    // examples from EIP-1014
+    let init_code_hash = crate::evm::keccak256(&[0x00]).as_hex().to_string();
+    assert_eq!(
+        create2_address(
+            "0x0000000000000000000000000000000000000000",
+            "0x0000000000000000000000000000000000000000000000000000000000000000",
+            &init_code_hash,
+        ),
+        "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"
+    );
+    assert_eq!(
+        create2_address(
+            "0xdeadbeef00000000000000000000000000000000",
+            "0x000000000000000000000000feed000000000000000000000000000000000000",
+            &init_code_hash,
+        ),
+        "0xD04116cDd17beBE565EB2422F2497E06cC1C9833"
+    );
+}
+
+#[test]
+fn test_evm_user_operation() {
+    let wallet = HDWallet::new(
+        0,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let sender = "0x1306b01bc3e4ad202612d3843387e94737673f53";
+
+    let op = UserOperationV06 {
+        sender: sender.to_string(),
+        nonce: "0".to_string(),
+        init_code: "0x".to_string(),
+        call_data: "0xb61d27f6".to_string(),
+        call_gas_limit: "100000".to_string(),
+        verification_gas_limit: "200000".to_string(),
+        pre_verification_gas: "50000".to_string(),
+        max_fee_per_gas: "30000000000".to_string(),
+        max_priority_fee_per_gas: "1000000000".to_string(),
+        paymaster_and_data: "0x".to_string(),
+    };
+    let entry_point = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789";
+    let hash = user_operation_v06_hash(&op, entry_point, 1);
+    assert_ne!(hash, user_operation_v06_hash(&op, entry_point, 137));
+
+    // getUserOpHash spelled out word by word, as UserOperationLib.pack of EntryPoint v0.6 and
+    // EntryPoint.getUserOpHash lay them out
+    let keccak = |hex: &str| {
+        crate::evm::keccak256(&Vec::<u8>::from_hex(hex).unwrap())
+            .as_hex()
+            .to_string()
+    };
+    let empty_hash = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+    assert_eq!(keccak(""), empty_hash);
+    let word = |n: u64| format!("{n:064x}");
+    let address_word = |address: &str| format!("{:0>64}", address[2..].to_lowercase());
+    let user_op_hash = |packed: String, entry_point: &str, chain_id: u64| {
+        format!(
+            "0x{}",
+            keccak(&format!(
+                "{}{}{}",
+                keccak(&packed),
+                address_word(entry_point),
+                word(chain_id)
+            ))
+        )
+    };
+    let packed = [
+        address_word(sender),
+        word(0),
+        empty_hash.to_string(),
+        keccak("b61d27f6"),
+        word(100000),
+        word(200000),
+        word(50000),
+        word(30000000000),
+        word(1000000000),
+        empty_hash.to_string(),
+    ]
+    .concat();
+    assert_eq!(hash, user_op_hash(packed, entry_point, 1));
+    let signature = wallet.evm_sign_user_operation_v06(&op, entry_point, 1);
+    assert_eq!(
+        recover_personal_signer(&hash, &signature).unwrap(),
+        wallet.evm_address()
+    );
+
+    let op = UserOperationV07 {
+        sender: sender.to_string(),
+        nonce: "0x1".to_string(),
+        factory: Some("0x9406Cc6185a346906296840746125a0E44976454".to_string()),
+        factory_data: "0x5fbfb9cf".to_string(),
+        call_data: "0xb61d27f6".to_string(),
+        call_gas_limit: "0x186a0".to_string(),
+        verification_gas_limit: "0x30d40".to_string(),
+        pre_verification_gas: "50000".to_string(),
+        max_fee_per_gas: "30000000000".to_string(),
+        max_priority_fee_per_gas: "1000000000".to_string(),
+        paymaster: None,
+        paymaster_verification_gas_limit: "0".to_string(),
+        paymaster_post_op_gas_limit: "0".to_string(),
+        paymaster_data: "0x".to_string(),
+    };
+    let packed = op.pack();
+    assert_eq!(
+        packed.init_code,
+        "0x9406cc6185a346906296840746125a0e449764545fbfb9cf"
+    );
+    assert_eq!(
+        packed.account_gas_limits,
+        "0x00000000000000000000000000030d40000000000000000000000000000186a0"
+    );
+    assert_eq!(
+        packed.gas_fees,
+        "0x0000000000000000000000003b9aca00000000000000000000000006fc23ac00"
+    );
+    assert_eq!(packed.paymaster_and_data, "0x");
+
+    let entry_point = "0x0000000071727De22E5E9d8BAf0edAc6f37da032";
+    let hash = user_operation_v07_hash(&op, entry_point, 1);
+    // PackedUserOperation as UserOperationLib.encode of EntryPoint v0.7 hashes it
+    let packed = [
+        address_word(sender),
+        word(1),
+        keccak("9406cc6185a346906296840746125a0e449764545fbfb9cf"),
+        keccak("b61d27f6"),
+        "00000000000000000000000000030d40000000000000000000000000000186a0".to_string(),
+        word(50000),
+        "0000000000000000000000003b9aca00000000000000000000000006fc23ac00".to_string(),
+        empty_hash.to_string(),
+    ]
+    .concat();
+    assert_eq!(hash, user_op_hash(packed, entry_point, 1));
+
+    // sponsored, `paymaster || uint128 verification gas || uint128 post-op gas || data`
+    let sponsored = UserOperationV07 {
+        sender: sender.to_string(),
+        nonce: "0x1".to_string(),
+        factory: None,
+        factory_data: "0x".to_string(),
+        call_data: "0xb61d27f6".to_string(),
+        call_gas_limit: "0x186a0".to_string(),
+        verification_gas_limit: "0x30d40".to_string(),
+        pre_verification_gas: "50000".to_string(),
+        max_fee_per_gas: "30000000000".to_string(),
+        max_priority_fee_per_gas: "1000000000".to_string(),
+        paymaster: Some("0x3535353535353535353535353535353535353535".to_string()),
+        paymaster_verification_gas_limit: "70000".to_string(),
+        paymaster_post_op_gas_limit: "0x7530".to_string(),
+        paymaster_data: "0xdeadbeef".to_string(),
+    };
+    let paymaster_and_data = format!(
+        "{}{:032x}{:032x}deadbeef",
+        "3535353535353535353535353535353535353535", 70000, 0x7530
+    );
+    assert_eq!(
+        sponsored.pack().paymaster_and_data,
+        format!("0x{paymaster_and_data}")
+    );
+    let packed = [
+        address_word(sender),
+        word(1),
+        empty_hash.to_string(),
+        keccak("b61d27f6"),
+        "00000000000000000000000000030d40000000000000000000000000000186a0".to_string(),
+        word(50000),
+        "0000000000000000000000003b9aca00000000000000000000000006fc23ac00".to_string(),
+        keccak(&paymaster_and_data),
+    ]
+    .concat();
+    assert_eq!(
+        user_operation_v07_hash(&sponsored, entry_point, 11155111),
+        user_op_hash(packed, entry_point, 11155111)
+    );
+    let signature = wallet.evm_sign_user_operation_v07(&op, entry_point, 1);
+    assert_eq!(
+        recover_personal_signer(&hash, &signature).unwrap(),
+        wallet.evm_address()
+    );
+}
+
+#[test]
+fn test_evm_set_code_transaction() {
+    let wallet = HDWallet::new(
+        0,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let auth = wallet.evm_sign_authorization(Authorization {
+        chain_id: 1,
+        address: "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b".to_string(),
+        nonce: 8,
+    });
+    assert_eq!(auth.address, "0x63c0c19a282a1B52b07dD5a65b58948A07DAE32B");
+    assert_eq!(recover_authority(&auth).unwrap(), wallet.evm_address());
+    for invalid in [
+        SignedAuthorization {
+            y_parity: 2,
+            ..auth.clone()
+        },
+        SignedAuthorization {
+            s: format!("0x{}", "ff".repeat(32)),
+            ..auth.clone()
+        },
+        SignedAuthorization {
+            r: "0xzz".to_string(),
+            ..auth.clone()
+        },
+    ] {
+        assert!(recover_authority(&invalid).is_none());
+    }
+
+    let signed = sign_set_code_transaction(
+        &wallet.evm_priv_hex(),
+        SetCodeTransaction {
+            chain_id: 1,
+            nonce: 7,
+            max_priority_fee_per_gas: "1000000000".to_string(),
+            max_fee_per_gas: "20000000000".to_string(),
+            gas_limit: 100000,
+            to: wallet.evm_address(),
+            value: "0".to_string(),
+            data: "0x".to_string(),
+            access_list: vec![],
+            authorization_list: vec![auth.clone()],
+        },
+    );
+    assert!(signed.raw_tx_hex.starts_with("0x04"));
+    let decoded = decode_transaction(&signed.raw_tx_hex).unwrap();
+    assert_eq!(decoded.tx_type, 4);
+    assert_eq!(decoded.nonce, 7);
+    assert_eq!(decoded.from, wallet.evm_address());
+    assert_eq!(decoded.authorization_list, vec![auth]);
+}
+
+#[test]
+fn test_evm_ens() {
+    assert_eq!(ens_normalize_ascii("Nick.ETH").unwrap(), "nick.eth");
+    assert_eq!(ens_normalize_ascii("_vitalik.eth").unwrap(), "_vitalik.eth");
+    assert_eq!(ens_normalize_ascii("$1.eth").unwrap(), "$1.eth");
+    assert!(ens_normalize_ascii("vit_alik.eth").is_none());
+    assert!(ens_normalize_ascii("ab--c.eth").is_none());
+    assert!(ens_normalize_ascii("nick..eth").is_none());
+    assert!(ens_normalize_ascii("nick eth").is_none());
+    assert!(ens_normalize_ascii("nick!.eth").is_none());
+    // ENSIP-15 maps fullwidth letters, and validates emoji and scripts, with its own tables
+    assert!(ens_normalize_ascii("\u{ff4e}\u{ff49}\u{ff43}\u{ff4b}.eth").is_none());
+    assert!(ens_normalize_ascii("\u{1f4a9}\u{fe0f}.eth").is_none());
+    assert!(ens_normalize_ascii("n\u{0131}ck.eth").is_none());
+    assert!(ens_namehash("\u{ff4e}\u{ff49}\u{ff43}\u{ff4b}.eth").is_none());
+    assert!(ens_labelhash("vit_alik").is_none());
+    assert!(ens_addr_call("nick..eth").is_none());
+
+    // examples from EIP-137
+    assert_eq!(
+        ens_namehash("").unwrap(),
+        "0x0000000000000000000000000000000000000000000000000000000000000000"
+    );
+    assert_eq!(
+        ens_namehash("eth").unwrap(),
+        "0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+    );
+    assert_eq!(
+        ens_namehash("foo.eth").unwrap(),
+        "0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+    );
+    assert_eq!(ens_namehash("Foo.ETH"), ens_namehash("foo.eth"));
+    assert_eq!(
+        ens_labelhash("eth").unwrap(),
+        "0x4f5b812789fc606be1b3b16908db13fc7a9adf7ca72641f84d75b47069d3d7f0"
+    );
+    assert_eq!(
+        ens_addr_call("foo.eth").unwrap(),
+        "0x3b3b57dede9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+    );
+}
+
+#[test]
+fn test_evm_keystore() {
+    // pbkdf2 example from the Web3 Secret Storage definition
+    let keystore = r#"{"crypto":{"cipher":"aes-128-ctr","cipherparams":{"iv":"6087dab2f9fdbbfaddc31a909735c1e6"},"ciphertext":"5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46","kdf":"pbkdf2","kdfparams":{"c":262144,"dklen":32,"prf":"hmac-sha256","salt":"ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"},"mac":"517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"},"id":"3198bc9c-6672-5ab3-d995-4942343ae5b6","version":3}"#;
+    assert_eq!(
+        decrypt_keystore(keystore, "testpassword").unwrap(),
+        "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d"
+    );
+    assert!(decrypt_keystore(keystore, "wrongpassword").is_none());
+    // malformed or unsupported keystores
+    for (from, to) in [
+        ("\"dklen\":32", "\"dklen\":16"),
+        ("\"version\":3", "\"version\":4"),
+        ("aes-128-ctr", "aes-128-cbc"),
+        ("\"kdf\":\"pbkdf2\"", "\"kdf\":\"argon2\""),
+        ("hmac-sha256", "hmac-sha512"),
+        ("\"c\":262144", "\"c\":4294967296"),
+        ("\"c\":262144", "\"c\":0"),
+        ("\"mac\"", "\"tag\""),
+        ("\"ciphertext\":\"53", "\"ciphertext\":\"5g"),
+        (
+            "6087dab2f9fdbbfaddc31a909735c1e6",
+            "6087dab2f9fdbbfaddc31a909735c1",
+        ),
+        ("\"salt\"", "\"nonce\""),
+        ("\"version\":3}", "\"version\":3"),
+    ] {
+        assert!(decrypt_keystore(&keystore.replace(from, to), "testpassword").is_none());
+    }
+    let scrypt = r#"{"crypto":{"cipher":"aes-128-ctr","cipherparams":{"iv":"00000000000000000000000000000000"},"ciphertext":"00","kdf":"scrypt","kdfparams":{"dklen":32,"n":4096,"p":1,"r":8,"salt":"00"},"mac":"00"},"version":3}"#;
+    for (from, to) in [
+        ("\"n\":4096", "\"n\":4294967296"),
+        ("\"n\":4096", "\"n\":4095"),
+        ("\"r\":8", "\"r\":4294967304"),
+        ("\"p\":1", "\"p\":4294967297"),
+    ] {
+        assert!(decrypt_keystore(&scrypt.replace(from, to), "testpassword").is_none());
+    }
+
+    let mnemonic = "work man father plunge mystery proud hollow address reunion sauce theory bonus";
+    let wallet = HDWallet::new(0, Some(mnemonic.to_string()));
+    let keystore = wallet.evm_export_keystore("password", KeystoreKdf::ScryptLight);
+    let json: serde_json::Value = serde_json::from_str(&keystore).unwrap();
+    assert_eq!(json["address"], "ffdb339065c91c88e8a3cc6857359b6c2fb78cf5");
+    assert_eq!(
+        decrypt_keystore(&keystore, "password").unwrap(),
+        wallet.evm_priv_hex()
+    );
+
+    let priv_hex = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+    let keystore = encrypt_keystore(priv_hex, "testpassword", KeystoreKdf::ScryptLight);
+    assert_eq!(
+        decrypt_keystore(&keystore, "testpassword").unwrap(),
+        priv_hex
+    );
+}
+
+#[test]
+fn test_encrypted_wallet() {
+    let mnemonic = "work man father plunge mystery proud hollow address reunion sauce theory bonus";
+    let wallet = HDWallet::new(0, Some(mnemonic.to_string()));
+    let encrypted = wallet.encrypt("password", r#"{"name":"main"}"#);
+    assert!(!encrypted.contains("plunge"));
+    assert_eq!(
+        decrypt_wallet_metadata(&encrypted, "password").unwrap(),
+        r#"{"name":"main"}"#
+    );
+    assert!(decrypt_wallet_metadata(&encrypted, "wrong").is_none());
+    assert!(HDWallet::from_encrypted(&encrypted, "wrong", None).is_none());
+    let tampered = encrypted.replace("\"t\":2", "\"t\":1");
+    assert!(decrypt_wallet_metadata(&tampered, "password").is_none());
+    let restored = HDWallet::from_encrypted(&encrypted, "password", None).unwrap();
+    assert_eq!(restored.export_mnemonic(), mnemonic);
+    assert_eq!(restored.bip86_address(), wallet.bip86_address());
+
+    let wallet = HDWallet::new_with_passphrase(1, Some(mnemonic.to_string()), "TREZOR");
+    let encrypted = wallet.encrypt("password", "");
+    assert!(HDWallet::from_encrypted(&encrypted, "password", None).is_none());
+    let restored =
+        HDWallet::from_encrypted(&encrypted, "password", Some("TREZOR".to_string())).unwrap();
+    assert_eq!(restored.export_master_priv(), wallet.export_master_priv());
+    assert_ne!(
+        restored.export_master_priv(),
+        HDWallet::new(1, Some(mnemonic.to_string())).export_master_priv()
+    );
+
+    let wallet = HDWallet::from_master_priv(&wallet.export_master_priv());
+    let encrypted = wallet.encrypt("password", "");
+    let restored = HDWallet::from_encrypted(&encrypted, "password", None).unwrap();
+    assert_eq!(restored.export_master_priv(), wallet.export_master_priv());
+
+    // version 1 wallets, whose additional data is only the version, stay readable
+    let encrypted = r#"{"version":1,"kdf":"argon2id","kdfparams":{"m":19456,"t":2,"p":1,"salt":"96d4a18c3956fb0fae1bcfafe039313f"},"cipher":"xchacha20-poly1305","nonce":"bb2b5527daa59901c88d1b8ca353b2897ecc7382081e9fe1","ciphertext":"a79b8a24b75c2de578f60c3d559ce1a72ca53b0c8434dd41923c366b6f946836c3905ff4e0e2487a99b21fe6db6bb40194883c9aa106c143e8ef0b8e73da6c1cbf4b8318e1d4b013deea98d94758c7e62b4fdd5c09c997f2ab969c0ef8243e2a99605d55cee6c84ef90feed9616e8ee798b8d741c6d8862d4165529df44e329cec3658df4af2a7e22598d1334504530d269402c603e48424ac99f9fa280570f5e026edbeb4ed5c0a035fd85e6999a4f7d498b268de"}"#;
+    let restored = HDWallet::from_encrypted(encrypted, "password", None).unwrap();
+    assert_eq!(restored.export_mnemonic(), mnemonic);
+    assert_eq!(
+        decrypt_wallet_metadata(encrypted, "password").unwrap(),
+        r#"{"name":"main"}"#
+    );
+
+    // corrupt envelopes, unknown versions and out of bounds parameters
+    for (from, to) in [
+        ("\"version\":1", "\"version\":3"),
+        ("\"version\":1", "\"version\":\"1\""),
+        ("\"kdf\":\"argon2id\"", "\"kdf\":\"scrypt\""),
+        ("xchacha20-poly1305", "aes-256-gcm"),
+        ("\"m\":19456", "\"m\":4294967296"),
+        ("\"m\":19456", "\"m\":2097152"),
+        ("\"t\":2", "\"t\":1000"),
+        ("\"p\":1", "\"p\":255"),
+        ("\"p\":1", "\"p\":0"),
+        ("\"nonce\":\"bb", "\"nonce\":\""),
+        ("\"ciphertext\":\"a7", "\"ciphertext\":\"z7"),
+        ("\"salt\"", "\"seed\""),
+        ("\"}", "\""),
+    ] {
+        let corrupt = encrypted.replace(from, to);
+        assert!(HDWallet::from_encrypted(&corrupt, "password", None).is_none());
+        assert!(decrypt_wallet_metadata(&corrupt, "password").is_none());
+    }
+}
+
+#[test]
+fn test_bip38() {
+    // test vectors from BIP38
+    let priv_hex = "cbf4b9f70470856bb4f40f80b87edb90865997ffee6df315ab166d713af433a5";
+    let encrypted = "6PRVWUbkzzsbcVac2qwfssoUJAN1Xhrg6bNk8J7Nzm5H7kxEbn2Nh2ZoGg";
+    assert_eq!(
+        bip38_encrypt(priv_hex, "TestingOneTwoThree", false),
+        encrypted
+    );
+    let key = bip38_decrypt(encrypted, "TestingOneTwoThree").unwrap();
+    assert_eq!(key.priv_hex, priv_hex);
+    assert!(!key.compressed);
+    assert!(bip38_decrypt(encrypted, "TestingOneTwoFour").is_none());
+
+    let encrypted = "6PYNKZ1EAgYgmQfmNVamxyXVWHzK5s6DGhwP4J5o44cvXdoY7sRzhtpUeo";
+    assert_eq!(
+        bip38_encrypt(priv_hex, "TestingOneTwoThree", true),
+        encrypted
+    );
+    assert!(
+        bip38_decrypt(encrypted, "TestingOneTwoThree")
+            .unwrap()
+            .compressed
+    );
+
+    let key = bip38_decrypt(
+        "6PfQu77ygVyJLZjfvMLyhLMQbYnu5uguoJJ4kMCLqWwPEdfpwANVS76gTX",
+        "TestingOneTwoThree",
+    )
+    .unwrap();
+    assert_eq!(
+        key.priv_hex,
+        "a43a940577f4e97f5c4d39eb14ff083a98187c64ea7c99ef7ce460833959a519"
+    );
+
+    let intermediate = bip38_intermediate_code("passphrase", Some(263183), Some(1));
+    let generated = bip38_generate_from_intermediate(&intermediate, true);
+    assert_eq!(
+        bip38_verify_confirmation(&generated.confirmation_code, "passphrase").unwrap(),
+        generated.address
+    );
+    let key = bip38_decrypt(&generated.encrypted_key, "passphrase").unwrap();
+    assert!(key.compressed);
+
+    let mnemonic = "work man father plunge mystery proud hollow address reunion sauce theory bonus";
+    let wallet = HDWallet::new(1, Some(mnemonic.to_string()));
+    let prevouts = vec![Prevout {
+        txid: "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b".to_string(),
+        vout: 0,
+        amount: 0.001,
+    }];
+    assert!(wallet
+        .sweep_bip38(
+            "6PRVWUbkzzsbcVac2qwfssoUJAN1Xhrg6bNk8J7Nzm5H7kxEbn2Nh2ZoGg",
+            "wrong",
+            prevouts.clone(),
+            1000,
+        )
+        .is_none());
+    let tx_hex = wallet
+        .sweep_bip38(
+            "6PRVWUbkzzsbcVac2qwfssoUJAN1Xhrg6bNk8J7Nzm5H7kxEbn2Nh2ZoGg",
+            "TestingOneTwoThree",
+            prevouts,
+            1000,
+        )
+        .unwrap();
+    let tx: Transaction = consensus::deserialize(&Vec::<u8>::from_hex(&tx_hex).unwrap()).unwrap();
+    assert_eq!(tx.output[0].value, Amount::from_sat(99000));
+    // uncompressed public key pushed after the signature
+    assert_eq!(
+        tx.input[0]
+            .script_sig
+            .instructions()
+            .last()
+            .unwrap()
+            .unwrap()
+            .push_bytes()
+            .unwrap()
+            .len(),
+        65
+    );
+}
+
+#[test]
+fn test_wif() {
+    let priv_hex = "cbf4b9f70470856bb4f40f80b87edb90865997ffee6df315ab166d713af433a5";
+    let wif = "5KN7MzqK5wt2TP1fQCYyHBtDrXdJuXbUzm4A9rKAteGu3Qi5CVR";
+    assert_eq!(encode_wif(priv_hex, 0, false), wif);
+    assert_eq!(decode_wif(wif), priv_hex);
+    let wallet = SingleKeyWallet::from_wif(wif);
+    assert!(!wallet.is_compressed());
+    assert_eq!(wallet.p2pkh_address(), "1Jq6MksXQVWzrznvZzxkV6oY57oWXD9TXB");
+
+    let wif = "L44B5gGEpqEDRS9vVPz7QT35jcBG2r3CZwSwQ4fCewXAhAhqGVpP";
+    assert_eq!(encode_wif(priv_hex, 0, true), wif);
+    let wallet = SingleKeyWallet::from_wif(wif);
+    assert_eq!(wallet.p2pkh_address(), "164MQi977u9GUteHr4EPH27VkkdxmfCvGW");
+    assert_eq!(wallet.export_wif(), wif);
+
+    let mnemonic = "work man father plunge mystery proud hollow address reunion sauce theory bonus";
+    let hd_wallet = HDWallet::new(1, Some(mnemonic.to_string()));
+    let wallet = SingleKeyWallet::from_wif(&hd_wallet.bip44_wif());
+    assert_eq!(wallet.p2pkh_address(), hd_wallet.bip44_address());
+    let wallet = SingleKeyWallet::from_wif(&hd_wallet.bip84_wif());
+    assert_eq!(wallet.p2wpkh_address(), hd_wallet.bip84_address());
+    assert_eq!(wallet.priv_hex(), hd_wallet.bip84_priv_hex());
+    let wallet = SingleKeyWallet::from_wif(&hd_wallet.bip86_wif());
+    assert_eq!(wallet.p2tr_address(), hd_wallet.bip86_address());
+    assert_eq!(
+        wallet.tweaked_priv_hex(None),
+        hd_wallet.bip86_tweaked_priv_hex(None)
+    );
+    let wallet = SingleKeyWallet::from_priv_hex(&hd_wallet.evm_priv_hex(), 1);
+    assert_eq!(wallet.evm_address(), hd_wallet.evm_address());
+}
+
+#[test]
+fn test_p2wpkh_sign() {
+    let wallet = SingleKeyWallet::from_priv_hex(
+        "cbf4b9f70470856bb4f40f80b87edb90865997ffee6df315ab166d713af433a5",
+        1,
+    );
+    let address = Address::from_str(&wallet.p2wpkh_address())
+        .unwrap()
+        .assume_checked();
+    let prevout = Prevout {
+        txid: "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b".to_string(),
+        vout: 1,
+        amount: 0.001,
+    };
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_str(&prevout.txid).unwrap(),
+                vout: prevout.vout,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(99000),
+            script_pubkey: address.script_pubkey(),
+        }],
+    };
+    let tx_hex = wallet.p2wpkh_sign(
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout],
+    );
+    let tx: Transaction = consensus::deserialize(&Vec::<u8>::from_hex(&tx_hex).unwrap()).unwrap();
+
+    let secp = Secp256k1::new();
+    let sighash = sighash::SighashCache::new(&unsigned_tx)
+        .p2wpkh_signature_hash(
+            0,
+            &address.script_pubkey(),
+            Amount::from_sat(100000),
+            EcdsaSighashType::All,
+        )
+        .unwrap();
+    let witness = &tx.input[0].witness;
+    let sig = bitcoin::ecdsa::Signature::from_slice(witness.nth(0).unwrap()).unwrap();
+    let pubkey = PublicKey::from_slice(witness.nth(1).unwrap()).unwrap();
+    secp.verify_ecdsa(
+        &Message::from_digest(sighash.to_byte_array()),
+        &sig.sig,
+        &pubkey.inner,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_multisig_wallet() {
+    // test vector from BIP-380
+    assert_eq!(descriptor_checksum("raw(deadbeef)"), "89f8spxm");
+
+    let a = HDWallet::new(
+        1,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let b = HDWallet::new(
+        1,
+        Some("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_string()),
+    );
+    let c = HDWallet::new(
+        1,
+        Some(
+            "legal winner thank year wave sausage worth useful legal winner thank yellow"
+                .to_string(),
+        ),
+    );
+    let script_type = MultisigScriptType::P2wsh;
+    let xpubs = [
+        a.bip48_xpub(script_type),
+        b.bip48_xpub(script_type),
+        c.bip48_xpub(script_type),
+    ];
+    assert!(xpubs[0].contains("/48h/1h/0h/2h]tpub"));
+
+    let ma = MultisigWallet::new(&a, 2, vec![xpubs[2].clone(), xpubs[1].clone()], script_type);
+    let mb = MultisigWallet::new(&b, 2, xpubs.to_vec(), script_type);
+    let mc = MultisigWallet::new(
+        &c,
+        2,
+        vec![format!("{}/0/*", xpubs[0]), xpubs[1].clone()],
+        script_type,
+    );
+    assert_eq!(ma.descriptor(false), mb.descriptor(false));
+    assert_eq!(ma.descriptor(true), mc.descriptor(true));
+    let descriptor = ma.descriptor(false);
+    let (body, checksum) = descriptor.split_once('#').unwrap();
+    assert!(body.starts_with("wsh(sortedmulti(2,["));
+    assert_eq!(descriptor_checksum(body), checksum);
+    assert_eq!(ma.address(false, 3), mc.address(false, 3));
+    assert_ne!(ma.address(false, 3), ma.address(true, 3));
+    assert!(ma.address(false, 0).starts_with("tb1q"));
+
+    let nested = MultisigWallet::new(
+        &a,
+        2,
+        vec![
+            b.bip48_xpub(MultisigScriptType::P2shP2wsh),
+            c.bip48_xpub(MultisigScriptType::P2shP2wsh),
+        ],
+        MultisigScriptType::P2shP2wsh,
+    );
+    assert!(nested
+        .descriptor(false)
+        .starts_with("sh(wsh(sortedmulti(2,"));
+    assert!(nested.address(false, 0).starts_with('2'));
+
+    let prevout = MultisigPrevout {
+        txid: "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b".to_string(),
+        vout: 0,
+        amount: 0.001,
+        is_change: false,
+        index: 3,
+    };
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_str(&prevout.txid).unwrap(),
+                vout: prevout.vout,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(99000),
+            script_pubkey: Address::from_str(&ma.address(true, 0))
+                .unwrap()
+                .assume_checked()
+                .script_pubkey(),
+        }],
+    };
+    let psbt = ma.create_psbt(
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout],
+    );
+    let signed_a = ma.sign_psbt(&psbt);
+    assert_eq!(signed_a.signed_inputs, vec![0]);
+    assert!(signed_a.errors.is_empty());
+    let signed_b = mb.sign_psbt(&psbt).psbt;
+
+    // an input missing its UTXO is left unsigned and reported instead of failing the whole PSBT
+    let mut partial = Psbt::from_str(&psbt).unwrap();
+    let mut txin = partial.unsigned_tx.input[0].clone();
+    txin.previous_output.vout = 1;
+    partial.unsigned_tx.input.push(txin);
+    let mut input = partial.inputs[0].clone();
+    input.witness_utxo = None;
+    partial.inputs.push(input);
+    let signed = ma.sign_psbt(&partial.to_string());
+    assert_eq!(signed.signed_inputs, vec![0]);
+    assert_eq!(signed.errors, vec!["input 1: missing utxo"]);
+    let partial = Psbt::from_str(&signed.psbt).unwrap();
+    assert_eq!(partial.inputs[0].partial_sigs.len(), 1);
+    assert!(partial.inputs[1].partial_sigs.is_empty());
+
+    let combined = combine_psbts(vec![signed_a.psbt, signed_b]);
+    let tx_hex = mc.finalize_psbt(&combined);
+    let tx: Transaction = consensus::deserialize(&Vec::<u8>::from_hex(&tx_hex).unwrap()).unwrap();
+
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 4);
+    assert!(witness.nth(0).unwrap().is_empty());
+    let witness_script = ScriptBuf::from_bytes(witness.nth(3).unwrap().to_vec());
+    assert_eq!(
+        Address::p2wsh(&witness_script, Network::Testnet).to_string(),
+        ma.address(false, 3)
+    );
+    let pubkeys: Vec<PublicKey> = witness_script
+        .instructions()
+        .filter_map(|instruction| match instruction.unwrap() {
+            script::Instruction::PushBytes(bytes) => PublicKey::from_slice(bytes.as_bytes()).ok(),
+            script::Instruction::Op(_) => None,
+        })
+        .collect();
+    assert_eq!(pubkeys.len(), 3);
+
+    let secp = Secp256k1::new();
+    let sighash = sighash::SighashCache::new(&unsigned_tx)
+        .p2wsh_signature_hash(
+            0,
+            &witness_script,
+            Amount::from_sat(100000),
+            EcdsaSighashType::All,
+        )
+        .unwrap();
+    let msg = Message::from_digest(sighash.to_byte_array());
+    for i in 1..3 {
+        let sig = bitcoin::ecdsa::Signature::from_slice(witness.nth(i).unwrap()).unwrap();
+        assert!(pubkeys
+            .iter()
+            .any(|pubkey| secp.verify_ecdsa(&msg, &sig.sig, &pubkey.inner).is_ok()));
+    }
+}
+
+#[test]
+fn test_taproot_multisig() {
+    let a = HDWallet::new(
+        1,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let b = HDWallet::new(
+        1,
+        Some("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_string()),
+    );
+    let c = HDWallet::new(
+        1,
+        Some(
+            "legal winner thank year wave sausage worth useful legal winner thank yellow"
+                .to_string(),
+        ),
+    );
+    let script_type = MultisigScriptType::P2tr;
+    let xpubs = vec![
+        a.bip48_xpub(script_type),
+        b.bip48_xpub(script_type),
+        c.bip48_xpub(script_type),
+    ];
+    assert!(xpubs[0].contains("/48h/1h/0h/3h]tpub"));
+
+    let ma = MultisigWallet::new(&a, 2, xpubs.clone(), script_type);
+    let mb = MultisigWallet::new_with_internal_key(
+        &b,
+        2,
+        vec![xpubs[0].clone(), xpubs[2].clone()],
+        None,
+    );
+    let mc = MultisigWallet::new(&c, 2, xpubs[..2].to_vec(), script_type);
+    assert_eq!(ma.descriptor(false), mb.descriptor(false));
+    assert!(ma.descriptor(false).starts_with(
+        "tr(50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0,sortedmulti_a(2,["
+    ));
+    assert!(ma.address(false, 0).starts_with("tb1p"));
+    assert_eq!(ma.address(false, 0), mc.address(false, 0));
+
+    let spend = |wallet: &MultisigWallet, signers: Vec<&MultisigWallet>| {
+        let prevout = MultisigPrevout {
+            txid: "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b".to_string(),
+            vout: 0,
+            amount: 0.001,
+            is_change: false,
+            index: 0,
+        };
+        let unsigned_tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_str(&prevout.txid).unwrap(),
+                    vout: prevout.vout,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(99000),
+                script_pubkey: Address::from_str(&wallet.address(true, 0))
+                    .unwrap()
+                    .assume_checked()
+                    .script_pubkey(),
+            }],
+        };
+        let psbt = wallet.create_psbt(
+            &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+            vec![prevout],
+        );
+        let signed = signers
+            .iter()
+            .map(|signer| {
+                let signed = signer.sign_psbt(&psbt);
+                assert_eq!(signed.signed_inputs, vec![0]);
+                assert!(signed.errors.is_empty());
+                signed.psbt
+            })
+            .collect();
+        let tx_hex = wallet.finalize_psbt(&combine_psbts(signed));
+        let tx: Transaction =
+            consensus::deserialize(&Vec::<u8>::from_hex(&tx_hex).unwrap()).unwrap();
+        let utxo = TxOut {
+            value: Amount::from_sat(100000),
+            script_pubkey: Address::from_str(&wallet.address(false, 0))
+                .unwrap()
+                .assume_checked()
+                .script_pubkey(),
+        };
+        (unsigned_tx, utxo, tx.input[0].witness.clone())
+    };
+
+    // script path, with the signatures of b and c
+    let secp = Secp256k1::new();
+    let (unsigned_tx, utxo, witness) = spend(&ma, vec![&mb, &mc]);
+    assert_eq!(witness.len(), 5);
+    let script = ScriptBuf::from_bytes(witness.nth(3).unwrap().to_vec());
+    let control_block = taproot::ControlBlock::decode(witness.nth(4).unwrap()).unwrap();
+    let output_key = XOnlyPublicKey::from_slice(&utxo.script_pubkey.as_bytes()[2..]).unwrap();
+    assert!(control_block.verify_taproot_commitment(&secp, output_key, &script));
+    let leaf_hash = TapLeafHash::from_script(&script, taproot::LeafVersion::TapScript);
+    let sighash = sighash::SighashCache::new(&unsigned_tx)
+        .taproot_script_spend_signature_hash(
+            0,
+            &sighash::Prevouts::All(&[utxo]),
+            leaf_hash,
+            TapSighashType::Default,
+        )
+        .unwrap();
+    let msg = Message::from_digest(sighash.to_byte_array());
+    let keys: Vec<XOnlyPublicKey> = script
+        .instructions()
+        .filter_map(|instruction| match instruction.unwrap() {
+            script::Instruction::PushBytes(bytes) => {
+                XOnlyPublicKey::from_slice(bytes.as_bytes()).ok()
+            }
+            script::Instruction::Op(_) => None,
+        })
+        .collect();
+    // witness elements are in reverse key order
+    let mut count = 0;
+    for (i, key) in keys.iter().rev().enumerate() {
+        let element = witness.nth(i).unwrap();
+        if !element.is_empty() {
+            let sig = schnorr::Signature::from_slice(element).unwrap();
+            secp.verify_schnorr(&sig, &msg, key).unwrap();
+            count += 1;
+        }
+    }
+    assert_eq!(count, 2);
+
+    // a taproot input missing its witness UTXO can't be signed, and is reported
+    let prevout = MultisigPrevout {
+        txid: "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b".to_string(),
+        vout: 0,
+        amount: 0.001,
+        is_change: false,
+        index: 0,
+    };
+    let psbt = ma.create_psbt(
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout],
+    );
+    let mut psbt = Psbt::from_str(&psbt).unwrap();
+    psbt.inputs[0].witness_utxo = None;
+    let signed = mb.sign_psbt(&psbt.to_string());
+    assert!(signed.signed_inputs.is_empty());
+    assert_eq!(
+        signed.errors,
+        vec!["input 0: missing witness utxo of an input"]
+    );
+
+    // key path of an internal key held by a
+    let internal_key = a.bip48_xpub(MultisigScriptType::P2tr);
+    let ka =
+        MultisigWallet::new_with_internal_key(&a, 2, xpubs.clone(), Some(internal_key.clone()));
+    let kb = MultisigWallet::new_with_internal_key(&b, 2, xpubs, Some(internal_key));
+    assert_eq!(ka.address(false, 0), kb.address(false, 0));
+    assert_ne!(ka.address(false, 0), ma.address(false, 0));
+    assert!(ka.descriptor(false).starts_with("tr(["));
+    let (unsigned_tx, utxo, witness) = spend(&kb, vec![&ka]);
+    assert_eq!(witness.len(), 1);
+    let output_key = XOnlyPublicKey::from_slice(&utxo.script_pubkey.as_bytes()[2..]).unwrap();
+    let sighash = sighash::SighashCache::new(&unsigned_tx)
+        .taproot_key_spend_signature_hash(
+            0,
+            &sighash::Prevouts::All(&[utxo]),
+            TapSighashType::Default,
+        )
+        .unwrap();
+    let sig = schnorr::Signature::from_slice(witness.nth(0).unwrap()).unwrap();
+    secp.verify_schnorr(
+        &sig,
+        &Message::from_digest(sighash.to_byte_array()),
+        &output_key,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_musig2() {
+    // test vectors from BIP-327
+    let pubkeys = [
+        "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+        "03DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        "023590A94E768F8E1815C2F24B4D80A8E3149316C3518CE7B7AD338368D038CA66",
+    ];
+    let key_agg = |indices: &[usize]| {
+        musig2_key_agg(indices.iter().map(|i| pubkeys[*i].to_string()).collect()).to_uppercase()
+    };
+    assert_eq!(
+        key_agg(&[0, 1, 2]),
+        "90539EEDE565F5D054F32CC0C220126889ED1E5D193BAF15AEF344FE59D4610C"
+    );
+    assert_eq!(
+        key_agg(&[2, 1, 0]),
+        "6204DE8B083426DC6EAF9502D27024D53FC826BF7D2012148A0575435DF54B2B"
+    );
+    assert_eq!(
+        key_agg(&[0, 0, 0]),
+        "B436E3BAD62B8CD409969A224731C193D051162D8C5AE8B109306127DA3AA935"
+    );
+    assert_eq!(
+        musig2_nonce_agg(vec![
+            "020151C80F435648DF67A22B749CD798CE54E0321D034B92B709B567D60A42E66603BA47FBC1834437B3212E89A84D8425E7BF12E0245D98262268EBDCB385D50641".to_string(),
+            "03FF406FFD8ADB9CD29877E4985014F66A59F6CD01C0E88CAA8E5F3166B1F676A60248C264CDD57D3C24D79990B0F865674EB62A0F9018277A95011B41BFC193B833".to_string(),
+        ])
+        .to_uppercase(),
+        "035FE1873B4F2967F52FEA4A06AD5A8ECCBE9D0FD73068012C894E2E87CCB5804B024725377345BDE0E9C33AF3C43C0A29A9249F2F2956FA8CFEB55C8573D0262DC8"
+    );
+
+    let a = HDWallet::new(
+        1,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let b = HDWallet::new(
+        1,
+        Some("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_string()),
+    );
+    let c = HDWallet::new(
+        1,
+        Some(
+            "legal winner thank year wave sausage worth useful legal winner thank yellow"
+                .to_string(),
+        ),
+    );
+    let pubkeys = vec![a.musig2_pubkey(), b.musig2_pubkey(), c.musig2_pubkey()];
+    let ma = Musig2Wallet::new(&a, pubkeys.clone());
+    let mb = Musig2Wallet::new(&b, vec![pubkeys[2].clone(), pubkeys[0].clone()]);
+    let mc = Musig2Wallet::new(&c, pubkeys[..2].to_vec());
+    assert_eq!(ma.aggregate_xpub(), musig2_aggregate_xpub(pubkeys, 1));
+    assert_eq!(ma.descriptor(false), mb.descriptor(false));
+    assert!(ma.descriptor(true).starts_with("tr(musig(0"));
+    assert!(ma.address(false, 1).starts_with("tb1p"));
+    assert_eq!(ma.address(false, 1), mc.address(false, 1));
+    assert_ne!(ma.address(false, 1), ma.address(true, 1));
+
+    let prevout = MultisigPrevout {
+        txid: "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b".to_string(),
+        vout: 0,
+        amount: 0.001,
+        is_change: false,
+        index: 1,
+    };
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_str(&prevout.txid).unwrap(),
+                vout: prevout.vout,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(99000),
+            script_pubkey: Address::from_str(&ma.address(true, 0))
+                .unwrap()
+                .assume_checked()
+                .script_pubkey(),
+        }],
+    };
+    let psbt = mc.create_psbt(
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout],
+    );
+    let nonces = combine_psbts(vec![
+        ma.nonce_psbt(&psbt),
+        mb.nonce_psbt(&psbt),
+        mc.nonce_psbt(&psbt),
+    ]);
+    let signed = combine_psbts(vec![
+        ma.sign_psbt(&nonces),
+        mb.sign_psbt(&nonces),
+        mc.sign_psbt(&nonces),
+    ]);
+    // the secret nonce of round 1 is gone once used
+    assert!(
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| ma.sign_psbt(&nonces))).is_err()
+    );
+    let tx_hex = ma.finalize_psbt(&signed);
+    let tx: Transaction = consensus::deserialize(&Vec::<u8>::from_hex(&tx_hex).unwrap()).unwrap();
+
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 1);
+    let utxo = TxOut {
+        value: Amount::from_sat(100000),
+        script_pubkey: Address::from_str(&ma.address(false, 1))
+            .unwrap()
+            .assume_checked()
+            .script_pubkey(),
+    };
+    let output_key = XOnlyPublicKey::from_slice(&utxo.script_pubkey.as_bytes()[2..]).unwrap();
+    let sighash = sighash::SighashCache::new(&unsigned_tx)
+        .taproot_key_spend_signature_hash(
+            0,
+            &sighash::Prevouts::All(&[utxo]),
+            TapSighashType::Default,
+        )
+        .unwrap();
+    let secp = Secp256k1::new();
+    let sig = schnorr::Signature::from_slice(witness.nth(0).unwrap()).unwrap();
+    secp.verify_schnorr(
+        &sig,
+        &Message::from_digest(sighash.to_byte_array()),
+        &output_key,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_frost() {
+    let secp = Secp256k1::new();
+    let digest = sha256::Hash::hash(b"frost").to_byte_array();
+    let msg = Message::from_digest(digest);
+
+    // signs with the signers at the given indices, returning the signature
+    let sign = |signers: &[&FrostSigner], taproot_tweak: bool| {
+        let commitments = signers.iter().map(|s| s.commit()).collect();
+        let package =
+            frost_signing_package(commitments, &digest.as_hex().to_string(), taproot_tweak)
+                .value
+                .unwrap();
+        let shares = signers.iter().map(|s| s.sign(&package).unwrap()).collect();
+        let sig = frost_aggregate(&package, shares, &signers[0].public_key_package());
+        assert!(sig.culprits.is_empty() && sig.errors.is_empty());
+        schnorr::Signature::from_str(&sig.value.unwrap()).unwrap()
+    };
+
+    // trusted dealer, 2 of 3 of a known key
+    let secret = "0b432b2677937381aef05bb02a66ecd012773062cf3fa2549e44f58ed2401710";
+    let signers: Vec<FrostSigner> = frost_trusted_dealer(2, 3, Some(secret.to_string()))
+        .iter()
+        .map(|share| FrostSigner::new(share))
+        .collect();
+    let keypair = Keypair::from_seckey_str(&secp, secret).unwrap();
+    let group_key = keypair.x_only_public_key().0;
+    assert_eq!(signers[1].group_public_key(), group_key.to_string());
+    let sig = sign(&[&signers[0], &signers[2]], false);
+    secp.verify_schnorr(&sig, &msg, &group_key).unwrap();
+    let sig = sign(&[&signers[1], &signers[2]], false);
+    secp.verify_schnorr(&sig, &msg, &group_key).unwrap();
+
+    // key path of the taproot address, as `schnorr_sign` with the tweaked key
+    let output_key = keypair
+        .tap_tweak(&secp, None)
+        .to_inner()
+        .x_only_public_key()
+        .0;
+    let address = Address::p2tr(&secp, group_key, None, Network::Testnet).to_string();
+    assert_eq!(signers[0].address(1), address);
+    let sig = sign(&[&signers[0], &signers[1], &signers[2]], true);
+    secp.verify_schnorr(&sig, &msg, &output_key).unwrap();
+    let expected = schnorr_sign(
+        &keypair
+            .tap_tweak(&secp, None)
+            .to_inner()
+            .display_secret()
+            .to_string(),
+        &digest.as_hex().to_string(),
+    );
+    let expected = schnorr::Signature::from_str(&expected).unwrap();
+    secp.verify_schnorr(&expected, &msg, &output_key).unwrap();
+
+    // below threshold
+    let package = frost_signing_package(
+        vec![signers[1].commit()],
+        &digest.as_hex().to_string(),
+        true,
+    );
+    assert!(signers[1].sign(&package.value.unwrap()).is_none());
+
+    // nonces can't sign twice
+    let commitments = vec![signers[0].commit(), signers[1].commit()];
+    let package = frost_signing_package(commitments, &digest.as_hex().to_string(), true);
+    let package = package.value.unwrap();
+    assert!(signers[0].sign(&package).is_some());
+    assert!(signers[0].sign(&package).is_none());
+
+    // wrong and missing shares are blamed on their signer by the coordinator
+    let commitments = vec![signers[0].commit(), signers[1].commit()];
+    let package = frost_signing_package(commitments, &digest.as_hex().to_string(), false);
+    let package = package.value.unwrap();
+    let mut shares: Vec<String> = vec![
+        signers[0].sign(&package).unwrap(),
+        signers[1].sign(&package).unwrap(),
+    ];
+    let public_key_package = signers[0].public_key_package();
+    let missing = frost_aggregate(&package, shares[..1].to_vec(), &public_key_package);
+    assert!(missing.value.is_none());
+    assert_eq!(missing.culprits, vec![2]);
+    assert_eq!(
+        missing.errors,
+        vec!["participant 2: missing signature share"]
+    );
+    shares[1] = json!({ "identifier": 2, "share": "01".repeat(32) }).to_string();
+    shares.push("invalid".to_string());
+    let wrong = frost_aggregate(&package, shares, &public_key_package);
+    assert!(wrong.value.is_none());
+    assert_eq!(wrong.culprits, vec![2]);
+    assert_eq!(
+        wrong.errors,
+        vec![
+            "participant 2: invalid signature share",
+            "package 2: invalid signature share"
+        ]
+    );
+
+    // invalid and duplicate commitments
+    let commitment = signers[2].commit();
+    let mut invalid: serde_json::Value = serde_json::from_str(&commitment).unwrap();
+    invalid["hiding"] = json!("04".repeat(33));
+    let package = frost_signing_package(
+        vec![
+            signers[0].commit(),
+            commitment.clone(),
+            commitment,
+            invalid.to_string(),
+            "{}".to_string(),
+        ],
+        &digest.as_hex().to_string(),
+        false,
+    );
+    assert!(package.value.is_none());
+    assert_eq!(package.culprits, vec![3]);
+    assert_eq!(
+        package.errors,
+        vec![
+            "participant 3: duplicate commitment",
+            "package 4: invalid commitment"
+        ]
+    );
+
+    // distributed key generation, 3 of 4
+    let round1: Vec<_> = (1..=4).map(|id| frost_dkg_part1(id, 3, 4)).collect();
+    let packages1: Vec<String> = round1.iter().map(|r| r.package.clone()).collect();
+    let round2: Vec<_> = round1
+        .iter()
+        .map(|r| frost_dkg_part2(&r.secret_package, packages1.clone()))
+        .collect();
+    assert!(round2.iter().all(|r| r.culprits.is_empty()));
+    let received = |recipient: usize| -> Vec<String> {
+        let recipient = json!(recipient);
+        round2
+            .iter()
+            .flat_map(|r| r.packages.iter())
+            .filter(|p| {
+                serde_json::from_str::<serde_json::Value>(p).unwrap()["recipient"] == recipient
+            })
+            .cloned()
+            .collect()
+    };
+    let signers: Vec<FrostSigner> = round2
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let key_share = frost_dkg_part3(
+                r.secret_package.as_ref().unwrap(),
+                packages1.clone(),
+                received(i + 1),
+            );
+            FrostSigner::new(&key_share.value.unwrap())
+        })
+        .collect();
+    assert!(signers
+        .iter()
+        .all(|s| s.public_key_package() == signers[0].public_key_package()));
+    let group_key = XOnlyPublicKey::from_str(&signers[0].group_public_key()).unwrap();
+    let sig = sign(&[&signers[3], &signers[0], &signers[2]], false);
+    secp.verify_schnorr(&sig, &msg, &group_key).unwrap();
+    let output_key = group_key.tap_tweak(&secp, None).0.to_inner();
+    let sig = sign(&[&signers[1], &signers[2], &signers[3]], true);
+    secp.verify_schnorr(&sig, &msg, &output_key).unwrap();
+
+    // duplicate, invalid and missing round 1 packages are blamed on their participant
+    let mut tampered: serde_json::Value = serde_json::from_str(&packages1[2]).unwrap();
+    tampered["proof"]["mu"] = json!("01".repeat(32));
+    let bad_packages1 = vec![
+        packages1[1].clone(),
+        packages1[1].clone(),
+        tampered.to_string(),
+        "invalid".to_string(),
+    ];
+    let bad_round2 = frost_dkg_part2(&round1[0].secret_package, bad_packages1.clone());
+    assert!(bad_round2.secret_package.is_none() && bad_round2.packages.is_empty());
+    assert_eq!(bad_round2.culprits, vec![2, 3, 4]);
+    assert_eq!(
+        bad_round2.errors,
+        vec![
+            "participant 2: duplicate round 1 package",
+            "participant 3: invalid commitments or proof of knowledge",
+            "participant 4: missing round 1 package"
+        ]
+    );
+    let secret_package = round2[0].secret_package.as_ref().unwrap();
+    let bad_key_share = frost_dkg_part3(secret_package, bad_packages1, received(1));
+    assert!(bad_key_share.value.is_none());
+    assert_eq!(bad_key_share.culprits, vec![2, 3, 4]);
+
+    // so are duplicate, invalid, misdirected and missing round 2 packages
+    let mut shares = received(1);
+    let mut invalid: serde_json::Value = serde_json::from_str(&shares[1]).unwrap();
+    invalid["share"] = json!("01".repeat(32));
+    shares[1] = invalid.to_string();
+    shares.push(shares[0].clone());
+    let bad_key_share = frost_dkg_part3(secret_package, packages1.clone(), shares);
+    assert_eq!(bad_key_share.culprits, vec![2, 3]);
+    assert_eq!(
+        bad_key_share.errors,
+        vec![
+            "participant 3: invalid share",
+            "participant 2: duplicate round 2 package"
+        ]
+    );
+    let mut shares = received(1);
+    shares[0] = received(3)[1].clone();
+    shares.pop();
+    let bad_key_share = frost_dkg_part3(secret_package, packages1, shares);
+    assert!(bad_key_share.value.is_none());
+    assert_eq!(bad_key_share.culprits, vec![2, 4]);
+    assert_eq!(
+        bad_key_share.errors,
+        vec![
+            "participant 2: share of another participant",
+            "participant 4: missing round 2 package"
+        ]
+    );
+}
+
+/// Minimal encoding of a script number
+fn script_num(n: i64) -> Vec<u8> {
+    let mut abs = n.unsigned_abs();
+    let mut bytes = vec![];
+    while abs > 0 {
+        bytes.push(abs as u8);
+        abs >>= 8;
+    }
+    match bytes.last() {
+        Some(last) if last & 0x80 != 0 => bytes.push(if n < 0 { 0x80 } else { 0 }),
+        Some(_) if n < 0 => *bytes.last_mut().unwrap() |= 0x80,
+        _ => {}
+    }
+    bytes
+}
+
+fn parse_script_num(bytes: &[u8]) -> i64 {
+    assert!(bytes.len() <= 5, "script number overflow");
+    let mut n = bytes
+        .iter()
+        .enumerate()
+        .fold(0i64, |n, (i, b)| n | (*b as i64) << (8 * i));
+    match bytes.last() {
+        Some(last) if last & 0x80 != 0 => {
+            n &= !(0x80i64 << (8 * (bytes.len() - 1)));
+            -n
+        }
+        _ => n,
+    }
+}
+
+fn cast_to_bool(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .enumerate()
+        .any(|(i, b)| *b != 0 && !(i == bytes.len() - 1 && *b == 0x80))
+}
+
+/// Run the witness of a P2WSH or taproot script path input, as libbitcoinconsensus would, for
+/// the opcodes miniscript and the timelock scripts use. Applies the segwit v0 and tapscript rules
+/// with the MINIMALIF, NULLFAIL, NULLDUMMY, LOW_S and clean stack policies, and panics with the
+/// reason when the spend is invalid.
+fn verify_script_spend(tx: &Transaction, input: usize, prevouts: &[TxOut]) {
+    use bitcoin::opcodes::all::*;
+
+    let secp = Secp256k1::new();
+    let script_pubkey = &prevouts[input].script_pubkey;
+    let mut stack = tx.input[input].witness.to_vec();
+    let (script, leaf_hash) = if script_pubkey.is_p2wsh() {
+        let script = ScriptBuf::from_bytes(stack.pop().expect("empty witness"));
+        assert_eq!(
+            script_pubkey,
+            &ScriptBuf::new_p2wsh(&script.wscript_hash()),
+            "witness script mismatch"
+        );
+        assert!(script.len() <= 3600, "witness script too large");
+        assert!(
+            stack.iter().all(|item| item.len() <= 80),
+            "witness item over 80 bytes"
+        );
+        (script, None)
+    } else if script_pubkey.is_p2tr() {
+        let control_block =
+            taproot::ControlBlock::decode(&stack.pop().expect("empty witness")).unwrap();
+        let script = ScriptBuf::from_bytes(stack.pop().expect("missing tapscript"));
+        let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).unwrap();
+        assert!(
+            control_block.verify_taproot_commitment(&secp, output_key, &script),
+            "invalid control block"
+        );
+        let leaf_hash = TapLeafHash::from_script(&script, control_block.leaf_version);
+        (script, Some(leaf_hash))
+    } else {
+        panic!("not a script spend");
+    };
+
+    // valid signature of the key, false for the empty signature
+    let check_sig = |sig: &[u8], key: &[u8]| -> bool {
+        if sig.is_empty() {
+            return false;
+        }
+        match leaf_hash {
+            None => {
+                let sig = bitcoin::ecdsa::Signature::from_slice(sig).expect("invalid signature");
+                let mut low_s = sig.sig;
+                low_s.normalize_s();
+                assert_eq!(low_s, sig.sig, "high s signature");
+                let key = PublicKey::from_slice(key).expect("invalid public key");
+                let sighash = sighash::SighashCache::new(tx)
+                    .p2wsh_signature_hash(input, &script, prevouts[input].value, sig.hash_ty)
+                    .unwrap();
+                let msg = Message::from_digest(sighash.to_byte_array());
+                secp.verify_ecdsa(&msg, &sig.sig, &key.inner).is_ok()
+            }
+            Some(leaf_hash) => {
+                let sig = taproot::Signature::from_slice(sig).expect("invalid signature");
+                let key = XOnlyPublicKey::from_slice(key).expect("invalid public key");
+                let sighash = sighash::SighashCache::new(tx)
+                    .taproot_script_spend_signature_hash(
+                        input,
+                        &sighash::Prevouts::All(prevouts),
+                        leaf_hash,
+                        sig.hash_ty,
+                    )
+                    .unwrap();
+                let msg = Message::from_digest(sighash.to_byte_array());
+                secp.verify_schnorr(&sig.sig, &msg, &key).is_ok()
+            }
+        }
+    };
+    let pop = |stack: &mut Vec<Vec<u8>>| stack.pop().expect("stack underflow");
+
+    let mut exec: Vec<bool> = vec![];
+    let mut alt: Vec<Vec<u8>> = vec![];
+    let mut ops = 0;
+    for instruction in script.instructions() {
+        let executing = exec.iter().all(|e| *e);
+        let op = match instruction.expect("invalid push") {
+            script::Instruction::PushBytes(bytes) => {
+                if executing {
+                    stack.push(bytes.as_bytes().to_vec());
+                }
+                continue;
+            }
+            script::Instruction::Op(op) => op,
+        };
+        if op.to_u8() > OP_PUSHNUM_16.to_u8() {
+            ops += 1;
+        }
+        match op {
+            OP_IF | OP_NOTIF => {
+                let mut condition = false;
+                if executing {
+                    let top = pop(&mut stack);
+                    assert!(top.is_empty() || top == [1], "MINIMALIF");
+                    condition = (top == [1]) == (op == OP_IF);
+                }
+                exec.push(condition);
+                continue;
+            }
+            OP_ELSE => {
+                let last = exec.last_mut().expect("ELSE without IF");
+                *last = !*last;
+                continue;
+            }
+            OP_ENDIF => {
+                exec.pop().expect("ENDIF without IF");
+                continue;
+            }
+            _ if !executing => continue,
+            _ => {}
+        }
+
+        let verify = matches!(
+            op,
+            OP_CHECKSIGVERIFY | OP_CHECKMULTISIGVERIFY | OP_EQUALVERIFY | OP_NUMEQUALVERIFY
+        );
+        let result = match op {
+            OP_PUSHNUM_NEG1 => Some(-1),
+            _ if op.to_u8() >= OP_PUSHNUM_1.to_u8() && op.to_u8() <= OP_PUSHNUM_16.to_u8() => {
+                Some((op.to_u8() - OP_PUSHNUM_1.to_u8() + 1) as i64)
+            }
+            OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+                let key = pop(&mut stack);
+                let sig = pop(&mut stack);
+                let valid = check_sig(&sig, &key);
+                assert!(valid || sig.is_empty(), "NULLFAIL");
+                Some(valid as i64)
+            }
+            OP_CHECKSIGADD => {
+                assert!(leaf_hash.is_some(), "CHECKSIGADD outside tapscript");
+                let key = pop(&mut stack);
+                let n = parse_script_num(&pop(&mut stack));
+                let sig = pop(&mut stack);
+                let valid = check_sig(&sig, &key);
+                assert!(valid || sig.is_empty(), "NULLFAIL");
+                Some(n + valid as i64)
+            }
+            OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => {
+                assert!(leaf_hash.is_none(), "CHECKMULTISIG in tapscript");
+                let n = parse_script_num(&pop(&mut stack)) as usize;
+                ops += n;
+                let mut keys: Vec<Vec<u8>> = (0..n).map(|_| pop(&mut stack)).collect();
+                keys.reverse();
+                let k = parse_script_num(&pop(&mut stack)) as usize;
+                let mut sigs: Vec<Vec<u8>> = (0..k).map(|_| pop(&mut stack)).collect();
+                sigs.reverse();
+                assert!(pop(&mut stack).is_empty(), "NULLDUMMY");
+                // signatures match keys in the same order
+                let mut keys = keys.iter();
+                let valid = sigs
+                    .iter()
+                    .all(|sig| keys.by_ref().any(|key| check_sig(sig, key)));
+                assert!(valid || sigs.iter().all(Vec::is_empty), "NULLFAIL");
+                Some(valid as i64)
+            }
+            OP_IFDUP => {
+                let top = stack.last().expect("stack underflow").clone();
+                if cast_to_bool(&top) {
+                    stack.push(top);
+                }
+                None
+            }
+            OP_SIZE => Some(stack.last().expect("stack underflow").len() as i64),
+            OP_EQUAL | OP_EQUALVERIFY => Some((pop(&mut stack) == pop(&mut stack)) as i64),
+            OP_SHA256 => {
+                let hash = sha256::Hash::hash(&pop(&mut stack));
+                stack.push(hash.to_byte_array().to_vec());
+                None
+            }
+            OP_VERIFY => {
+                assert!(cast_to_bool(&pop(&mut stack)), "VERIFY failed");
+                None
+            }
+            OP_DROP => {
+                pop(&mut stack);
+                None
+            }
+            OP_TOALTSTACK => {
+                alt.push(pop(&mut stack));
+                None
+            }
+            OP_FROMALTSTACK => {
+                stack.push(alt.pop().expect("altstack underflow"));
+                None
+            }
+            OP_0NOTEQUAL => Some((parse_script_num(&pop(&mut stack)) != 0) as i64),
+            OP_ADD => Some(parse_script_num(&pop(&mut stack)) + parse_script_num(&pop(&mut stack))),
+            OP_NUMEQUAL | OP_NUMEQUALVERIFY => Some(
+                (parse_script_num(&pop(&mut stack)) == parse_script_num(&pop(&mut stack))) as i64,
+            ),
+            OP_CLTV => {
+                let n = parse_script_num(stack.last().expect("stack underflow"));
+                let lock_time = tx.lock_time.to_consensus_u32() as i64;
+                assert!(n >= 0, "negative locktime");
+                assert_eq!(
+                    n < 500_000_000,
+                    lock_time < 500_000_000,
+                    "CLTV type mismatch"
+                );
+                assert!(lock_time >= n, "CLTV locktime not reached");
+                assert_ne!(
+                    tx.input[input].sequence,
+                    Sequence::MAX,
+                    "CLTV with final sequence"
+                );
+                None
+            }
+            OP_CSV => {
+                let n = parse_script_num(stack.last().expect("stack underflow"));
+                assert!(n >= 0, "negative sequence");
+                if n & (1 << 31) == 0 {
+                    let sequence = tx.input[input].sequence.to_consensus_u32() as i64;
+                    assert!(tx.version.0 >= 2, "CSV with version 1");
+                    assert_eq!(sequence & (1 << 31), 0, "CSV with disabled sequence");
+                    assert_eq!(n & (1 << 22), sequence & (1 << 22), "CSV type mismatch");
+                    assert!(sequence & 0xffff >= n & 0xffff, "CSV sequence not reached");
+                }
+                None
+            }
+            _ => panic!("unsupported opcode {op:?}"),
+        };
+        match (result, verify) {
+            (Some(result), true) => assert!(result != 0, "{op:?} failed"),
+            (Some(result), false) => stack.push(script_num(result)),
+            (None, _) => {}
+        }
+        assert!(stack.len() + alt.len() <= 1000, "stack size limit");
+    }
+    if leaf_hash.is_none() {
+        assert!(ops <= 201, "opcode limit");
+    }
+    assert!(exec.is_empty(), "unbalanced IF");
+    assert_eq!(stack.len(), 1, "clean stack");
+    assert!(cast_to_bool(&stack[0]), "script returned false");
+}
+
+#[test]
+fn test_miniscript() {
+    let policy = "or(thresh(2,pk(A),pk(B),pk(C)),and(pk(D),older(26280)))";
+    assert_eq!(
+        miniscript_compile(policy, MiniscriptType::Wsh),
+        "or_d(multi(2,A,B,C),and_v(v:pk(D),older(26280)))"
+    );
+    assert_eq!(
+        miniscript_compile(policy, MiniscriptType::Tr),
+        "or_d(multi_a(2,A,B,C),and_v(v:pk(D),older(26280)))"
+    );
+    assert_eq!(
+        miniscript_compile("or(99@pk(A), 1@pk(B))", MiniscriptType::Wsh),
+        "or_d(pk(A),pk(B))"
+    );
+    assert_eq!(
+        miniscript_compile("or(and(pk(A),after(100)),pk(B))", MiniscriptType::Wsh),
+        "or_d(pk(B),and_v(v:pk(A),after(100)))"
+    );
+    assert_eq!(
+        miniscript_compile("thresh(2,pk(A),pk(B),after(100))", MiniscriptType::Wsh),
+        "thresh(2,pk(A),a:pk(B),aln:after(100))"
+    );
+    assert!(std::panic::catch_unwind(|| miniscript_compile(
+        "or(pk(A),pk(A))",
+        MiniscriptType::Wsh
+    ))
+    .is_err());
+    // a transaction has one nLockTime and one nSequence per input, so a branch can't need both
+    // a height and a time lock of the same kind
+    for policy in [
+        "and(after(100),after(500000001))",
+        "and(pk(A),and(older(10),older(4194305)))",
+        "thresh(2,pk(A),after(100),after(500000001))",
+    ] {
+        assert!(
+            std::panic::catch_unwind(|| miniscript_compile(policy, MiniscriptType::Wsh)).is_err()
+        );
+    }
+    assert_eq!(
+        miniscript_compile(
+            "or(and(pk(A),after(100)),and(pk(B),after(500000001)))",
+            MiniscriptType::Wsh
+        ),
+        "or_i(and_v(v:pk(A),after(100)),and_v(v:pk(B),after(500000001)))"
+    );
+    assert_eq!(
+        miniscript_compile(
+            "thresh(1,pk(A),after(100),after(500000001))",
+            MiniscriptType::Wsh
+        ),
+        "thresh(1,pk(A),aln:after(100),aln:after(500000001))"
+    );
+    assert_eq!(
+        miniscript_compile("and(after(100),older(4194305))", MiniscriptType::Wsh),
+        "and_v(v:after(100),older(4194305))"
+    );
+
+    // P2WSH scripts are limited to 201 opcodes and 3600 bytes, tapscript to neither
+    let keys = |n: usize, prefix: &str| -> String {
+        (0..n)
+            .map(|i| format!("pk({prefix}{i})"))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let many_keys = format!("thresh(2,{})", keys(60, "K"));
+    assert!(
+        std::panic::catch_unwind(|| miniscript_compile(&many_keys, MiniscriptType::Wsh)).is_err()
+    );
+    assert!(miniscript_compile(&many_keys, MiniscriptType::Tr).starts_with("multi_a(2,K0,K1,"));
+    let large = format!(
+        "thresh(1,{})",
+        (0..6)
+            .map(|i| format!("thresh(2,{})", keys(20, &format!("K{i}_"))))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    assert!(std::panic::catch_unwind(|| miniscript_compile(&large, MiniscriptType::Wsh)).is_err());
+    miniscript_compile(&large, MiniscriptType::Tr);
+
+    let a = HDWallet::new(
+        1,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let b = HDWallet::new(
+        1,
+        Some("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_string()),
+    );
+    let c = HDWallet::new(
+        1,
+        Some(
+            "legal winner thank year wave sausage worth useful legal winner thank yellow"
+                .to_string(),
+        ),
+    );
+    let d = HDWallet::new(
+        1,
+        Some("visit frame clay clap often dance pair cousin peanut thumb fine foster".to_string()),
+    );
+    let txid = "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b";
+    let spend = |address: &str, lock_time: u32, sequence: Sequence| Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::from_consensus(lock_time),
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_str(txid).unwrap(),
+                vout: 1,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(99000),
+            script_pubkey: Address::from_str(address)
+                .unwrap()
+                .assume_checked()
+                .script_pubkey(),
+        }],
+    };
+    let prevout = || MultisigPrevout {
+        txid: txid.to_string(),
+        vout: 1,
+        amount: 0.001,
+        is_change: false,
+        index: 0,
+    };
+
+    // 2-of-3, or D alone after 26280 blocks
+    let script_type = MultisigScriptType::P2wsh;
+    let policy = format!(
+        "or(thresh(2,pk({}),pk({}),pk({})),and(pk({}),older(26280)))",
+        a.bip48_xpub(script_type),
+        b.bip48_xpub(script_type),
+        c.bip48_xpub(script_type),
+        d.bip48_xpub(script_type)
+    );
+    let ma = MiniscriptWallet::new(&a, &policy, MiniscriptType::Wsh);
+    let md = MiniscriptWallet::new(&d, &policy, MiniscriptType::Wsh);
+    let descriptor = ma.descriptor(false);
+    let (body, checksum) = descriptor.split_once('#').unwrap();
+    assert!(body.starts_with("wsh(or_d(multi(2,["));
+    assert!(body.ends_with("/0/*),older(26280))))"));
+    assert_eq!(descriptor_checksum(body), checksum);
+    assert_eq!(md.descriptor(true), ma.descriptor(true));
+    let address = ma.address(false, 0);
+    assert!(address.starts_with("tb1q"));
+    assert_eq!(md.address(false, 0), address);
+
+    let unsigned_tx = spend(&address, 0, Sequence::ENABLE_RBF_NO_LOCKTIME);
+    let psbt = ma.create_psbt(
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout()],
+    );
+    let script = Psbt::from_str(&psbt).unwrap().inputs[0]
+        .witness_script
+        .clone()
+        .unwrap();
+    let asm = script.to_asm_string();
+    assert!(asm.starts_with("OP_PUSHNUM_2 OP_PUSHBYTES_33"));
+    assert!(asm.contains("OP_PUSHNUM_3 OP_CHECKMULTISIG OP_IFDUP OP_NOTIF OP_PUSHBYTES_33"));
+    assert!(asm.ends_with("OP_CHECKSIGVERIFY OP_PUSHBYTES_2 a866 OP_CSV OP_ENDIF"));
+
+    let utxo = Psbt::from_str(&psbt).unwrap().inputs[0]
+        .witness_utxo
+        .clone()
+        .unwrap();
+
+    // the recovery key alone can't spend before the timelock
+    let signed_d = md.sign_psbt(&psbt);
+    assert_eq!(signed_d.signed_inputs, vec![0]);
+    assert!(md.finalize_psbt(&signed_d.psbt, vec![]).is_none());
+    assert!(md
+        .finalize_psbt(&signed_d.psbt, vec!["zz".to_string()])
+        .is_none());
+
+    let mb = MiniscriptWallet::new(&b, &policy, MiniscriptType::Wsh);
+    let psbt = combine_psbts(vec![
+        ma.sign_psbt(&psbt).psbt,
+        mb.sign_psbt(&psbt).psbt,
+        signed_d.psbt,
+    ]);
+    let tx: Transaction = consensus::deserialize(
+        &Vec::<u8>::from_hex(&ma.finalize_psbt(&psbt, vec![]).unwrap()).unwrap(),
+    )
+    .unwrap();
+    verify_script_spend(&tx, 0, std::slice::from_ref(&utxo));
+    // CHECKMULTISIG needs the signatures in key order
+    let mut swapped = tx.clone();
+    let mut items = swapped.input[0].witness.to_vec();
+    items.swap(1, 2);
+    swapped.input[0].witness = Witness::from_slice(&items);
+    let prevouts = [utxo.clone()];
+    assert!(std::panic::catch_unwind(|| verify_script_spend(&swapped, 0, &prevouts)).is_err());
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 4);
+    assert!(witness.nth(0).unwrap().is_empty());
+    assert_eq!(witness.last().unwrap(), script.as_bytes());
+
+    let unsigned_tx = spend(&address, 0, Sequence::from_height(26280));
+    let psbt = md.create_psbt(
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout()],
+    );
+    let tx: Transaction = consensus::deserialize(
+        &Vec::<u8>::from_hex(&md.finalize_psbt(&md.sign_psbt(&psbt).psbt, vec![]).unwrap())
+            .unwrap(),
+    )
+    .unwrap();
+    verify_script_spend(&tx, 0, &[utxo]);
+    let witness = tx.input[0].witness.to_vec();
+    assert_eq!(witness.len(), 5);
+    assert!(witness[1..4].iter().all(Vec::is_empty));
+    assert_eq!(witness[4], script.to_bytes());
+    let sighash = sighash::SighashCache::new(&unsigned_tx)
+        .p2wsh_signature_hash(0, &script, Amount::from_sat(100000), EcdsaSighashType::All)
+        .unwrap();
+    let sig = ecdsa::Signature::from_der(&witness[0][..witness[0].len() - 1]).unwrap();
+    let recovery_key = Psbt::from_str(&psbt).unwrap().inputs[0]
+        .bip32_derivation
+        .iter()
+        .find(|(_, (fingerprint, _))| {
+            d.bip48_xpub(MultisigScriptType::P2wsh)[1..9] == fingerprint.to_string()
+        })
+        .map(|(pubkey, _)| *pubkey)
+        .unwrap();
+    Secp256k1::new()
+        .verify_ecdsa(
+            &Message::from_digest(sighash.to_byte_array()),
+            &sig,
+            &recovery_key,
+        )
+        .unwrap();
+
+    // tapscript, A with a preimage, or B after block 100
+    let preimage = [7u8; 32];
+    let hash = sha256::Hash::hash(&preimage);
+    let script_type = MultisigScriptType::P2tr;
+    let policy = format!(
+        "or(and(pk({}),sha256({hash})),and(pk({}),after(100)))",
+        a.bip48_xpub(script_type),
+        b.bip48_xpub(script_type)
+    );
+    let ta = MiniscriptWallet::new(&a, &policy, MiniscriptType::Tr);
+    let tb = MiniscriptWallet::new(&b, &policy, MiniscriptType::Tr);
+    assert!(ta.descriptor(false).starts_with(
+        "tr(50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0,or_i(and_v(v:pk(["
+    ));
+    let address = ta.address(false, 0);
+    assert!(address.starts_with("tb1p"));
+
+    let unsigned_tx = spend(&address, 0, Sequence::ENABLE_RBF_NO_LOCKTIME);
+    let psbt = ta.create_psbt(
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout()],
+    );
+    let utxo = Psbt::from_str(&psbt).unwrap().inputs[0]
+        .witness_utxo
+        .clone()
+        .unwrap();
+    let signed = ta.sign_psbt(&psbt).psbt;
+    assert!(ta.finalize_psbt(&signed, vec![]).is_none());
+    let tx: Transaction = consensus::deserialize(
+        &Vec::<u8>::from_hex(
+            &ta.finalize_psbt(&signed, vec![preimage.as_hex().to_string()])
+                .unwrap(),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    verify_script_spend(&tx, 0, std::slice::from_ref(&utxo));
+    let witness = tx.input[0].witness.to_vec();
+    assert_eq!(witness.len(), 5);
+    assert_eq!(witness[0], preimage);
+    assert_eq!(witness[2], [1]);
+
+    let input = &Psbt::from_str(&psbt).unwrap().inputs[0];
+    let (control_block, (leaf, _)) = input.tap_scripts.iter().next().unwrap();
+    assert_eq!(witness[3], leaf.to_bytes());
+    assert_eq!(witness[4], control_block.serialize());
+    let output_key = XOnlyPublicKey::from_slice(
+        &input
+            .witness_utxo
+            .as_ref()
+            .unwrap()
+            .script_pubkey
+            .as_bytes()[2..],
+    )
+    .unwrap();
+    assert!(control_block.verify_taproot_commitment(&Secp256k1::new(), output_key, leaf));
+    let leaf_hash = TapLeafHash::from_script(leaf, taproot::LeafVersion::TapScript);
+    let sighash = sighash::SighashCache::new(&unsigned_tx)
+        .taproot_script_spend_signature_hash(
+            0,
+            &sighash::Prevouts::All(&[input.witness_utxo.clone().unwrap()]),
+            leaf_hash,
+            TapSighashType::Default,
+        )
+        .unwrap();
+    let key_a = input
+        .tap_key_origins
+        .iter()
+        .find(|(_, (_, (fingerprint, _)))| {
+            a.bip48_xpub(script_type)[1..9] == fingerprint.to_string()
+        })
+        .map(|(key, _)| *key)
+        .unwrap();
+    Secp256k1::new()
+        .verify_schnorr(
+            &schnorr::Signature::from_slice(&witness[1]).unwrap(),
+            &Message::from_digest(sighash.to_byte_array()),
+            &key_a,
+        )
+        .unwrap();
+
+    let unsigned_tx = spend(&address, 100, Sequence::ENABLE_LOCKTIME_NO_RBF);
+    let psbt = tb.create_psbt(
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout()],
+    );
+    let tx: Transaction = consensus::deserialize(
+        &Vec::<u8>::from_hex(&tb.finalize_psbt(&tb.sign_psbt(&psbt).psbt, vec![]).unwrap())
+            .unwrap(),
+    )
+    .unwrap();
+    verify_script_spend(&tx, 0, &[utxo]);
+    let witness = tx.input[0].witness.to_vec();
+    assert_eq!(witness.len(), 4);
+    assert_eq!(witness[0].len(), 64);
+    assert!(witness[1].is_empty());
+}
+
+#[test]
+fn test_timelock() {
+    let wallet = HDWallet::new(
+        1,
+        Some("visit frame clay clap often dance pair cousin peanut thumb fine foster".to_string()),
+    );
+    let secp = Secp256k1::new();
+    let priv_hex = wallet.bip84_priv_hex();
+    let private_key = SecretKey::from_str(&priv_hex).unwrap();
+    let pubkey = PublicKey::new(private_key.public_key(&secp));
+    let pubkey_hex = pubkey.to_string();
+
+    let script = timelock_script(
+        &pubkey_hex,
+        TimelockType::AbsoluteHeight,
+        840000,
+        TimelockScriptType::P2wsh,
+    );
+    assert_eq!(
+        ScriptBuf::from_hex(&script).unwrap().to_asm_string(),
+        format!("OP_PUSHBYTES_33 {pubkey_hex} OP_CHECKSIGVERIFY OP_PUSHBYTES_3 40d10c OP_CLTV")
+    );
+    let script = timelock_script(
+        &pubkey_hex,
+        TimelockType::RelativeTime,
+        30 * 24 * 3600,
+        TimelockScriptType::P2tr,
+    );
+    // 2592000 seconds are 5063 intervals of 512 seconds, rounded up, with the type flag
+    assert_eq!(
+        ScriptBuf::from_hex(&script).unwrap().to_asm_string(),
+        format!(
+            "OP_PUSHBYTES_32 {} OP_CHECKSIGVERIFY OP_PUSHBYTES_3 c71340 OP_CSV",
+            &pubkey_hex[2..]
+        )
+    );
+    assert!(std::panic::catch_unwind(|| timelock_script(
+        &pubkey_hex,
+        TimelockType::AbsoluteHeight,
+        500_000_000,
+        TimelockScriptType::P2wsh
+    ))
+    .is_err());
+    assert!(std::panic::catch_unwind(|| timelock_script(
+        &pubkey_hex,
+        TimelockType::RelativeHeight,
+        65536,
+        TimelockScriptType::P2wsh
+    ))
+    .is_err());
+    // `0 CLTV` and `0 CSV` can never be spent
+    for timelock_type in [
+        TimelockType::AbsoluteHeight,
+        TimelockType::RelativeHeight,
+        TimelockType::RelativeTime,
+    ] {
+        assert!(std::panic::catch_unwind(|| timelock_address(
+            &pubkey_hex,
+            timelock_type,
+            0,
+            TimelockScriptType::P2tr,
+            1
+        ))
+        .is_err());
+    }
+
+    let txid = "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b";
+    let cases = [
+        (
+            TimelockType::AbsoluteHeight,
+            840000,
+            TimelockScriptType::P2wsh,
+        ),
+        (
+            TimelockType::AbsoluteTime,
+            1_735_689_600,
+            TimelockScriptType::P2tr,
+        ),
+        (
+            TimelockType::RelativeHeight,
+            26280,
+            TimelockScriptType::P2wsh,
+        ),
+        (
+            TimelockType::RelativeTime,
+            30 * 24 * 3600,
+            TimelockScriptType::P2tr,
+        ),
+    ];
+    for (timelock_type, value, script_type) in cases {
+        let address = timelock_address(&pubkey_hex, timelock_type, value, script_type, 1);
+        let script_pubkey = Address::from_str(&address)
+            .unwrap()
+            .assume_checked()
+            .script_pubkey();
+        let unsigned_tx = Transaction {
+            version: transaction::Version::ONE,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_str(txid).unwrap(),
+                    vout: 0,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(99000),
+                script_pubkey: script_pubkey.clone(),
+            }],
+        };
+        let tx_hex = timelock_spend(
+            &priv_hex,
+            timelock_type,
+            value,
+            script_type,
+            &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+            vec![Prevout {
+                txid: txid.to_string(),
+                vout: 0,
+                amount: 0.001,
+            }],
+        );
+        let tx: Transaction =
+            consensus::deserialize(&Vec::<u8>::from_hex(&tx_hex).unwrap()).unwrap();
+        let sequence = tx.input[0].sequence;
+        match timelock_type {
+            TimelockType::AbsoluteHeight | TimelockType::AbsoluteTime => {
+                assert_eq!(tx.lock_time.to_consensus_u32(), value);
+                assert!(sequence.enables_absolute_lock_time());
+            }
+            TimelockType::RelativeHeight => {
+                assert_eq!(tx.version, transaction::Version::TWO);
+                assert_eq!(sequence, Sequence::from_height(26280));
+            }
+            TimelockType::RelativeTime => {
+                assert_eq!(tx.version, transaction::Version::TWO);
+                assert_eq!(sequence, Sequence::from_512_second_intervals(5063));
+            }
+        }
+
+        let witness = tx.input[0].witness.to_vec();
+        let script = ScriptBuf::from_hex(&timelock_script(
+            &pubkey_hex,
+            timelock_type,
+            value,
+            script_type,
+        ))
+        .unwrap();
+        assert_eq!(witness[1], script.to_bytes());
+        let utxo = TxOut {
+            value: Amount::from_sat(100000),
+            script_pubkey,
+        };
+        verify_script_spend(&tx, 0, std::slice::from_ref(&utxo));
+        let mut cache = sighash::SighashCache::new(&tx);
+        match script_type {
+            TimelockScriptType::P2wsh => {
+                assert_eq!(witness.len(), 2);
+                let sighash = cache
+                    .p2wsh_signature_hash(0, &script, utxo.value, EcdsaSighashType::All)
+                    .unwrap();
+                let sig = ecdsa::Signature::from_der(&witness[0][..witness[0].len() - 1]).unwrap();
+                secp.verify_ecdsa(
+                    &Message::from_digest(sighash.to_byte_array()),
+                    &sig,
+                    &pubkey.inner,
+                )
+                .unwrap();
+            }
+            TimelockScriptType::P2tr => {
+                assert_eq!(witness.len(), 3);
+                let control_block = taproot::ControlBlock::decode(&witness[2]).unwrap();
+                let output_key =
+                    XOnlyPublicKey::from_slice(&utxo.script_pubkey.as_bytes()[2..]).unwrap();
+                assert!(control_block.verify_taproot_commitment(&secp, output_key, &script));
+                let leaf_hash = TapLeafHash::from_script(&script, taproot::LeafVersion::TapScript);
+                let sighash = cache
+                    .taproot_script_spend_signature_hash(
+                        0,
+                        &sighash::Prevouts::All(&[utxo]),
+                        leaf_hash,
+                        TapSighashType::Default,
+                    )
+                    .unwrap();
+                secp.verify_schnorr(
+                    &schnorr::Signature::from_slice(&witness[0]).unwrap(),
+                    &Message::from_digest(sighash.to_byte_array()),
+                    &pubkey.inner.x_only_public_key().0,
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    // inheritance, the owner at any time or the heir after 26280 blocks
+    let owner = HDWallet::new(
+        1,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let owner_priv_hex = owner.bip84_priv_hex();
+    let owner_hex = PublicKey::new(
+        SecretKey::from_str(&owner_priv_hex)
+            .unwrap()
+            .public_key(&secp),
+    )
+    .to_string();
+    let script = inheritance_script(
+        &owner_hex,
+        &pubkey_hex,
+        TimelockType::RelativeHeight,
+        26280,
+        TimelockScriptType::P2wsh,
+    );
+    assert_eq!(
+        ScriptBuf::from_hex(&script).unwrap().to_asm_string(),
+        format!(
+            "OP_PUSHBYTES_33 {owner_hex} OP_CHECKSIG OP_IFDUP OP_NOTIF OP_PUSHBYTES_33 \
+             {pubkey_hex} OP_CHECKSIGVERIFY OP_PUSHBYTES_2 a866 OP_CSV OP_ENDIF"
+        )
+    );
+    assert!(std::panic::catch_unwind(|| inheritance_script(
+        &owner_hex,
+        &owner_hex,
+        TimelockType::RelativeHeight,
+        26280,
+        TimelockScriptType::P2wsh
+    ))
+    .is_err());
+
+    for script_type in [TimelockScriptType::P2wsh, TimelockScriptType::P2tr] {
+        let address = inheritance_address(
+            &owner_hex,
+            &pubkey_hex,
+            TimelockType::RelativeHeight,
+            26280,
+            script_type,
+            1,
+        );
+        let utxo = TxOut {
+            value: Amount::from_sat(100000),
+            script_pubkey: Address::from_str(&address)
+                .unwrap()
+                .assume_checked()
+                .script_pubkey(),
+        };
+        let unsigned_tx = Transaction {
+            version: transaction::Version::ONE,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_str(txid).unwrap(),
+                    vout: 0,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(99000),
+                script_pubkey: utxo.script_pubkey.clone(),
+            }],
+        };
+        let spend = |priv_hex: &str| -> Transaction {
+            let tx_hex = inheritance_spend(
+                priv_hex,
+                &owner_hex,
+                &pubkey_hex,
+                TimelockType::RelativeHeight,
+                26280,
+                script_type,
+                &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+                vec![Prevout {
+                    txid: txid.to_string(),
+                    vout: 0,
+                    amount: 0.001,
+                }],
+            );
+            consensus::deserialize(&Vec::<u8>::from_hex(&tx_hex).unwrap()).unwrap()
+        };
+
+        // the owner spends right away, the transaction as given
+        let tx = spend(&owner_priv_hex);
+        assert_eq!(tx.input[0].sequence, Sequence::MAX);
+        assert_eq!(tx.version, transaction::Version::ONE);
+        verify_script_spend(&tx, 0, std::slice::from_ref(&utxo));
+
+        // the heir with the sequence of the timelock, and the owner's signature left empty
+        let tx = spend(&priv_hex);
+        assert_eq!(tx.input[0].sequence, Sequence::from_height(26280));
+        assert!(tx.input[0].witness.nth(1).unwrap().is_empty());
+        verify_script_spend(&tx, 0, std::slice::from_ref(&utxo));
+
+        let stranger = HDWallet::new(1, None).bip84_priv_hex();
+        assert!(std::panic::catch_unwind(|| spend(&stranger)).is_err());
+    }
+}
+
+#[test]
+fn test_fee_bump() {
+    let wallet = HDWallet::new(
+        1,
+        Some("visit frame clay clap often dance pair cousin peanut thumb fine foster".to_string()),
+    );
+    let recipient = HDWallet::new(
+        1,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    )
+    .bip86_address();
+    let address = wallet.bip84_address();
+    let priv_hex = wallet.bip84_priv_hex();
+    let script_pubkey = |address: &str| {
+        Address::from_str(address)
+            .unwrap()
+            .assume_checked()
+            .script_pubkey()
+    };
+    let prevout = |vout: u32| Prevout {
+        txid: "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b".to_string(),
+        vout,
+        amount: 0.001,
+    };
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::from_str(&prevout(0).txid).unwrap(),
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }],
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(50000),
+                script_pubkey: script_pubkey(&recipient),
+            },
+            TxOut {
+                value: Amount::from_sat(49000),
+                script_pubkey: script_pubkey(&address),
+            },
+        ],
+    };
+    let original = p2wpkh_sign(
+        &address,
+        &priv_hex,
+        &consensus::serialize(&unsigned_tx).as_hex().to_string(),
+        vec![prevout(0)],
+    );
+    let decode = |tx_hex: &str| {
+        consensus::deserialize::<Transaction>(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap()
+    };
+    let fee = |tx: &Transaction, inputs: u64| {
+        100000 * inputs - tx.output.iter().map(|o| o.value.to_sat()).sum::<u64>()
+    };
+    let rate = |tx: &Transaction, inputs: u64| fee(tx, inputs) as f64 / tx.vsize() as f64;
+
+    // the original pays 1000 sats, about 6.5 sat/vB
+    assert!(std::panic::catch_unwind(|| rbf_bump_fee(
+        &address,
+        &priv_hex,
+        &original,
+        vec![prevout(0)],
+        5.0,
+        vec![]
+    ))
+    .is_err());
+
+    // reduce the change
+    let bumped = decode(&rbf_bump_fee(
+        &address,
+        &priv_hex,
+        &original,
+        vec![prevout(0)],
+        20.0,
+        vec![],
+    ));
+    assert_eq!(bumped.input.len(), 1);
+    assert_eq!(
+        bumped.input[0].previous_output,
+        unsigned_tx.input[0].previous_output
+    );
+    assert!(bumped.input[0].sequence.is_rbf());
+    assert_eq!(bumped.output[0], unsigned_tx.output[0]);
+    assert_eq!(bumped.output.len(), 2);
+    assert!(rate(&bumped, 1) >= 20.0);
+    assert!(rate(&bumped, 1) < 20.5);
+    assert_eq!(bumped.input[0].witness.len(), 2);
+
+    // the change would be dust
+    let bumped = decode(&rbf_bump_fee(
+        &address,
+        &priv_hex,
+        &original,
+        vec![prevout(0)],
+        326.0,
+        vec![],
+    ));
+    assert_eq!(bumped.output, vec![unsigned_tx.output[0].clone()]);
+    assert_eq!(fee(&bumped, 1), 50000);
+
+    // not enough without another input
+    assert!(std::panic::catch_unwind(|| rbf_bump_fee(
+        &address,
+        &priv_hex,
+        &original,
+        vec![prevout(0)],
+        420.0,
+        vec![]
+    ))
+    .is_err());
+    let bumped = decode(&rbf_bump_fee(
+        &address,
+        &priv_hex,
+        &original,
+        vec![prevout(0)],
+        420.0,
+        vec![prevout(1)],
+    ));
+    assert_eq!(bumped.input.len(), 2);
+    assert_eq!(bumped.input[1].previous_output.vout, 1);
+    assert_eq!(bumped.output.len(), 2);
+    assert_eq!(bumped.output[0], unsigned_tx.output[0]);
+    assert!(rate(&bumped, 2) >= 420.0);
+
+    // a child of the change bringing the package to 30 sat/vB
+    let parent = decode(&original);
+    let child = decode(&cpfp_child(
+        &address,
+        &priv_hex,
+        &original,
+        vec![prevout(0)],
+        30.0,
+    ));
+    assert_eq!(child.input.len(), 1);
+    assert_eq!(
+        child.input[0].previous_output,
+        OutPoint {
+            txid: parent.txid(),
+            vout: 1
+        }
+    );
+    assert_eq!(child.output.len(), 1);
+    assert_eq!(child.output[0].script_pubkey, script_pubkey(&address));
+    let child_fee = 49000 - child.output[0].value.to_sat();
+    let package_rate = (1000 + child_fee) as f64 / (parent.vsize() + child.vsize()) as f64;
+    assert!(package_rate >= 30.0);
+    assert!(package_rate < 30.5);
+    assert!(std::panic::catch_unwind(|| cpfp_child(
+        &address,
+        &priv_hex,
+        &original,
+        vec![prevout(0)],
+        500.0
+    ))
+    .is_err());
+}
+
+#[test]
+fn test_silent_payments() {
+    let wallet = HDWallet::new(
+        1,
+        Some("visit frame clay clap often dance pair cousin peanut thumb fine foster".to_string()),
+    );
+    let sender = HDWallet::new(
+        1,
+        Some(
+            "work man father plunge mystery proud hollow address reunion sauce theory bonus"
+                .to_string(),
+        ),
+    );
+    let sp_wallet = SilentPaymentWallet::new(&wallet);
+    let address = sp_wallet.address(None);
+    let labeled = sp_wallet.address(Some(1));
+    assert!(address.starts_with("tsp1q"));
+    assert_eq!(address.len(), 117);
+    assert_ne!(address, labeled);
+    assert_eq!(address[..57], labeled[..57]);
+    assert!(SilentPaymentWallet::new(&HDWallet::new(
+        0,
+        Some("visit frame clay clap often dance pair cousin peanut thumb fine foster".to_string())
+    ))
+    .address(None)
+    .starts_with("sp1q"));
+
+    let script_pubkey = |address: &str| {
+        Address::from_str(address)
+            .unwrap()
+            .assume_checked()
+            .script_pubkey()
+    };
+    let txid = "2ad1e0a0b2fd4fd5fca5f5dd0d4b9ab1d7e4b8f2d6a1b2c3d4e5f60718293a4b";
+    let p2wsh = ScriptBuf::new_p2wsh(&ScriptBuf::from_bytes(vec![0x51]).wscript_hash());
+    let input =
+        |vout: u32, priv_hex: Option<String>, script_pubkey: &ScriptBuf| SilentPaymentInput {
+            txid: txid.to_string(),
+            vout,
+            priv_hex,
+            script_pubkey: script_pubkey.to_hex_string(),
+        };
+    let inputs = || {
+        vec![
+            input(
+                2,
+                Some(sender.bip84_priv_hex()),
+                &script_pubkey(&sender.bip84_address()),
+            ),
+            input(
+                1,
+                Some(sender.bip86_tweaked_priv_hex(None)),
+                &script_pubkey(&sender.bip86_address()),
+            ),
+            // a P2WSH input, whose outpoint is the smallest
+            input(0, None, &p2wsh),
+        ]
+    };
+    let outputs = silent_payment_outputs(
+        inputs(),
+        vec![address.clone(), labeled.clone(), address.clone()],
+    )
+    .unwrap();
+    assert_eq!(outputs.len(), 3);
+    assert!(outputs.iter().all(|o| o.starts_with("tb1p")));
+    assert_ne!(outputs[0], outputs[2]);
+
+    let secp = Secp256k1::new();
+    let p2wpkh_pubkey = SecretKey::from_str(&sender.bip84_priv_hex())
+        .unwrap()
+        .public_key(&secp)
+        .serialize();
+    let tx_in = |vout: u32, witness: Vec<Vec<u8>>| TxIn {
+        previous_output: OutPoint {
+            txid: Txid::from_str(txid).unwrap(),
+            vout,
+        },
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+        witness: Witness::from_slice(&witness),
+    };
+    let mut tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![
+            tx_in(2, vec![vec![0; 72], p2wpkh_pubkey.to_vec()]),
+            tx_in(1, vec![vec![0; 64]]),
+            tx_in(0, vec![vec![0; 72], vec![0x51]]),
+        ],
+        output: vec![TxOut {
+            value: Amount::from_sat(10000),
+            script_pubkey: script_pubkey(&sender.bip84_address()),
+        }],
+    };
+    tx.output.extend(outputs.iter().map(|o| TxOut {
+        value: Amount::from_sat(20000),
+        script_pubkey: script_pubkey(o),
+    }));
+    let tx_hex = consensus::serialize(&tx).as_hex().to_string();
+    let prevout_script_pubkeys: Vec<String> =
+        inputs().into_iter().map(|i| i.script_pubkey).collect();
+
+    let found = sp_wallet.scan(&tx_hex, prevout_script_pubkeys.clone(), vec![1]);
+    assert_eq!(found.len(), 3);
+    for output in &found {
+        let key = SecretKey::from_str(&output.priv_hex)
+            .unwrap()
+            .x_only_public_key(&secp)
+            .0;
+        assert_eq!(
+            tx.output[output.vout as usize].script_pubkey,
+            ScriptBuf::new_p2tr_tweaked(key.dangerous_assume_tweaked())
+        );
+    }
+    let mut labels: Vec<Option<u32>> = found.iter().map(|o| o.label).collect();
+    labels.sort();
+    assert_eq!(labels, vec![None, None, Some(1)]);
+    assert!(found.iter().all(|o| o.vout > 0));
+
+    // scanning stops at the payment to a label not given
+    assert_eq!(
+        sp_wallet
+            .scan(&tx_hex, prevout_script_pubkeys.clone(), vec![])
+            .len(),
+        1
+    );
+    // another wallet
+    assert!(SilentPaymentWallet::new(&sender)
+        .scan(&tx_hex, prevout_script_pubkeys.clone(), vec![1])
+        .is_empty());
+    // outputs depend on every outpoint
+    let mut other_inputs = inputs();
+    other_inputs[2].vout = 3;
+    assert_ne!(
+        silent_payment_outputs(other_inputs, vec![address.clone()]).unwrap()[0],
+        outputs[0]
+    );
+    // spending a segwit output of a version above 1, the sender refuses and the receiver skips
+    let segwit_v2 = ScriptBuf::from_hex(&format!("5220{}", "11".repeat(32))).unwrap();
+    let mut future_inputs = inputs();
+    future_inputs[2] = input(0, None, &segwit_v2);
+    assert!(silent_payment_outputs(future_inputs, vec![address.clone()]).is_none());
+    let mut future_prevouts = prevout_script_pubkeys.clone();
+    future_prevouts[2] = segwit_v2.to_hex_string();
+    assert!(sp_wallet.scan(&tx_hex, future_prevouts, vec![1]).is_empty());
+    // mixed case
+    let mut invalid = address.clone();
+    invalid.replace_range(..1, "T");
+    assert!(std::panic::catch_unwind(|| silent_payment_outputs(inputs(), vec![invalid])).is_err());
+}
+
+#[test]
+fn test_silent_payments_bip352() {
+    // send_and_receive_test_vectors.json of BIP-352
+    let recipient = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+    let txid_a = "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16";
+    let txid_b = "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d";
+    let key_a = "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1";
+    let key_b = "93f5ed907ad5b2bdbbdcb5d9116ebc0a4e1f92f910d5260237fa45a9408aad16";
+    let secp = Secp256k1::new();
+    let input = |txid: &str, vout: u32, priv_hex: &str, is_taproot: bool| {
+        let key = SecretKey::from_str(priv_hex).unwrap();
+        let script_pubkey = match is_taproot {
+            true => ScriptBuf::new_p2tr_tweaked(
+                key.x_only_public_key(&secp).0.dangerous_assume_tweaked(),
+            ),
+            false => ScriptBuf::new_p2wpkh(
+                &PublicKey::new(key.public_key(&secp))
+                    .wpubkey_hash()
+                    .unwrap(),
+            ),
+        };
+        SilentPaymentInput {
+            txid: txid.to_string(),
+            vout,
+            priv_hex: Some(priv_hex.to_string()),
+            script_pubkey: script_pubkey.to_hex_string(),
+        }
+    };
+    let output_keys = |inputs: Vec<SilentPaymentInput>, recipients: Vec<String>| {
+        silent_payment_outputs(inputs, recipients)
+            .unwrap()
+            .iter()
+            .map(|output| {
+                Address::from_str(output)
+                    .unwrap()
+                    .assume_checked()
+                    .script_pubkey()
+                    .as_bytes()[2..]
+                    .to_lower_hex_string()
+            })
+            .collect::<Vec<_>>()
+    };
+    let output_key = |inputs: Vec<SilentPaymentInput>| {
+        output_keys(inputs, vec![recipient.to_string()])[0].clone()
+    };
+    let cases = [
+        // simple send: two inputs
+        (
+            vec![
+                input(txid_a, 0, key_a, false),
+                input(txid_b, 0, key_b, false),
+            ],
+            "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+        ),
+        // simple send: two inputs, order reversed
+        (
+            vec![
+                input(txid_b, 0, key_b, false),
+                input(txid_a, 0, key_a, false),
+            ],
+            "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1",
+        ),
+        // simple send: two inputs from the same transaction
+        (
+            vec![
+                input(txid_a, 3, key_a, false),
+                input(txid_a, 7, key_b, false),
+            ],
+            "79e71baa2ba3fc66396de3a04f168c7bf24d6870ec88ca877754790c1db357b6",
+        ),
+        (
+            vec![
+                input(txid_b, 7, key_b, false),
+                input(txid_b, 3, key_a, false),
+            ],
+            "f4c2da807f89cb1501f1a77322a895acfb93c28e08ed2724d2beb8e44539ba38",
+        ),
+        // single recipient: multiple UTXOs from the same public key
+        (
+            vec![
+                input(txid_a, 0, key_a, false),
+                input(txid_b, 0, key_a, false),
+            ],
+            "548ae55c8eec1e736e8d3e520f011f1f42a56d166116ad210b3937599f87f566",
+        ),
+        // single recipient: taproot only inputs with even y-values
+        (
+            vec![
+                input(txid_a, 0, key_a, true),
+                input(
+                    txid_b,
+                    0,
+                    "fc8716a97a48ba9a05a98ae47b5cd201a25a7fd5d8b73c203c5f7b6b6b3b6ad7",
+                    true,
+                ),
+            ],
+            "de88bea8e7ffc9ce1af30d1132f910323c505185aec8eae361670421e749a1fb",
+        ),
+        // single recipient: taproot only with mixed even/odd y-values
+        (
+            vec![
+                input(txid_a, 0, key_a, true),
+                input(
+                    txid_b,
+                    0,
+                    "1d37787c2b7116ee983e9f9c13269df29091b391c04db94239e0d2bc2182c3bf",
+                    true,
+                ),
+            ],
+            "77cab7dd12b10259ee82c6ea4b509774e33e7078e7138f568092241bf26b99f1",
+        ),
+        // single recipient: taproot input with even y and non-taproot input
+        (
+            vec![
+                input(txid_a, 0, key_a, true),
+                input(
+                    txid_b,
+                    0,
+                    "8d4751f6e8a3586880fb66c19ae277969bd5aa06f61c4ee2f1e2486efdf666d3",
+                    false,
+                ),
+            ],
+            "30523cca96b2a9ae3c98beb5e60f7d190ec5bc79b2d11a0b2d4d09a608c448f0",
+        ),
+        // single recipient: taproot input with odd y and non-taproot input
+        (
+            vec![
+                input(
+                    txid_a,
+                    0,
+                    "1d37787c2b7116ee983e9f9c13269df29091b391c04db94239e0d2bc2182c3bf",
+                    true,
+                ),
+                input(
+                    txid_b,
+                    0,
+                    "8d4751f6e8a3586880fb66c19ae277969bd5aa06f61c4ee2f1e2486efdf666d3",
+                    false,
+                ),
+            ],
+            "359358f59ee9e9eec3f00bdf4882570fd5c182e451aa2650b788544aff012a3a",
+        ),
+    ];
+    for (inputs, expected) in cases {
+        assert_eq!(output_key(inputs), expected);
+    }
+
+    // receiving the simple send
+    let scan_key =
+        SecretKey::from_str("0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c")
+            .unwrap();
+    let spend_key =
+        SecretKey::from_str("9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3")
+            .unwrap();
+    let sp_wallet = SilentPaymentWallet::from_keys(Network::Bitcoin, scan_key, spend_key);
+    assert_eq!(sp_wallet.address(None), recipient);
+    let tweak = "f438b40179a3c4262de12986c0e6cce0634007cdc79c1dcd3e20b9ebc2e7eef6";
+
+    let pubkey =
+        |priv_hex: &str| PublicKey::new(SecretKey::from_str(priv_hex).unwrap().public_key(&secp));
+    let tx_in = |txid: &str, vout: u32, script_sig: ScriptBuf, witness: Vec<Vec<u8>>| TxIn {
+        previous_output: OutPoint {
+            txid: Txid::from_str(txid).unwrap(),
+            vout,
+        },
+        script_sig,
+        sequence: Sequence::MAX,
+        witness: Witness::from_slice(&witness),
+    };
+    let p2pkh = |txid: &str, priv_hex: &str| {
+        let pubkey = pubkey(priv_hex);
+        (
+            tx_in(
+                txid,
+                0,
+                script::Builder::new()
+                    .push_slice([0; 71])
+                    .push_key(&pubkey)
+                    .into_script(),
+                vec![],
+            ),
+            ScriptBuf::new_p2pkh(&pubkey.pubkey_hash()),
+        )
+    };
+    let p2sh_p2wpkh = |txid: &str, priv_hex: &str| {
+        let pubkey = pubkey(priv_hex);
+        let redeem_script = ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash().unwrap());
+        let mut script_sig = script::PushBytesBuf::new();
+        script_sig
+            .extend_from_slice(redeem_script.as_bytes())
+            .unwrap();
+        (
+            tx_in(
+                txid,
+                0,
+                script::Builder::new().push_slice(script_sig).into_script(),
+                vec![vec![0; 72], pubkey.to_bytes()],
+            ),
+            ScriptBuf::new_p2sh(&redeem_script.script_hash()),
+        )
+    };
+    let p2wpkh = |txid: &str, priv_hex: &str| {
+        let pubkey = pubkey(priv_hex);
+        (
+            tx_in(
+                txid,
+                0,
+                ScriptBuf::new(),
+                vec![vec![0; 72], pubkey.to_bytes()],
+            ),
+            ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash().unwrap()),
+        )
+    };
+    // script path spend of a taproot output whose internal key is the NUMS point of BIP-341
+    let nums_script_path = {
+        let mut control_block = vec![0xc0];
+        control_block.extend(
+            Vec::<u8>::from_hex("50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0")
+                .unwrap(),
+        );
+        (
+            tx_in(
+                txid_b,
+                1,
+                ScriptBuf::new(),
+                vec![vec![0; 64], vec![0x51], control_block],
+            ),
+            ScriptBuf::new_p2tr_tweaked(
+                pubkey(key_a)
+                    .inner
+                    .x_only_public_key()
+                    .0
+                    .dangerous_assume_tweaked(),
+            ),
+        )
+    };
+    let scan = |inputs: Vec<(TxIn, ScriptBuf)>, output_key: &str, labels: Vec<u32>| {
+        let tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: inputs.iter().map(|(input, _)| input.clone()).collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(1000),
+                script_pubkey: ScriptBuf::from_hex(&format!("5120{output_key}")).unwrap(),
+            }],
+        };
+        sp_wallet.scan(
+            &consensus::serialize(&tx).as_hex().to_string(),
+            inputs
+                .iter()
+                .map(|(_, script_pubkey)| script_pubkey.to_hex_string())
+                .collect(),
+            labels,
+        )
+    };
+    let output_key = "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1";
+    for inputs in [
+        vec![p2pkh(txid_a, key_a), p2pkh(txid_b, key_b)],
+        vec![p2sh_p2wpkh(txid_a, key_a), p2wpkh(txid_b, key_b)],
+        vec![
+            p2pkh(txid_a, key_a),
+            nums_script_path.clone(),
+            p2wpkh(txid_b, key_b),
+        ],
+    ] {
+        let found = scan(inputs, output_key, vec![]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].vout, 0);
+        assert_eq!(found[0].label, None);
+        assert_eq!(found[0].tweak, tweak);
+        let key = SecretKey::from_str(&found[0].priv_hex).unwrap();
+        assert_eq!(
+            key.x_only_public_key(&secp)
+                .0
+                .serialize()
+                .to_lower_hex_string(),
+            output_key
+        );
+    }
+
+    // P2PKH with malleated script sig, the key is read from its bytes
+    let (mut malleated, p2pkh_script_pubkey) = p2pkh(txid_a, key_a);
+    let mut script_sig = script::Builder::new()
+        .push_opcode(opcodes::OP_0)
+        .push_opcode(opcodes::all::OP_DROP)
+        .into_bytes();
+    script_sig.extend(malleated.script_sig.as_bytes());
+    script_sig.push(opcodes::all::OP_NOP.to_u8());
+    malleated.script_sig = ScriptBuf::from_bytes(script_sig);
+    let found = scan(
+        vec![(malleated, p2pkh_script_pubkey), p2pkh(txid_b, key_b)],
+        output_key,
+        vec![],
+    );
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].tweak, tweak);
+
+    // multiple outputs to the same recipient, k = 0 and 1
+    let key_c = "0378e95685b74565fa56751b84a32dfd18545d10d691641b8372e32164fad66a";
+    let inputs = || {
+        vec![
+            input(txid_a, 0, key_a, false),
+            input(txid_b, 0, key_c, false),
+        ]
+    };
+    let both = vec![
+        "f207162b1a7abc51c42017bef055e9ec1efc3d3567cb720357e2b84325db33ac".to_string(),
+        "e976a58fbd38aeb4e6093d4df02e9c1de0c4513ae0c588cef68cda5b2f8834ca".to_string(),
+    ];
+    assert_eq!(
+        output_keys(inputs(), vec![recipient.to_string(), recipient.to_string()]),
+        both
+    );
+    let scan = |output_keys: &[String], labels: Vec<u32>| {
+        let (a, a_script_pubkey) = p2pkh(txid_a, key_a);
+        let (c, c_script_pubkey) = p2pkh(txid_b, key_c);
+        let tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![a, c],
+            output: output_keys
+                .iter()
+                .map(|key| TxOut {
+                    value: Amount::from_sat(1000),
+                    script_pubkey: ScriptBuf::from_hex(&format!("5120{key}")).unwrap(),
+                })
+                .collect(),
+        };
+        sp_wallet.scan(
+            &consensus::serialize(&tx).as_hex().to_string(),
+            vec![
+                a_script_pubkey.to_hex_string(),
+                c_script_pubkey.to_hex_string(),
+            ],
+            labels,
+        )
+    };
+    let found = scan(&[both[1].clone(), both[0].clone()], vec![]);
+    assert_eq!(found.iter().map(|o| o.vout).collect::<Vec<_>>(), vec![1, 0]);
+    // the k = 1 output alone isn't found, scanning stops at k = 0
+    assert!(scan(&both[1..], vec![]).is_empty());
+
+    // receiving with labels: even and odd parity, large label integer
+    for (label, address, output_key) in [
+        (
+            2,
+            "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjex54dmqmmv6rw353tsuqhs99ydvadxzrsy9nuvk74epvee55drs734pqq",
+            "d014d4860f67d607d60b1af70e0ee236b99658b61bb769832acbbe87c374439a",
+        ),
+        (
+            3,
+            "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqsg59z2rppn4qlkx0yz9sdltmjv3j8zgcqadjn4ug98m3t6plujsq9qvu5n",
+            "67626aebb3c4307cf0f6c39ca23247598fabf675ab783292eb2f81ae75ad1f8c",
+        ),
+        (
+            1001337,
+            "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgq7c2zfthc6x3a5yecwc52nxa0kfd20xuz08zyrjpfw4l2j257yq6qgnkdh5",
+            "7efa60ce78ac343df8a013a2027c6c5ef29f9502edcbd769d2c21717fecc5951",
+        ),
+    ] {
+        assert_eq!(sp_wallet.address(Some(label)), address);
+        assert_eq!(
+            output_keys(inputs(), vec![address.to_string()]),
+            vec![output_key]
+        );
+        let output_keys = [output_key.to_string()];
+        assert!(scan(&output_keys, vec![]).is_empty());
+        let found = scan(&output_keys, vec![1, label]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].label, Some(label));
+        let key = SecretKey::from_str(&found[0].priv_hex).unwrap();
+        assert_eq!(
+            key.x_only_public_key(&secp)
+                .0
+                .serialize()
+                .to_lower_hex_string(),
+            output_key
+        );
+    }
+}
+
+#[test]
+fn test_slip39() {
+    // test vectors from SLIP-39
+    let share = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard";
+    assert!(slip39_validate_share(share));
+    assert_eq!(
+        slip39_recover(vec![share.to_string()], "TREZOR").unwrap(),
+        "bb54aac4b89dc868ba37d9cc21b2cece"
+    );
+    assert!(!slip39_validate_share(
+        "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney"
+    ));
+    assert_eq!(
+        slip39_recover(
+            vec![
+                "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed".to_string(),
+                "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking".to_string(),
+            ],
+            "TREZOR"
+        )
+        .unwrap(),
+        "b43ceb7e57a0ea8766221624d01b0864"
+    );
+    assert!(is_slip39_word("academic"));
+    assert!(!is_slip39_word("abandon"));
+
+    let groups = slip39_generate(
+        "bb54aac4b89dc868ba37d9cc21b2cece",
+        "TREZOR",
+        2,
+        vec![
+            Slip39Group {
+                member_threshold: 1,
+                member_count: 1,
+            },
+            Slip39Group {
+                member_threshold: 2,
+                member_count: 3,
+            },
+            Slip39Group {
+                member_threshold: 3,
+                member_count: 5,
+            },
+        ],
+        0,
+    );
+    assert_eq!(groups[2].len(), 5);
+    assert!(groups.iter().flatten().all(|s| slip39_validate_share(s)));
+    let shares = vec![
+        groups[2][4].clone(),
+        groups[1][2].clone(),
+        groups[2][0].clone(),
+        groups[1][0].clone(),
+        groups[2][1].clone(),
+    ];
+    assert_eq!(
+        slip39_recover(shares, "TREZOR").unwrap(),
+        "bb54aac4b89dc868ba37d9cc21b2cece"
+    );
+
+    // invalid shares, shares of different secrets and too few shares recover nothing
+    assert!(slip39_recover(vec![], "TREZOR").is_none());
+    assert!(slip39_recover(vec!["duckling enlarge".to_string()], "TREZOR").is_none());
+    assert!(slip39_recover(
+        vec![
+            share.to_string(),
+            groups[1][0].clone(),
+            groups[2][0].clone()
+        ],
+        "TREZOR"
+    )
+    .is_none());
+    assert!(slip39_recover(
+        vec![
+            groups[1][0].clone(),
+            groups[2][0].clone(),
+            groups[2][1].clone()
+        ],
+        "TREZOR"
+    )
+    .is_none());
+
+    let two_of_three = || {
+        vec![Slip39Group {
+            member_threshold: 2,
+            member_count: 3,
+        }]
+    };
+    let wallet = HDWallet::new_slip39(0);
+    let groups = wallet.slip39_shares("", 1, two_of_three(), 0).unwrap();
+    // 16 bytes secrets give the 20 words shares of hardware wallets
+    assert!(groups[0].iter().all(|s| s.split(' ').count() == 20));
+    let restored = HDWallet::from_slip39(0, groups[0][1..].to_vec(), "").unwrap();
+    assert_eq!(restored.export_master_priv(), wallet.export_master_priv());
+    assert_eq!(restored.bip86_address(), wallet.bip86_address());
+    assert!(HDWallet::from_slip39(0, groups[0][..1].to_vec(), "").is_none());
+
+    // the seed is stored, so a restored wallet still splits it
+    let encrypted = wallet.encrypt("password", "");
+    let restored = HDWallet::from_encrypted(&encrypted, "password", None).unwrap();
+    assert_eq!(restored.export_master_priv(), wallet.export_master_priv());
+    let groups = restored
+        .slip39_shares("secret", 1, two_of_three(), 0)
+        .unwrap();
+    let restored = HDWallet::from_slip39(
+        0,
+        vec![groups[0][0].clone(), groups[0][2].clone()],
+        "secret",
+    )
+    .unwrap();
+    assert_eq!(restored.export_master_priv(), wallet.export_master_priv());
+
+    // 32 bytes secrets give 33 words shares
+    let secret = "bb54aac4b89dc868ba37d9cc21b2cecebb54aac4b89dc868ba37d9cc21b2cece";
+    let groups = slip39_generate(secret, "", 1, two_of_three(), 0);
+    let wallet = HDWallet::from_slip39(1, groups[0][..2].to_vec(), "").unwrap();
+    let groups = wallet.slip39_shares("", 1, two_of_three(), 0).unwrap();
+    assert!(groups[0].iter().all(|s| s.split(' ').count() == 33));
+    assert_eq!(slip39_recover(groups[0][1..].to_vec(), "").unwrap(), secret);
+
+    // the 64 bytes seed of a BIP39 mnemonic and master keys can't be split
+    let mnemonic = "work man father plunge mystery proud hollow address reunion sauce theory bonus";
+    let wallet = HDWallet::new(0, Some(mnemonic.to_string()));
+    assert!(wallet.slip39_shares("", 1, two_of_three(), 0).is_none());
+    let wallet = HDWallet::from_master_priv(&wallet.export_master_priv());
+    assert!(wallet.slip39_shares("", 1, two_of_three(), 0).is_none());
+
+    // the master secret is the BIP32 seed, as in the SLIP-39 test vectors
+    assert_eq!(
+        HDWallet::from_slip39(0, vec![share.to_string()], "TREZOR")
+            .unwrap()
+            .export_master_priv(),
+        "xprv9s21ZrQH143K4QViKpwKCpS2zVbz8GrZgpEchMDg6KME9HZtjfL7iThE9w5muQA4YPHKN1u5VM1w8D4pvnjxa2BmpGMfXr7hnRrRHZ93awZ"
+    );
+}
+
+#[test]
+fn test_bip85() {
+    // test vectors from BIP85
+    let wallet = HDWallet::from_master_priv("xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb");
+    assert_eq!(
+        bip85_entropy(&wallet.export_master_priv(), "m/83696968'/0'/0'"),
+        "efecfbccffea313214232d29e71563d941229afb4338c21f9517c41aaa0d16f00b83d2a09ef747e7a64e8e2bd5a14869e693da66ce94ac2da570ab7ee48618f7"
+    );
+    assert_eq!(
+        wallet.bip85_mnemonic(0, 12, 0),
+        "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose"
+    );
+    assert_eq!(
+        wallet.bip85_mnemonic(0, 18, 0),
+        "near account window bike charge season chef number sketch tomorrow excuse sniff circle vital hockey outdoor supply token"
+    );
+    assert_eq!(
+        wallet.bip85_mnemonic(0, 24, 0),
+        "puppy ocean match cereal symbol another shed magic wrap hammer bulb intact gadget divorce twin tonight reason outdoor destroy simple truth cigar social volcano"
+    );
+    assert_eq!(
+        wallet.bip85_wif(0),
+        "Kzyv4uF39d4Jrw2W7UryTHwZr1zQVNk4dAFyqE6BuMrMh1Za7uhp"
+    );
+    assert_eq!(
+        wallet.bip85_xprv(0),
+        "xprv9s21ZrQH143K2srSbCSg4m4kLvPMzcWydgmKEnMmoZUurYuBuYG46c6P71UGXMzmriLzCCBvKQWBUv3vPB3m1SATMhp3uEjXHJ42jFg7myX"
+    );
+    assert_eq!(
+        wallet.bip85_hex(64, 0),
+        "492db4698cf3b73a5a24998aa3e9d7fa96275d85724a91e71aa2d645442f878555d078fd1f1f67e368976f04137b1f7a0d19232136ca50c44614af72b5582a5c"
+    );
+    assert_eq!(wallet.bip85_base64_password(21, 0), "dKLoepugzdVJvdL56ogNV");
+
+    let japanese = wallet.bip85_mnemonic(1, 12, 0);
+    assert_eq!(japanese.split_whitespace().count(), 12);
+    assert!(!japanese.is_ascii());
+}
+
 #[tokio::test]
 async fn test_p2tr_sign() {
     // from
@@ -0,0 +1,427 @@
+use crate::multisig_wallet::NUMS_INTERNAL_KEY;
+use crate::signer::Prevout;
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::Hash;
+use bitcoin::hex::DisplayHex;
+use bitcoin::opcodes::all::{
+    OP_CHECKSIG, OP_CHECKSIGVERIFY, OP_CLTV, OP_CSV, OP_ENDIF, OP_IFDUP, OP_NOTIF,
+};
+use bitcoin::script::Builder;
+use bitcoin::secp256k1::{All, Keypair, Message, Secp256k1, SecretKey, XOnlyPublicKey};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::taproot::{LeafVersion, TapLeafHash, TaprootBuilder, TaprootSpendInfo};
+use bitcoin::{
+    absolute, consensus, ecdsa, taproot, transaction, Address, Amount, EcdsaSighashType, Network,
+    PublicKey, ScriptBuf, Sequence, TapSighashType, Transaction, TxOut, Witness,
+};
+use std::str::FromStr;
+
+/// Kind of timelock of a vault, the unit of its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelockType {
+    /// `OP_CHECKLOCKTIMEVERIFY` until a block height, below 500000000
+    AbsoluteHeight,
+    /// `OP_CHECKLOCKTIMEVERIFY` until a unix timestamp, from 500000000
+    AbsoluteTime,
+    /// `OP_CHECKSEQUENCEVERIFY` for a number of blocks after confirmation, up to 65535
+    RelativeHeight,
+    /// `OP_CHECKSEQUENCEVERIFY` for a number of seconds after confirmation, rounded up to
+    /// 512 seconds intervals, up to 33553920
+    RelativeTime,
+}
+
+/// Output type of a vault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelockScriptType {
+    P2wsh,
+    /// the single leaf of a taproot output whose internal key is the BIP-341 NUMS point, so
+    /// that the key can't spend through the key path before the timelock
+    P2tr,
+}
+
+/// Checked timelock of a vault
+#[derive(Debug, Clone, Copy)]
+enum Timelock {
+    Absolute(absolute::LockTime),
+    Relative(Sequence),
+}
+
+impl Timelock {
+    fn new(timelock_type: TimelockType, value: u32) -> Self {
+        // `0 CLTV` or `0 CSV` leaves an empty stack top, as miniscript `after` and `older` the
+        // value starts from 1
+        assert!(value > 0, "timelock of 0");
+        match timelock_type {
+            TimelockType::AbsoluteHeight => Timelock::Absolute(
+                absolute::LockTime::from_height(value).expect("invalid block height"),
+            ),
+            TimelockType::AbsoluteTime => {
+                Timelock::Absolute(absolute::LockTime::from_time(value).expect("invalid timestamp"))
+            }
+            TimelockType::RelativeHeight => Timelock::Relative(Sequence::from_height(
+                u16::try_from(value).expect("relative height above 65535"),
+            )),
+            TimelockType::RelativeTime => Timelock::Relative(
+                Sequence::from_seconds_ceil(value).expect("relative time too long"),
+            ),
+        }
+    }
+}
+
+/// `and_v(v:pk(K),after(n))` or `and_v(v:pk(K),older(n))` in miniscript, or
+/// `or_d(pk(O),and_v(v:pk(K),...))` when an owner `O` can spend at any time
+fn vault_script(
+    owner: Option<&PublicKey>,
+    pubkey: &PublicKey,
+    timelock: Timelock,
+    script_type: TimelockScriptType,
+) -> ScriptBuf {
+    let push_key = |builder: Builder, pubkey: &PublicKey| match script_type {
+        TimelockScriptType::P2wsh => builder.push_key(pubkey),
+        TimelockScriptType::P2tr => builder.push_x_only_key(&pubkey.inner.into()),
+    };
+    let builder = match owner {
+        Some(owner) => push_key(Builder::new(), owner)
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_IFDUP)
+            .push_opcode(OP_NOTIF),
+        None => Builder::new(),
+    };
+    let builder = push_key(builder, pubkey).push_opcode(OP_CHECKSIGVERIFY);
+    let builder = match timelock {
+        Timelock::Absolute(lock_time) => builder
+            .push_int(lock_time.to_consensus_u32() as i64)
+            .push_opcode(OP_CLTV),
+        Timelock::Relative(sequence) => builder
+            .push_int(sequence.to_consensus_u32() as i64)
+            .push_opcode(OP_CSV),
+    };
+    match owner {
+        Some(_) => builder.push_opcode(OP_ENDIF),
+        None => builder,
+    }
+    .into_script()
+}
+
+fn compressed_key(pubkey_hex: &str) -> PublicKey {
+    let pubkey = PublicKey::from_str(pubkey_hex).unwrap();
+    assert!(pubkey.compressed, "uncompressed public key");
+    pubkey
+}
+
+fn network(is_testnet: u8) -> Network {
+    match is_testnet {
+        0 => Network::Bitcoin,
+        1 => Network::Testnet,
+        _ => unreachable!(),
+    }
+}
+
+fn spend_info(secp: &Secp256k1<All>, script: ScriptBuf) -> TaprootSpendInfo {
+    TaprootBuilder::new()
+        .add_leaf(0, script)
+        .unwrap()
+        .finalize(secp, XOnlyPublicKey::from_str(NUMS_INTERNAL_KEY).unwrap())
+        .unwrap()
+}
+
+fn vault_address(
+    secp: &Secp256k1<All>,
+    script: &ScriptBuf,
+    script_type: TimelockScriptType,
+    network: Network,
+) -> Address {
+    match script_type {
+        TimelockScriptType::P2wsh => Address::p2wsh(script, network),
+        TimelockScriptType::P2tr => {
+            Address::p2tr_tweaked(spend_info(secp, script.clone()).output_key(), network)
+        }
+    }
+}
+
+/// ### Script of a timelocked vault
+///
+/// pubkey_hex:
+/// compressed public key able to spend once the timelock expires
+///
+/// value:
+/// height, timestamp, blocks or seconds depending on `timelock_type`
+///
+/// returns the witness script of P2WSH, or the tapscript leaf of P2TR, in hex
+pub fn timelock_script(
+    pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+) -> String {
+    let pubkey = compressed_key(pubkey_hex);
+    vault_script(
+        None,
+        &pubkey,
+        Timelock::new(timelock_type, value),
+        script_type,
+    )
+    .to_hex_string()
+}
+
+/// ### Address of a timelocked vault, see [`timelock_script`]
+pub fn timelock_address(
+    pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+    is_testnet: u8,
+) -> String {
+    let script = ScriptBuf::from_hex(&timelock_script(
+        pubkey_hex,
+        timelock_type,
+        value,
+        script_type,
+    ))
+    .unwrap();
+    vault_address(&Secp256k1::new(), &script, script_type, network(is_testnet)).to_string()
+}
+
+/// ### Spend outputs of a timelocked vault
+///
+/// priv_hex:
+/// private key of the vault, every input spends an output of the same vault
+///
+/// tx_hex:
+/// unsigned transaction in hex, its `nLockTime`, version and input `nSequence` are set as the
+/// timelock requires. An absolute timelock can only be spent in a block after it expires, a
+/// relative one once the outputs have that many confirmations or that much time.
+///
+/// tx_prevouts:
+/// responding prevouts of tx inputs
+///
+/// returns the signed transaction in hex
+pub fn timelock_spend(
+    priv_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+) -> String {
+    let secp = Secp256k1::new();
+    let private_key = SecretKey::from_str(priv_hex).unwrap();
+    let pubkey = PublicKey::new(private_key.public_key(&secp));
+    let timelock = Timelock::new(timelock_type, value);
+    let script = vault_script(None, &pubkey, timelock, script_type);
+    sign_vault_spend(
+        &private_key,
+        &script,
+        Some(timelock),
+        &[],
+        script_type,
+        tx_hex,
+        tx_prevouts,
+    )
+}
+
+/// Set the `nLockTime`, version and input `nSequence` of a transaction as the timelock requires
+fn apply_timelock(tx: &mut Transaction, timelock: Timelock) {
+    match timelock {
+        Timelock::Absolute(lock_time) => {
+            if !lock_time.is_implied_by(tx.lock_time) {
+                assert!(
+                    tx.lock_time == absolute::LockTime::ZERO
+                        || tx.lock_time.is_same_unit(lock_time),
+                    "nLockTime of another unit"
+                );
+                tx.lock_time = lock_time;
+            }
+            // a final sequence disables nLockTime
+            for input in tx.input.iter_mut() {
+                if !input.sequence.enables_absolute_lock_time() {
+                    input.sequence = Sequence::ENABLE_LOCKTIME_NO_RBF;
+                }
+            }
+        }
+        Timelock::Relative(sequence) => {
+            // BIP68 relative locktimes apply from version 2
+            if tx.version < transaction::Version::TWO {
+                tx.version = transaction::Version::TWO;
+            }
+            for input in tx.input.iter_mut() {
+                input.sequence = sequence;
+            }
+        }
+    }
+}
+
+/// Sign every input of a transaction spending outputs of the vault script
+///
+/// timelock:
+/// applied to the transaction first, `None` when the owner spends
+///
+/// dissatisfactions:
+/// witness elements above the signature, the empty signature of the owner when the heir spends
+fn sign_vault_spend(
+    private_key: &SecretKey,
+    script: &ScriptBuf,
+    timelock: Option<Timelock>,
+    dissatisfactions: &[Vec<u8>],
+    script_type: TimelockScriptType,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+) -> String {
+    let mut tx =
+        consensus::deserialize::<Transaction>(&Vec::<u8>::from_hex(tx_hex).unwrap()).unwrap();
+    assert_eq!(tx.input.len(), tx_prevouts.len());
+    if let Some(timelock) = timelock {
+        apply_timelock(&mut tx, timelock);
+    }
+
+    let secp = Secp256k1::new();
+    let script_pubkey = vault_address(&secp, script, script_type, Network::Bitcoin).script_pubkey();
+    let utxos: Vec<TxOut> = tx_prevouts
+        .iter()
+        .map(|prevout| TxOut {
+            value: Amount::from_btc(prevout.amount).unwrap(),
+            script_pubkey: script_pubkey.clone(),
+        })
+        .collect();
+
+    let mut sighash_cache = SighashCache::new(&mut tx);
+    for (i, utxo) in utxos.iter().enumerate() {
+        let witness = match script_type {
+            TimelockScriptType::P2wsh => {
+                let hash_ty = EcdsaSighashType::All;
+                let sighash = sighash_cache
+                    .p2wsh_signature_hash(i, script, utxo.value, hash_ty)
+                    .unwrap();
+                let msg = Message::from_digest(sighash.to_byte_array());
+                let signature = ecdsa::Signature {
+                    sig: secp.sign_ecdsa(&msg, private_key),
+                    hash_ty,
+                };
+                let mut witness = vec![signature.to_vec()];
+                witness.extend_from_slice(dissatisfactions);
+                witness.push(script.to_bytes());
+                Witness::from_slice(&witness)
+            }
+            TimelockScriptType::P2tr => {
+                let hash_ty = TapSighashType::Default;
+                let leaf_hash = TapLeafHash::from_script(script, LeafVersion::TapScript);
+                let sighash = sighash_cache
+                    .taproot_script_spend_signature_hash(
+                        i,
+                        &Prevouts::All(&utxos),
+                        leaf_hash,
+                        hash_ty,
+                    )
+                    .unwrap();
+                let msg = Message::from_digest(sighash.to_byte_array());
+                let signature = taproot::Signature {
+                    sig: secp.sign_schnorr(&msg, &Keypair::from_secret_key(&secp, private_key)),
+                    hash_ty,
+                };
+                let control_block = spend_info(&secp, script.clone())
+                    .control_block(&(script.clone(), LeafVersion::TapScript))
+                    .unwrap();
+                let mut witness = vec![signature.to_vec()];
+                witness.extend_from_slice(dissatisfactions);
+                witness.push(script.to_bytes());
+                witness.push(control_block.serialize());
+                Witness::from_slice(&witness)
+            }
+        };
+        *sighash_cache.witness_mut(i).unwrap() = witness;
+    }
+
+    consensus::serialize(&sighash_cache.into_transaction())
+        .as_hex()
+        .to_string()
+}
+
+/// ### Script of an inheritance vault
+///
+/// the owner spends at any time, the heir once the timelock expires, as the miniscript
+/// `or_d(pk(OWNER),and_v(v:pk(HEIR),after(n)))` or with `older(n)`. With a relative timelock
+/// the owner restarts the heir's wait by moving the funds to a new output of the vault.
+///
+/// owner_pubkey_hex, heir_pubkey_hex:
+/// compressed public keys of the owner and the heir
+///
+/// returns the witness script of P2WSH, or the tapscript leaf of P2TR, in hex
+pub fn inheritance_script(
+    owner_pubkey_hex: &str,
+    heir_pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+) -> String {
+    let owner = compressed_key(owner_pubkey_hex);
+    let heir = compressed_key(heir_pubkey_hex);
+    assert_ne!(owner, heir, "owner and heir keys are the same");
+    let timelock = Timelock::new(timelock_type, value);
+    vault_script(Some(&owner), &heir, timelock, script_type).to_hex_string()
+}
+
+/// ### Address of an inheritance vault, see [`inheritance_script`]
+pub fn inheritance_address(
+    owner_pubkey_hex: &str,
+    heir_pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+    is_testnet: u8,
+) -> String {
+    let script = ScriptBuf::from_hex(&inheritance_script(
+        owner_pubkey_hex,
+        heir_pubkey_hex,
+        timelock_type,
+        value,
+        script_type,
+    ))
+    .unwrap();
+    vault_address(&Secp256k1::new(), &script, script_type, network(is_testnet)).to_string()
+}
+
+/// ### Spend outputs of an inheritance vault
+///
+/// priv_hex:
+/// private key of the owner or of the heir, every input spends an output of the same vault.
+/// The owner's transaction is signed as given, the heir's gets the `nLockTime`, version and
+/// `nSequence` the timelock requires as with [`timelock_spend`].
+///
+/// returns the signed transaction in hex
+#[allow(clippy::too_many_arguments)]
+pub fn inheritance_spend(
+    priv_hex: &str,
+    owner_pubkey_hex: &str,
+    heir_pubkey_hex: &str,
+    timelock_type: TimelockType,
+    value: u32,
+    script_type: TimelockScriptType,
+    tx_hex: &str,
+    tx_prevouts: Vec<Prevout>,
+) -> String {
+    let secp = Secp256k1::new();
+    let private_key = SecretKey::from_str(priv_hex).unwrap();
+    let pubkey = PublicKey::new(private_key.public_key(&secp));
+    let owner = compressed_key(owner_pubkey_hex);
+    let heir = compressed_key(heir_pubkey_hex);
+    let timelock = Timelock::new(timelock_type, value);
+    let script = vault_script(Some(&owner), &heir, timelock, script_type);
+
+    let (timelock, dissatisfactions) = if pubkey == owner {
+        (None, vec![])
+    } else if pubkey == heir {
+        // the empty signature fails the owner's `CHECKSIG` without failing the script
+        (Some(timelock), vec![vec![]])
+    } else {
+        panic!("key of neither the owner nor the heir")
+    };
+    sign_vault_spend(
+        &private_key,
+        &script,
+        timelock,
+        &dissatisfactions,
+        script_type,
+        tx_hex,
+        tx_prevouts,
+    )
+}